@@ -1,8 +1,6 @@
-use actix_web::http::header::ContentType;
-use actix_web::http::{Method, header};
-use actix_web::web::Html;
+use actix_web::http::{Method, StatusCode, header};
 use actix_web::{
-    App, HttpRequest, HttpResponse, HttpServer, Responder, Result, get, middleware, web,
+    App, Either, HttpRequest, HttpResponse, HttpServer, Responder, Result, get, middleware, web,
 };
 use rinja::Template;
 use serde::Deserialize;
@@ -76,7 +74,7 @@ enum Lang {
 }
 
 /// This is your "Error: 404 - not found" handler
-async fn not_found_handler(req: HttpRequest) -> Result<impl Responder> {
+async fn not_found_handler(req: HttpRequest) -> impl Responder {
     // It uses a rinja template to display its content.
     // The member `req` contains the request, and is used e.g. to generate URLs in our template.
     // The member `lang` is used by "_layout.html" which "404.html" extends. Even though it
@@ -90,15 +88,15 @@ async fn not_found_handler(req: HttpRequest) -> Result<impl Responder> {
     }
 
     if req.method() == Method::GET {
+        // Because of the `with-actix-web` feature, the template implements `Responder`, so it can
+        // be returned as is, or like in here, customized with a different status code.
         let tmpl = Tmpl {
             req,
             lang: Lang::default(),
         };
-        Ok(HttpResponse::NotFound()
-            .insert_header(ContentType::html())
-            .body(tmpl.render().map_err(|err| err.into_io_error())?))
+        Either::Left(tmpl.customize().with_status(StatusCode::NOT_FOUND))
     } else {
-        Ok(HttpResponse::MethodNotAllowed().finish())
+        Either::Right(HttpResponse::MethodNotAllowed().finish())
     }
 }
 
@@ -130,7 +128,7 @@ async fn index_handler(
     req: HttpRequest,
     path: web::Path<(Lang,)>,
     web::Query(query): web::Query<IndexHandlerQuery>,
-) -> Result<impl Responder> {
+) -> impl Responder {
     // Same as in `not_found_handler`, we have `req` to build URLs in the template, and
     // `lang` to select the display language. In the template we both use `{% match lang %}` and
     // `{% if lang !=`, the former to select the text of a specific language, e.g. in the `<title>`;
@@ -149,14 +147,11 @@ async fn index_handler(
     }
 
     let (lang,) = path.into_inner();
-    let template = Tmpl {
+    Tmpl {
         req,
         lang,
         name: query.name,
-    };
-    Ok(Html::new(
-        template.render().map_err(|err| err.into_io_error())?,
-    ))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -174,7 +169,7 @@ async fn greeting_handler(
     req: HttpRequest,
     path: web::Path<(Lang,)>,
     web::Query(query): web::Query<GreetingHandlerQuery>,
-) -> Result<impl Responder> {
+) -> impl Responder {
     #[derive(Debug, Template)]
     #[template(path = "greet.html")]
     struct Tmpl {
@@ -184,12 +179,9 @@ async fn greeting_handler(
     }
 
     let (lang,) = path.into_inner();
-    let template = Tmpl {
+    Tmpl {
         req,
         lang,
         name: query.name,
-    };
-    Ok(Html::new(
-        template.render().map_err(|err| err.into_io_error())?,
-    ))
+    }
 }
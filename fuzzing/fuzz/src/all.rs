@@ -67,5 +67,6 @@ macro_rules! this_file {
 this_file! {
     crate::filters::Filters;
     crate::html::Html;
+    crate::js::Js;
     crate::parser::Parser;
 }
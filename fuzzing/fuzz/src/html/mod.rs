@@ -23,6 +23,13 @@ impl<'a> super::Scenario<'a> for Scenario<'a> {
                 let mut dest = String::with_capacity(src.len());
                 html::write_escaped_str(&mut dest, src).unwrap();
 
+                // the chunked fast path must agree with escaping one character after another
+                let mut expected = String::with_capacity(src.len());
+                for c in src.chars() {
+                    html::write_escaped_char(&mut expected, c).unwrap();
+                }
+                assert_eq!(dest, expected);
+
                 let mut unescaped = String::with_capacity(src.len());
                 let unescaped = decode_html_entities_to_string(dest, &mut unescaped);
                 assert_eq!(src, unescaped);
@@ -0,0 +1,74 @@
+use arbitrary::{Arbitrary, Unstructured};
+use rinja::filters::{Escaper, Js};
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub enum Scenario<'a> {
+    String(&'a str),
+    Char(char),
+}
+
+impl<'a> super::Scenario<'a> for Scenario<'a> {
+    type RunError = std::convert::Infallible;
+
+    fn new(data: &'a [u8]) -> Result<Self, arbitrary::Error> {
+        Self::arbitrary_take_rest(Unstructured::new(data))
+    }
+
+    fn run(&self) -> Result<(), Self::RunError> {
+        match *self {
+            Scenario::String(src) => {
+                let mut dest = String::with_capacity(src.len());
+                Js.write_escaped_str(&mut dest, src).unwrap();
+                assert_eq!(src, unescape(&dest));
+            }
+            Scenario::Char(c) => {
+                let mut dest = String::with_capacity(6);
+                Js.write_escaped_char(&mut dest, c).unwrap();
+
+                let mut src = [0; 4];
+                let src = c.encode_utf8(&mut src);
+                assert_eq!(*src, unescape(&dest));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reverses the escaping, and checks that no unsafe character is left in the output
+fn unescape(escaped: &str) -> String {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        assert!(
+            !matches!(
+                c,
+                '\0'..='\x1f'
+                    | '\x7f'..='\u{9f}'
+                    | '"'
+                    | '&'
+                    | '\''
+                    | '<'
+                    | '>'
+                    | '`'
+                    | '\u{2028}'
+                    | '\u{2029}'
+            ),
+            "unescaped {c:?} in {escaped:?}",
+        );
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('u') => {
+                let hex = chars.as_str().get(..4).unwrap();
+                let c = u32::from_str_radix(hex, 16).unwrap();
+                unescaped.push(char::from_u32(c).unwrap());
+                chars = chars.as_str()[4..].chars();
+            }
+            c => panic!("unexpected escape sequence {c:?} in {escaped:?}"),
+        }
+    }
+    unescaped
+}
@@ -5,6 +5,7 @@
 pub mod all;
 pub mod filters;
 pub mod html;
+pub mod js;
 pub mod parser;
 
 use std::error::Error;
@@ -16,6 +17,7 @@ pub const TARGETS: &[(&str, TargetBuilder)] = &[
         NamedTarget::new::<filters::Scenario<'_>>(data)
     }),
     ("html", |data| NamedTarget::new::<html::Scenario<'_>>(data)),
+    ("js", |data| NamedTarget::new::<js::Scenario<'_>>(data)),
     ("parser", |data| {
         NamedTarget::new::<parser::Scenario<'_>>(data)
     }),
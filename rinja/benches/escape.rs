@@ -1,11 +1,19 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
-use rinja::filters::{Html, escape};
+use rinja::Template;
+use rinja::filters::{FastWritable, Html, escape};
 
 criterion_main!(benches);
 criterion_group!(benches, functions);
 
 fn functions(c: &mut Criterion) {
     c.bench_function("Escaping", escaping);
+    c.bench_function("Escaping safe text", escaping_safe_text);
+    c.bench_function("Escaping markup", escaping_markup);
+    c.bench_function("Escaping template with Display", escaping_template_display);
+    c.bench_function(
+        "Escaping template with FastWritable",
+        escaping_template_fast_writable,
+    );
 }
 
 fn escaping(b: &mut criterion::Bencher<'_>) {
@@ -16,4 +24,46 @@ fn escaping(b: &mut criterion::Bencher<'_>) {
     });
 }
 
+fn escaping_safe_text(b: &mut criterion::Bencher<'_>) {
+    let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(256);
+    b.iter(|| {
+        let _ = black_box(format!("{}", escape(black_box(&text), Html).unwrap()));
+    });
+}
+
+fn escaping_markup(b: &mut criterion::Bencher<'_>) {
+    let text = r#"<a href="/?a=1&b=2" title='x'>link</a> "#.repeat(256);
+    b.iter(|| {
+        let _ = black_box(format!("{}", escape(black_box(&text), Html).unwrap()));
+    });
+}
+
+#[derive(Template)]
+#[template(
+    source = "{% for s in strings %}<li>{{ s }}</li>\n{% endfor %}",
+    ext = "txt"
+)]
+struct Items<'a> {
+    strings: &'a [&'a str],
+}
+
+fn escaping_template_display(b: &mut criterion::Bencher<'_>) {
+    let items = Items { strings: STRINGS };
+    b.iter(|| {
+        let _ = black_box(format!("{}", escape(black_box(&items), Html).unwrap()));
+    });
+}
+
+fn escaping_template_fast_writable(b: &mut criterion::Bencher<'_>) {
+    let items = Items { strings: STRINGS };
+    b.iter(|| {
+        let mut output = String::new();
+        escape(black_box(&items), Html)
+            .unwrap()
+            .write_into(&mut output)
+            .unwrap();
+        black_box(output)
+    });
+}
+
 const STRINGS: &[&str] = include!("strings.inc");
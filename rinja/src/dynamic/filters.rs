@@ -0,0 +1,408 @@
+//! The built-in filters, applied to [`Value`]s
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::interpreter::{Result, Unsupported};
+use super::{DynEscaper, Value};
+use crate::PrimitiveType;
+use crate::filters::{
+    self, Css, Html, Js, Latex, NumberValue, PluralizeCount, Roff, Shell, Text, Xml,
+};
+
+/// Filters whose output in the interpreter is identical to the compiled code, if they are called
+/// without arguments
+pub(crate) const MIRRORED: &[&str] = &[
+    "capitalize",
+    "e",
+    "escape",
+    "escapejs",
+    "lower",
+    "lowercase",
+    "safe",
+    "title",
+    "trim",
+    "upper",
+    "uppercase",
+];
+
+/// Applies the filter `name` to `value`
+pub(crate) fn apply(
+    escaper: &dyn DynEscaper,
+    name: &str,
+    value: Value,
+    args: &[Value],
+) -> Result<Value> {
+    let string = || {
+        value
+            .to_display()
+            .ok_or_else(|| invalid_input(name, &value))
+    };
+    Ok(match (name, args) {
+        ("safe", []) => Value::Safe(string()?.into()),
+        ("escape" | "e", []) => escape(escaper, &string()?)?,
+        ("escape" | "e", [escaper_name]) => {
+            let escaper_name = escaper_name
+                .as_str()
+                .ok_or(Unsupported::new("the escaper must be a string"))?;
+            let escaper = escaper_by_name(escaper_name)
+                .ok_or_else(|| Unsupported(format!("unknown escaper `{escaper_name}`").into()))?;
+            escape(escaper, &string()?)?
+        }
+        ("escapejs", []) => escape(&Js, &string()?)?,
+        ("capitalize", []) => {
+            Value::Str(filters::capitalize(string()?).map_err(failed(name))?.into())
+        }
+        ("lower" | "lowercase", []) => {
+            Value::Str(filters::lower(string()?).map_err(failed(name))?.into())
+        }
+        ("upper" | "uppercase", []) => {
+            Value::Str(filters::upper(string()?).map_err(failed(name))?.into())
+        }
+        ("title", []) => Value::Str(filters::title(string()?).map_err(failed(name))?.into()),
+        ("trim", []) => Value::Str(filters::trim(string()?).map_err(failed(name))?.into()),
+        ("center", [width]) => {
+            let centered =
+                filters::center(string()?, usize_arg(name, width)?).map_err(failed(name))?;
+            Value::Str(centered.to_string().into())
+        }
+        ("indent", [width]) => {
+            let indented =
+                filters::indent(string()?, usize_arg(name, width)?).map_err(failed(name))?;
+            Value::Str(indented.into())
+        }
+        ("truncate", [length]) => {
+            let truncated =
+                filters::truncate(string()?, usize_arg(name, length)?).map_err(failed(name))?;
+            Value::Str(truncated.to_string().into())
+        }
+        ("wordcount", []) => {
+            Value::Int(filters::wordcount(string()?).map_err(failed(name))? as i128)
+        }
+        ("linebreaks" | "linebreaksbr" | "paragraphbreaks", []) => {
+            // The input is always HTML escaped, regardless of the selected escaper.
+            let mut escaped = String::new();
+            match &value {
+                Value::Safe(s) => escaped.push_str(s),
+                _ => DynEscaper::write_escaped_str(&Html, &mut escaped, &string()?)
+                    .map_err(failed(name))?,
+            }
+            let output = match name {
+                "linebreaks" => filters::linebreaks(escaped),
+                "linebreaksbr" => filters::linebreaksbr(escaped),
+                _ => filters::paragraphbreaks(escaped),
+            };
+            html_safe(escaper, output.map_err(failed(name))?.0)
+        }
+        ("join", [separator]) => {
+            let Value::List(items) = &value else {
+                return Err(invalid_input(name, &value));
+            };
+            let items = items
+                .iter()
+                .map(|item| item.to_display().ok_or_else(|| invalid_input(name, item)))
+                .collect::<Result<Vec<_>>>()?;
+            let separator = separator
+                .to_display()
+                .ok_or_else(|| invalid_input(name, separator))?;
+            let joined = filters::join(items, separator).map_err(failed(name))?;
+            Value::Str(joined.to_string().into())
+        }
+        ("length", []) => match &value {
+            Value::List(items) => Value::Int(items.len() as i128),
+            Value::Map(items) => Value::Int(items.len() as i128),
+            _ => return Err(invalid_input(name, &value)),
+        },
+        (
+            "first" | "last" | "reverse" | "sort" | "unique" | "min" | "max" | "sum" | "select"
+            | "reject" | "groupby" | "batch" | "slice",
+            _,
+        ) => {
+            let Value::List(items) = &value else {
+                return Err(invalid_input(name, &value));
+            };
+            collection(name, items, args)?
+        }
+        ("pluralize", [] | [_] | [_, _]) => {
+            let Value::Int(count) = value else {
+                return Err(invalid_input(name, &value));
+            };
+            let singular = args.first().cloned();
+            let plural = args.get(1).cloned();
+            match count.is_singular().map_err(failed(name))? {
+                true => singular.unwrap_or_else(|| Value::Str("".into())),
+                false => plural.unwrap_or_else(|| Value::Str("s".into())),
+            }
+        }
+        ("filesizeformat", []) => {
+            let size = match value {
+                Value::Int(size) => size as f32,
+                Value::Float(size) => size as f32,
+                _ => return Err(invalid_input(name, &value)),
+            };
+            let formatted = filters::filesizeformat(size).map_err(failed(name))?;
+            html_safe(escaper, formatted.to_string())
+        }
+        ("numberformat" | "percent", [] | [_] | [_, _])
+        | ("currency", [_] | [_, _] | [_, _, _]) => {
+            let (code, args) = match name {
+                "currency" => (
+                    args[0]
+                        .as_str()
+                        .ok_or(Unsupported::new("the currency code must be a string"))?,
+                    &args[1..],
+                ),
+                _ => ("", args),
+            };
+            let decimals = args.first().map(|arg| usize_arg(name, arg)).transpose()?;
+            let locale = match args.get(1) {
+                Some(locale) => locale
+                    .as_str()
+                    .ok_or(Unsupported::new("the locale must be a string"))?,
+                None => filters::DEFAULT_LOCALE,
+            };
+            let formatted = match value {
+                Value::Int(value) => format_number(name, value, code, decimals, locale),
+                Value::Float(value) => format_number(name, value, code, decimals, locale),
+                _ => return Err(invalid_input(name, &value)),
+            };
+            html_safe(escaper, formatted.map_err(failed(name))?)
+        }
+        #[cfg(feature = "urlencode")]
+        ("urlencode", []) => {
+            let encoded = filters::urlencode(string()?).map_err(failed(name))?;
+            html_safe(escaper, encoded.to_string())
+        }
+        #[cfg(feature = "urlencode")]
+        ("urlencode_strict", []) => {
+            let encoded = filters::urlencode_strict(string()?).map_err(failed(name))?;
+            html_safe(escaper, encoded.to_string())
+        }
+        #[cfg(feature = "serde_json")]
+        ("json" | "tojson", []) => Value::Str(
+            filters::json(&value)
+                .map_err(failed(name))?
+                .to_string()
+                .into(),
+        ),
+        #[cfg(feature = "serde_json")]
+        ("json" | "tojson", [indent]) => {
+            let json = match indent {
+                Value::Str(indent) | Value::Safe(indent) => {
+                    filters::json_pretty(&value, &**indent).map(|json| json.to_string())
+                }
+                indent => filters::json_pretty(&value, usize_arg(name, indent)?)
+                    .map(|json| json.to_string()),
+            };
+            Value::Str(json.map_err(failed(name))?.into())
+        }
+        _ => return Err(Unsupported(format!("unknown filter `{name}`").into())),
+    })
+}
+
+/// Returns the built-in escaper for the file extension `name`, like `|escape("html")`
+pub(crate) fn escaper_by_name(name: &str) -> Option<&'static dyn DynEscaper> {
+    match name {
+        "html" | "htm" | "j2" | "jinja" | "jinja2" | "rinja" => Some(&Html),
+        "css" => Some(&Css),
+        "js" | "mjs" => Some(&Js),
+        "md" | "none" | "txt" | "yml" | "" => Some(&Text),
+        "tex" => Some(&Latex),
+        "bash" | "sh" => Some(&Shell),
+        "atom" | "rss" | "svg" | "xhtml" | "xml" => Some(&Xml),
+        "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" | "man" | "roff" => Some(&Roff),
+        _ => None,
+    }
+}
+
+fn escape(escaper: &dyn DynEscaper, string: &str) -> Result<Value> {
+    let mut escaped = String::new();
+    escaper
+        .write_escaped_str(&mut escaped, string)
+        .map_err(|_| Unsupported::new("could not escape the value"))?;
+    Ok(Value::Safe(escaped.into()))
+}
+
+/// Output that is known to be HTML safe is only escaped by other escapers
+fn html_safe(escaper: &dyn DynEscaper, output: String) -> Value {
+    match escaper.is_html() {
+        true => Value::Safe(output.into()),
+        false => Value::Str(output.into()),
+    }
+}
+
+fn collection(name: &str, items: &[Value], args: &[Value]) -> Result<Value> {
+    let compare = |a: &Value, b: &Value| a.same_kind_cmp(b).unwrap_or(Ordering::Equal);
+    let list = |items: Vec<Value>| Value::List(items.into());
+    Ok(match (name, args) {
+        ("first", []) => items.first().cloned().unwrap_or(Value::None),
+        ("last", []) => items.last().cloned().unwrap_or(Value::None),
+        ("reverse", []) => list(items.iter().rev().cloned().collect()),
+        ("sort", [] | [Value::Bool(_)]) => {
+            let mut items = items.to_vec();
+            match args {
+                [Value::Bool(true)] => items.sort_by(|a, b| compare(b, a)),
+                _ => items.sort_by(compare),
+            }
+            list(items)
+        }
+        ("unique", []) => {
+            let mut unique: Vec<Value> = Vec::with_capacity(items.len());
+            for item in items {
+                if !unique.iter().any(|u| u.same_kind_eq(item) == Some(true)) {
+                    unique.push(item.clone());
+                }
+            }
+            list(unique)
+        }
+        ("min" | "max", []) => {
+            let wanted = match name {
+                "min" => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+            items
+                .iter()
+                .reduce(|best, item| match compare(item, best) == wanted {
+                    true => item,
+                    false => best,
+                })
+                .cloned()
+                .unwrap_or(Value::None)
+        }
+        ("sum", []) => match items.first() {
+            None | Some(Value::Int(_)) => {
+                Value::Int(items.iter().try_fold(0_i128, |sum, item| {
+                    match item {
+                        Value::Int(item) => sum
+                            .checked_add(*item)
+                            .ok_or(Unsupported::new("the `sum` of the items overflows")),
+                        item => Err(invalid_input(name, item)),
+                    }
+                })?)
+            }
+            Some(_) => Value::Float(
+                items
+                    .iter()
+                    .map(|item| match item {
+                        Value::Float(item) => Ok(*item),
+                        item => Err(invalid_input(name, item)),
+                    })
+                    .sum::<Result<f64>>()?,
+            ),
+        },
+        ("select" | "reject", [] | [_]) => {
+            let test = match args.first() {
+                Some(test) => Some(
+                    test.as_str()
+                        .ok_or(Unsupported::new("the test must be a string"))?,
+                ),
+                None => None,
+            };
+            let mut selected = Vec::new();
+            for item in items {
+                if passes_test(name, test, item)? == (name == "select") {
+                    selected.push(item.clone());
+                }
+            }
+            list(selected)
+        }
+        ("groupby", [attribute]) => {
+            let attribute = attribute
+                .as_str()
+                .ok_or(Unsupported::new("the attribute must be a string"))?;
+            let key = |item: &Value| {
+                attribute
+                    .split('.')
+                    .try_fold(item, |value, field| value.attr(field))
+                    .cloned()
+                    .ok_or_else(|| Unsupported(format!("unknown attribute `{attribute}`").into()))
+            };
+            let mut items = items
+                .iter()
+                .map(|item| Ok((key(item)?, item.clone())))
+                .collect::<Result<Vec<_>>>()?;
+            items.sort_by(|(a, _), (b, _)| compare(a, b));
+
+            let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+            for (key, item) in items {
+                match groups.last_mut() {
+                    Some((group, items)) if group.same_kind_eq(&key) == Some(true) => {
+                        items.push(item);
+                    }
+                    _ => groups.push((key, alloc::vec![item])),
+                }
+            }
+            list(
+                groups
+                    .into_iter()
+                    .map(|(key, items)| list(alloc::vec![key, list(items)]))
+                    .collect(),
+            )
+        }
+        ("batch", [size] | [size, _]) => {
+            let size = usize_arg(name, size)?;
+            let rows = filters::batch(items, size, args.get(1)).map_err(failed(name))?;
+            list(rows.map(|row| list(row.cloned().collect())).collect())
+        }
+        ("slice", [count]) => {
+            let columns = filters::slice(items, usize_arg(name, count)?).map_err(failed(name))?;
+            list(
+                columns
+                    .map(|column| list(column.cloned().collect()))
+                    .collect(),
+            )
+        }
+        _ => return Err(Unsupported(format!("unknown filter `{name}`").into())),
+    })
+}
+
+/// Applies the test of `select(test)` or `reject(test)` to `item`, e.g. `"odd"`
+fn passes_test(name: &str, test: Option<&str>, item: &Value) -> Result<bool> {
+    Ok(match (test, item) {
+        (None | Some("true"), &Value::Bool(value)) => value,
+        (Some("false"), &Value::Bool(value)) => !value,
+        (Some("even"), &Value::Int(value)) => value % 2 == 0,
+        (Some("odd"), &Value::Int(value)) => value % 2 != 0,
+        (Some("none"), item) => matches!(item, Value::None),
+        (Some("some"), item) => !matches!(item, Value::None),
+        (None | Some("true" | "false" | "even" | "odd"), item) => {
+            return Err(invalid_input(name, item));
+        }
+        (Some(test), _) => return Err(Unsupported(format!("unknown test `{test}`").into())),
+    })
+}
+
+fn format_number<T>(
+    name: &str,
+    value: T,
+    code: &str,
+    decimals: Option<usize>,
+    locale: &str,
+) -> crate::Result<String>
+where
+    T: PrimitiveType<Value = T> + NumberValue,
+{
+    Ok(match name {
+        "numberformat" => filters::numberformat(value, decimals, locale)?.to_string(),
+        "percent" => filters::percent(value, decimals, locale)?.to_string(),
+        _ => filters::currency(value, code, decimals, locale)?.to_string(),
+    })
+}
+
+fn usize_arg(name: &str, value: &Value) -> Result<usize> {
+    match value {
+        Value::Int(value) => usize::try_from(*value).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| Unsupported(format!("filter `{name}` expects a non-negative integer").into()))
+}
+
+fn failed<E>(name: &str) -> impl FnOnce(E) -> Unsupported + '_ {
+    move |_| Unsupported(format!("filter `{name}` failed").into())
+}
+
+fn invalid_input(name: &str, value: &Value) -> Unsupported {
+    Unsupported(format!("filter `{name}` cannot be applied to {}", value.kind()).into())
+}
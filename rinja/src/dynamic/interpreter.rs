@@ -0,0 +1,1180 @@
+//! Interpreter for parsed templates
+//!
+//! In [`Mode::Mirror`] the interpreter mirrors what `rinja_derive` generates for a template,
+//! including its whitespace handling, so its output is identical to the output of the compiled
+//! template. Whenever it encounters a construct that it cannot reproduce faithfully, it bails out
+//! with [`Unsupported`] and the caller falls back to the compiled code.
+//!
+//! In [`Mode::Standalone`] there is no compiled code to fall back to, so the interpreter also
+//! accepts `{% match %}`, macros, filter blocks, `is defined` and the other built-in filters.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use rinja_parser::expr::Filter;
+use rinja_parser::node::{
+    BlockDef, Call, Cond, FilterBlock, If, Let, Lit, Loop, Macro, Match, Whitespace, Ws,
+};
+use rinja_parser::{CharLit, Expr, IntKind, Node, Num, StrLit, Target, WithSpan};
+
+use super::{DynEscaper, Value, filters};
+use crate::{Limit, Limits};
+
+/// How deeply `{% call %}`s may be nested, so a recursive macro cannot overflow the stack
+///
+/// There is no separate limit for `{% include %}`, because it is not supported.
+const MAX_MACRO_DEPTH: usize = 64;
+
+/// How many items a range may have if it is not iterated over directly by a `{% for %}` loop
+const MAX_RANGE_LEN: usize = 65_536;
+
+/// The template uses a construct that the interpreter cannot reproduce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Unsupported(pub(crate) Cow<'static, str>);
+
+impl Unsupported {
+    pub(crate) const fn new(msg: &'static str) -> Self {
+        Self(Cow::Borrowed(msg))
+    }
+}
+
+pub(crate) type Result<T, E = Unsupported> = core::result::Result<T, E>;
+
+/// Which constructs the [`Interpreter`] accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// Only accept constructs whose output is identical to the compiled template
+    #[cfg_attr(not(feature = "hot-reload"), allow(dead_code))]
+    Mirror,
+    /// Accept every construct that can be interpreted without a compiled template
+    Standalone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+pub(crate) struct Interpreter<'a, 'n> {
+    out: String,
+    mode: Mode,
+    escaper: &'a dyn DynEscaper,
+    fields: &'a dyn Fn(&str) -> Option<Value>,
+    field_cache: Vec<(&'n str, Option<Value>)>,
+    /// A variable that was declared with `{% let x %}` has no value until it is assigned
+    locals: Vec<(&'n str, Option<Value>)>,
+    macros: Vec<&'n Macro<'n>>,
+    filter_source: Option<Value>,
+    whitespace: Whitespace,
+    next_ws: Option<&'n str>,
+    skip_ws: Whitespace,
+    macro_depth: usize,
+    limits: Limits,
+    loop_iterations: usize,
+    /// The length of the output that was put aside while a filter block is rendered
+    pending_len: usize,
+    exceeded: Option<Limit>,
+}
+
+impl<'a, 'n> Interpreter<'a, 'n> {
+    pub(crate) fn new(
+        mode: Mode,
+        escaper: &'a dyn DynEscaper,
+        fields: &'a dyn Fn(&str) -> Option<Value>,
+        whitespace: Whitespace,
+    ) -> Self {
+        Self {
+            out: String::new(),
+            mode,
+            escaper,
+            fields,
+            field_cache: Vec::new(),
+            locals: Vec::new(),
+            macros: Vec::new(),
+            filter_source: None,
+            whitespace,
+            next_ws: None,
+            skip_ws: Whitespace::Preserve,
+            macro_depth: 0,
+            limits: Limits::default(),
+            loop_iterations: 0,
+            pending_len: 0,
+            exceeded: None,
+        }
+    }
+
+    /// Aborts the rendering if it exceeds one of the `limits`
+    pub(crate) fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The limit that aborted the last [`render()`][Self::render], if any
+    pub(crate) fn exceeded(&self) -> Option<Limit> {
+        self.exceeded
+    }
+
+    /// Renders the top-level `nodes` of a template
+    pub(crate) fn render(&mut self, nodes: &'n [Node<'n>]) -> Result<String> {
+        if nodes.iter().any(|node| matches!(node, Node::Extends(_))) {
+            return Err(Unsupported::new("`{% extends %}` is not supported"));
+        }
+        for node in nodes {
+            if let Node::Macro(m) = node {
+                self.macros.push(m);
+            }
+        }
+        self.handle(nodes)?;
+        // Handle any pending whitespace.
+        if self.next_ws.is_some() {
+            self.flush_ws(Ws(Some(self.skip_ws), None));
+            self.check_output()?;
+        }
+        Ok(core::mem::take(&mut self.out))
+    }
+
+    /// Returns an error if the output exceeds [`Limits::max_bytes`]
+    fn check_output(&mut self) -> Result<()> {
+        match self.pending_len.checked_add(self.out.len()) {
+            Some(len) if len <= self.limits.max_bytes => Ok(()),
+            _ => Err(self.exceed(Limit::Bytes)),
+        }
+    }
+
+    /// Returns an error if the loops exceed [`Limits::max_loop_iterations`]
+    fn take_loop_iteration(&mut self) -> Result<()> {
+        if self.loop_iterations >= self.limits.max_loop_iterations {
+            return Err(self.exceed(Limit::LoopIterations));
+        }
+        self.loop_iterations += 1;
+        Ok(())
+    }
+
+    fn exceed(&mut self, limit: Limit) -> Unsupported {
+        self.exceeded = Some(limit);
+        Unsupported(format!("{limit}").into())
+    }
+
+    /// Returns an error in [`Mode::Mirror`], because the compiled code treats `construct`
+    /// differently than the interpreter
+    fn standalone(&self, construct: &'static str) -> Result<()> {
+        match self.mode {
+            Mode::Mirror => Err(Unsupported::new(construct)),
+            Mode::Standalone => Ok(()),
+        }
+    }
+
+    fn handle(&mut self, nodes: &'n [Node<'n>]) -> Result<Flow> {
+        for node in nodes {
+            let flow = match node {
+                Node::Lit(lit) => {
+                    self.visit_lit(lit);
+                    Flow::Normal
+                }
+                Node::Comment(comment) => {
+                    self.handle_ws(comment.ws);
+                    Flow::Normal
+                }
+                Node::Expr(ws, expr) => {
+                    self.handle_ws(*ws);
+                    self.write_expr(expr)?;
+                    Flow::Normal
+                }
+                Node::Let(l) => {
+                    self.handle_ws(l.ws);
+                    self.write_let(l)?;
+                    Flow::Normal
+                }
+                Node::If(i) => self.write_if(i)?,
+                Node::Match(m) => {
+                    self.standalone("`{% match %}`")?;
+                    self.write_match(m)?
+                }
+                Node::Loop(l) => self.write_loop(l)?,
+                Node::BlockDef(b) => self.write_block(b)?,
+                Node::Macro(m) => {
+                    self.standalone("`{% macro %}`")?;
+                    self.flush_ws(m.ws1);
+                    self.prepare_ws(m.ws2);
+                    Flow::Normal
+                }
+                Node::Call(call) => {
+                    self.standalone("`{% call %}`")?;
+                    self.write_call(call)?
+                }
+                Node::FilterBlock(filter) => {
+                    self.standalone("`{% filter %}`")?;
+                    self.write_filter_block(filter)?
+                }
+                Node::Raw(raw) => {
+                    self.handle_ws(raw.ws1);
+                    self.visit_lit(&raw.lit);
+                    self.handle_ws(raw.ws2);
+                    Flow::Normal
+                }
+                Node::Break(ws) => {
+                    self.handle_ws(**ws);
+                    Flow::Break
+                }
+                Node::Continue(ws) => {
+                    self.handle_ws(**ws);
+                    Flow::Continue
+                }
+                Node::Include(_) => {
+                    return Err(Unsupported::new("`{% include %}` is not supported"));
+                }
+                Node::Import(_) => return Err(Unsupported::new("`{% import %}` is not supported")),
+                Node::Extends(_) => {
+                    return Err(Unsupported::new(
+                        "`{% extends %}` is only allowed at the top",
+                    ));
+                }
+            };
+            self.check_output()?;
+            if flow != Flow::Normal {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn write_let(&mut self, l: &'n Let<'n>) -> Result<()> {
+        let Some(val) = &l.val else {
+            self.standalone("`{% let %}` without a value")?;
+            let Target::Name(name) = l.var else {
+                return Err(Unsupported::new(
+                    "only a name can be declared without a value",
+                ));
+            };
+            self.locals.push((name, None));
+            return Ok(());
+        };
+
+        let val = self.eval(val)?;
+        if let Target::Name(name) = l.var {
+            // Assign a value to a variable that was declared before.
+            if let Some((_, slot @ None)) = self.locals.iter_mut().rev().find(|(n, _)| *n == name) {
+                *slot = Some(val);
+                return Ok(());
+            }
+        }
+        self.bind(&l.var, val)
+    }
+
+    fn write_if(&mut self, if_: &'n If<'n>) -> Result<Flow> {
+        // Conditions containing literals or `is defined` are evaluated at compile time,
+        // which changes where the whitespace of the pruned branches ends up.
+        for cond in &if_.branches {
+            if let Some(test) = &cond.cond {
+                if test.target.is_some() || test.contains_bool_lit_or_is_defined {
+                    self.standalone("`{% if let %}` or a condition that is known at compile time")?;
+                }
+            }
+        }
+
+        let Some(first) = if_.branches.first() else {
+            return Ok(Flow::Normal);
+        };
+        self.handle_ws(first.ws);
+
+        let mut iter = if_.branches.iter().peekable();
+        while let Some(cond) = iter.next() {
+            let Some(bindings) = self.eval_cond(cond)? else {
+                continue;
+            };
+
+            self.next_ws = None;
+            self.prepare_ws(cond.ws);
+            let flow = self.with_scope(|this| {
+                this.push_bindings(bindings);
+                this.handle(&cond.nodes)
+            })?;
+            if flow != Flow::Normal {
+                return Ok(flow);
+            }
+            match iter.peek() {
+                Some(next) => self.handle_ws(next.ws),
+                None => self.handle_ws(if_.ws),
+            }
+            break;
+        }
+
+        self.next_ws = None;
+        self.prepare_ws(if_.ws);
+        Ok(Flow::Normal)
+    }
+
+    /// Returns the variables bound by the condition if it is true
+    fn eval_cond(&mut self, cond: &'n Cond<'n>) -> Result<Option<Vec<(&'n str, Value)>>> {
+        let Some(test) = &cond.cond else {
+            return Ok(Some(Vec::new()));
+        };
+        match &test.target {
+            Some(target) => {
+                let value = self.eval(&test.expr)?;
+                let mut bindings = Vec::new();
+                Ok(match_target(target, &value, &mut bindings)?.then_some(bindings))
+            }
+            None => Ok(self.eval_bool(&test.expr)?.then(Vec::new)),
+        }
+    }
+
+    fn write_match(&mut self, m: &'n Match<'n>) -> Result<Flow> {
+        self.flush_ws(m.ws1);
+        let value = self.eval(&m.expr)?;
+
+        let mut matched = false;
+        for (index, arm) in m.arms.iter().enumerate() {
+            let mut bindings = Vec::new();
+            if !match_any(&arm.target, &value, &mut bindings)? {
+                continue;
+            }
+
+            self.next_ws = None;
+            self.prepare_ws(arm.ws);
+            let flow = self.with_scope(|this| {
+                this.push_bindings(bindings);
+                this.handle(&arm.nodes)
+            })?;
+            if flow != Flow::Normal {
+                return Ok(flow);
+            }
+            match m.arms.get(index + 1) {
+                Some(next) => self.flush_ws(next.ws),
+                None => self.flush_ws(m.ws2),
+            }
+            matched = true;
+            break;
+        }
+        if !matched {
+            return Err(Unsupported::new(
+                "no arm of `{% match %}` matches the value",
+            ));
+        }
+
+        self.next_ws = None;
+        self.prepare_ws(m.ws2);
+        Ok(Flow::Normal)
+    }
+
+    fn write_loop(&mut self, loop_block: &'n Loop<'n>) -> Result<Flow> {
+        self.handle_ws(loop_block.ws1);
+
+        // Ranges are iterated lazily, so a loop can `{% break %}` out of a huge range.
+        let mut items: Box<dyn Iterator<Item = Value>> = match &*loop_block.iter {
+            Expr::Range(op, start, end) => {
+                Box::new(self.eval_range(op, start, end)?.map(Value::Int))
+            }
+            _ => match self.eval(&loop_block.iter)? {
+                Value::List(items) => Box::new((0..items.len()).map(move |i| items[i].clone())),
+                Value::Map(items) => Box::new((0..items.len()).map(move |i| {
+                    let (key, value) = &items[i];
+                    Value::List(Arc::new([key.clone(), value.clone()]))
+                })),
+                _ => return Err(Unsupported::new("only lists and maps can be iterated over")),
+            },
+        };
+
+        // The next item is fetched before the current one is rendered to know if it is the last.
+        let mut next = self.next_loop_item(loop_block, &mut items)?;
+        let mut index = 0;
+        while let Some(item) = next {
+            next = self.next_loop_item(loop_block, &mut items)?;
+            self.next_ws = None;
+            self.prepare_ws(loop_block.ws1);
+            let last = next.is_none();
+            let flow = self.with_scope(|this| {
+                this.bind(&loop_block.var, item)?;
+                this.locals.push(("loop", Some(loop_value(index, last))));
+                this.handle(&loop_block.body)
+            })?;
+            index += 1;
+            match flow {
+                Flow::Normal => self.handle_ws(loop_block.ws2),
+                Flow::Continue => {}
+                Flow::Break => break,
+            }
+        }
+
+        if index == 0 && !loop_block.else_nodes.is_empty() {
+            self.next_ws = None;
+            self.prepare_ws(loop_block.ws2);
+            let flow = self.with_scope(|this| this.handle(&loop_block.else_nodes))?;
+            if flow != Flow::Normal {
+                return Ok(flow);
+            }
+            self.handle_ws(loop_block.ws3);
+        }
+
+        self.next_ws = None;
+        self.prepare_ws(loop_block.ws3);
+        Ok(Flow::Normal)
+    }
+
+    /// Returns the next item of `items` that passes the condition of the loop
+    ///
+    /// Every item counts against [`Limits::max_loop_iterations`], even if it is skipped.
+    fn next_loop_item(
+        &mut self,
+        loop_block: &'n Loop<'n>,
+        items: &mut dyn Iterator<Item = Value>,
+    ) -> Result<Option<Value>> {
+        for item in items {
+            self.take_loop_iteration()?;
+            let Some(cond) = &loop_block.cond else {
+                return Ok(Some(item));
+            };
+            let keep = self.with_scope(|this| {
+                this.bind(&loop_block.var, item.clone())?;
+                this.eval_bool(cond)
+            })?;
+            if keep {
+                return Ok(Some(item));
+            }
+        }
+        Ok(None)
+    }
+
+    fn write_block(&mut self, block: &'n BlockDef<'n>) -> Result<Flow> {
+        let outer = Ws(block.ws1.0, block.ws2.1);
+        self.flush_ws(outer);
+        self.prepare_ws(block.ws1);
+        let flow = self.with_scope(|this| this.handle(&block.nodes))?;
+        if flow != Flow::Normal {
+            return Ok(flow);
+        }
+        self.flush_ws(block.ws2);
+        self.prepare_ws(outer);
+        Ok(Flow::Normal)
+    }
+
+    fn write_call(&mut self, call: &'n Call<'n>) -> Result<Flow> {
+        if call.scope.is_some() {
+            return Err(Unsupported::new("calling imported macros is not supported"));
+        }
+        let def = *self
+            .macros
+            .iter()
+            .find(|m| m.name == call.name)
+            .ok_or_else(|| Unsupported(format!("macro `{}` not found", call.name).into()))?;
+        if self.macro_depth >= MAX_MACRO_DEPTH {
+            return Err(Unsupported(
+                format!("macro calls are nested too deeply in `{}`", def.name).into(),
+            ));
+        }
+
+        // Named arguments can only be passed last.
+        let positional = call
+            .args
+            .iter()
+            .take_while(|arg| !matches!(***arg, Expr::NamedArgument(..)))
+            .count();
+        if positional > def.args.len() {
+            return Err(Unsupported(
+                format!("too many arguments in call to macro `{}`", def.name).into(),
+            ));
+        }
+        let mut args = Vec::with_capacity(def.args.len());
+        for (index, (name, default)) in def.args.iter().enumerate() {
+            let named = call.args[positional..].iter().find_map(|arg| match &**arg {
+                Expr::NamedArgument(arg_name, value) if arg_name == name => Some(&**value),
+                _ => None,
+            });
+            let expr = match (call.args.get(index), named, default) {
+                (Some(arg), _, _) if index < positional => arg,
+                (_, Some(arg), _) | (_, None, Some(arg)) => arg,
+                (_, None, None) => {
+                    return Err(Unsupported(
+                        format!("missing `{name}` argument in call to macro `{}`", def.name).into(),
+                    ));
+                }
+            };
+            args.push((*name, Some(self.eval(expr)?)));
+        }
+
+        self.flush_ws(call.ws);
+        // Macros cannot see the variables of their caller, only their own arguments.
+        let caller = core::mem::replace(&mut self.locals, args);
+        self.prepare_ws(def.ws1);
+        self.macro_depth += 1;
+        let flow = self.handle(&def.nodes);
+        self.macro_depth -= 1;
+        self.locals = caller;
+        if flow? == Flow::Normal {
+            self.flush_ws(def.ws2);
+        }
+        self.prepare_ws(call.ws);
+        Ok(Flow::Normal)
+    }
+
+    fn write_filter_block(&mut self, filter: &'n FilterBlock<'n>) -> Result<Flow> {
+        self.flush_ws(filter.ws1);
+
+        let outer = core::mem::take(&mut self.out);
+        self.pending_len += outer.len();
+        self.prepare_ws(filter.ws1);
+        let flow = self.with_scope(|this| this.handle(&filter.nodes));
+        if matches!(flow, Ok(Flow::Normal)) {
+            self.flush_ws(filter.ws2);
+        }
+        self.pending_len -= outer.len();
+        let content = core::mem::replace(&mut self.out, outer);
+        let flow = flow?;
+
+        let source = self.filter_source.replace(Value::Str(content.into()));
+        let value = self.eval_filter(&filter.filters);
+        self.filter_source = source;
+        self.write_value(&value?)?;
+
+        self.prepare_ws(filter.ws2);
+        Ok(flow)
+    }
+
+    fn write_expr(&mut self, expr: &'n WithSpan<'n, Expr<'n>>) -> Result<()> {
+        let items = match &**expr {
+            Expr::Concat(exprs) => exprs.as_slice(),
+            _ => core::slice::from_ref(expr),
+        };
+        for expr in items {
+            let value = self.eval(expr)?;
+            self.write_value(&value)?;
+        }
+        Ok(())
+    }
+
+    fn write_value(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Safe(s) => self.out.push_str(s),
+            value => {
+                let s = value.to_display().ok_or_else(|| not_display(value))?;
+                self.escaper
+                    .write_escaped_str(&mut self.out, &s)
+                    .map_err(|_| Unsupported::new("could not escape the value"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn with_scope<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let len = self.locals.len();
+        let result = f(self);
+        self.locals.truncate(len);
+        result
+    }
+
+    fn push_bindings(&mut self, bindings: Vec<(&'n str, Value)>) {
+        self.locals.extend(
+            bindings
+                .into_iter()
+                .map(|(name, value)| (name, Some(value))),
+        );
+    }
+
+    fn bind(&mut self, target: &'n Target<'n>, value: Value) -> Result<()> {
+        let mut bindings = Vec::new();
+        if !match_target(target, &value, &mut bindings)? {
+            return Err(Unsupported::new("the value does not match the pattern"));
+        }
+        self.push_bindings(bindings);
+        Ok(())
+    }
+
+    fn lookup(&mut self, name: &'n str) -> Result<Value> {
+        if let Some((_, value)) = self.locals.iter().rev().find(|(n, _)| *n == name) {
+            return value.clone().ok_or_else(|| {
+                Unsupported(format!("`{name}` is used before it is assigned").into())
+            });
+        }
+        self.field(name)
+    }
+
+    /// Converts a field of the template into a [`Value`], only once per rendering
+    fn field(&mut self, name: &'n str) -> Result<Value> {
+        self.try_field(name)
+            .ok_or_else(|| Unsupported(format!("unknown variable `{name}`").into()))
+    }
+
+    fn try_field(&mut self, name: &'n str) -> Option<Value> {
+        match self.field_cache.iter().find(|(n, _)| *n == name) {
+            Some((_, value)) => value.clone(),
+            None => {
+                let value = (self.fields)(name);
+                self.field_cache.push((name, value.clone()));
+                value
+            }
+        }
+    }
+
+    fn is_defined(&mut self, name: &'n str) -> bool {
+        self.locals.iter().any(|(n, _)| *n == name) || self.try_field(name).is_some()
+    }
+
+    fn eval_bool(&mut self, expr: &'n WithSpan<'n, Expr<'n>>) -> Result<bool> {
+        match self.eval(expr)? {
+            Value::Bool(value) => Ok(value),
+            _ => Err(Unsupported::new("expected a boolean")),
+        }
+    }
+
+    fn eval(&mut self, expr: &'n WithSpan<'n, Expr<'n>>) -> Result<Value> {
+        Ok(match &**expr {
+            Expr::BoolLit(value) => Value::Bool(*value),
+            Expr::NumLit(_, num) => eval_num(num)?,
+            Expr::StrLit(StrLit {
+                prefix: None,
+                content,
+            }) => Value::Str(unescape(content)?.into()),
+            Expr::CharLit(CharLit {
+                prefix: None,
+                content,
+            }) => Value::Str(unescape(content)?.into()),
+            Expr::Var(name) => self.lookup(name)?,
+            Expr::Path(path) if path.as_slice() == ["None"] => {
+                self.standalone("`None`")?;
+                Value::None
+            }
+            Expr::Attr(obj, name) => match &***obj {
+                Expr::Var("self") => self.field(name)?,
+                _ => self
+                    .eval(obj)?
+                    .attr(name)
+                    .ok_or_else(|| Unsupported(format!("unknown attribute `{name}`").into()))?
+                    .clone(),
+            },
+            Expr::Index(obj, index) => {
+                let obj = self.eval(obj)?;
+                let index = self.eval(index)?;
+                obj.index(&index)
+                    .ok_or(Unsupported::new("index out of bounds"))?
+                    .clone()
+            }
+            Expr::Filter(filter) => self.eval_filter(filter)?,
+            Expr::Unary(op, inner) => match (*op, self.eval(inner)?) {
+                ("!", Value::Bool(value)) => Value::Bool(!value),
+                ("-", Value::Int(value)) => Value::Int(
+                    value
+                        .checked_neg()
+                        .ok_or(Unsupported::new("integer overflow"))?,
+                ),
+                ("-", Value::Float(value)) => Value::Float(-value),
+                // Values are owned, so (de)referencing them is a no-op.
+                ("*" | "&", value) => value,
+                _ => return Err(Unsupported(format!("invalid operand for `{op}`").into())),
+            },
+            Expr::BinOp(op, left, right) => self.eval_binop(op, left, right)?,
+            Expr::Range(op, start, end) => {
+                let range = self.eval_range(op, start, end)?;
+                if !matches!(range.size_hint(), (_, Some(len)) if len <= MAX_RANGE_LEN) {
+                    return Err(Unsupported(
+                        format!(
+                            "ranges outside of loops cannot have more than {MAX_RANGE_LEN} items"
+                        )
+                        .into(),
+                    ));
+                }
+                Value::List(range.map(Value::Int).collect())
+            }
+            Expr::Group(inner) => self.eval(inner)?,
+            Expr::Array(items) | Expr::Tuple(items) => Value::List(
+                items
+                    .iter()
+                    .map(|item| self.eval(item))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Concat(items) => {
+                let mut s = String::new();
+                for item in items {
+                    let value = self.eval(item)?;
+                    s.push_str(&value.to_display().ok_or_else(|| not_display(&value))?);
+                }
+                Value::Str(s.into())
+            }
+            Expr::As(inner, ty) => {
+                self.standalone("`as`")?;
+                cast(self.eval(inner)?, ty)?
+            }
+            Expr::FilterSource => self
+                .filter_source
+                .clone()
+                .ok_or(Unsupported::new("no filter block source"))?,
+            Expr::IsDefined(name) => {
+                self.standalone("`is defined`")?;
+                Value::Bool(self.is_defined(name))
+            }
+            Expr::IsNotDefined(name) => {
+                self.standalone("`is not defined`")?;
+                Value::Bool(!self.is_defined(name))
+            }
+            Expr::StrLit(_) | Expr::CharLit(_) => {
+                return Err(Unsupported::new("prefixed literals are not supported"));
+            }
+            Expr::Path(_) => return Err(Unsupported::new("paths are not supported")),
+            Expr::NamedArgument(_, _) => {
+                return Err(Unsupported::new(
+                    "named arguments are only supported in macro calls",
+                ));
+            }
+            Expr::Call(_, _) => {
+                return Err(Unsupported::new("calling functions is not supported"));
+            }
+            Expr::RustMacro(_, _) => {
+                return Err(Unsupported::new("calling macros is not supported"));
+            }
+            Expr::Try(_) => return Err(Unsupported::new("`?` is not supported")),
+        })
+    }
+
+    fn eval_range(
+        &mut self,
+        op: &str,
+        start: &'n Option<Box<WithSpan<'n, Expr<'n>>>>,
+        end: &'n Option<Box<WithSpan<'n, Expr<'n>>>>,
+    ) -> Result<Box<dyn Iterator<Item = i128>>> {
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(Unsupported::new("only bounded ranges are supported"));
+        };
+        let (Value::Int(start), Value::Int(end)) = (self.eval(start)?, self.eval(end)?) else {
+            return Err(Unsupported::new("the bounds of a range must be integers"));
+        };
+        match op {
+            ".." => Ok(Box::new(start..end)),
+            "..=" => Ok(Box::new(start..=end)),
+            _ => Err(Unsupported(format!("unknown range `{op}`").into())),
+        }
+    }
+
+    fn eval_binop(
+        &mut self,
+        op: &str,
+        left: &'n WithSpan<'n, Expr<'n>>,
+        right: &'n WithSpan<'n, Expr<'n>>,
+    ) -> Result<Value> {
+        use core::cmp::Ordering;
+
+        match op {
+            "&&" => return Ok(Value::Bool(self.eval_bool(left)? && self.eval_bool(right)?)),
+            "||" => return Ok(Value::Bool(self.eval_bool(left)? || self.eval_bool(right)?)),
+            _ => {}
+        }
+
+        let left = self.eval(left)?;
+        let right = self.eval(right)?;
+        let mismatch = || Unsupported(format!("cannot apply `{op}` to these values").into());
+        let cmp = |expected: &[Ordering]| {
+            let ordering = left.same_kind_cmp(&right).ok_or_else(mismatch)?;
+            Ok(Value::Bool(expected.contains(&ordering)))
+        };
+        match op {
+            "==" => Ok(Value::Bool(left.same_kind_eq(&right).ok_or_else(mismatch)?)),
+            "!=" => Ok(Value::Bool(
+                !left.same_kind_eq(&right).ok_or_else(mismatch)?,
+            )),
+            "<" => cmp(&[Ordering::Less]),
+            "<=" => cmp(&[Ordering::Less, Ordering::Equal]),
+            ">" => cmp(&[Ordering::Greater]),
+            ">=" => cmp(&[Ordering::Greater, Ordering::Equal]),
+            _ => match (left, right) {
+                (Value::Int(a), Value::Int(b)) => Ok(Value::Int(
+                    match op {
+                        "+" => a.checked_add(b),
+                        "-" => a.checked_sub(b),
+                        "*" => a.checked_mul(b),
+                        "/" => a.checked_div(b),
+                        "%" => a.checked_rem(b),
+                        "&" => Some(a & b),
+                        "|" => Some(a | b),
+                        "^" => Some(a ^ b),
+                        _ => return Err(mismatch()),
+                    }
+                    .ok_or(Unsupported::new("integer overflow or division by zero"))?,
+                )),
+                (Value::Float(a), Value::Float(b)) => Ok(Value::Float(match op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    "%" => a % b,
+                    _ => return Err(mismatch()),
+                })),
+                _ => Err(mismatch()),
+            },
+        }
+    }
+
+    fn eval_filter(&mut self, filter: &'n Filter<'n>) -> Result<Value> {
+        let [value, args @ ..] = filter.arguments.as_slice() else {
+            return Err(Unsupported::new("a filter needs an input"));
+        };
+        if !args.is_empty() || !filters::MIRRORED.contains(&filter.name) {
+            self.standalone("a filter that is not reproduced exactly")?;
+        }
+        let value = self.eval(value)?;
+        let args = args
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Result<Vec<_>>>()?;
+        filters::apply(self.escaper, filter.name, value, &args)
+    }
+
+    // Helper methods for dealing with whitespace nodes, cf. `rinja_derive`'s `Generator`
+
+    fn visit_lit(&mut self, lit: &'n Lit<'n>) {
+        let Lit { lws, val, rws } = *lit;
+        if !lws.is_empty() {
+            match self.skip_ws {
+                Whitespace::Suppress => {}
+                _ if val.is_empty() => {
+                    self.next_ws = Some(lws);
+                }
+                Whitespace::Preserve => self.out.push_str(lws),
+                Whitespace::Minimize => self.out.push_str(minimized(lws)),
+            }
+        }
+
+        if !val.is_empty() {
+            self.skip_ws = Whitespace::Preserve;
+            self.out.push_str(val);
+        }
+
+        if !rws.is_empty() {
+            self.next_ws = Some(rws);
+        }
+    }
+
+    fn handle_ws(&mut self, ws: Ws) {
+        self.flush_ws(ws);
+        self.prepare_ws(ws);
+    }
+
+    fn should_trim_ws(&self, ws: Option<Whitespace>) -> Whitespace {
+        ws.unwrap_or(self.whitespace)
+    }
+
+    fn flush_ws(&mut self, ws: Ws) {
+        let Some(val) = self.next_ws.take() else {
+            return;
+        };
+        match self.should_trim_ws(ws.0) {
+            Whitespace::Preserve => self.out.push_str(val),
+            Whitespace::Minimize if !val.is_empty() => self.out.push_str(minimized(val)),
+            Whitespace::Minimize | Whitespace::Suppress => {}
+        }
+    }
+
+    fn prepare_ws(&mut self, ws: Ws) {
+        self.skip_ws = self.should_trim_ws(ws.1);
+    }
+}
+
+fn minimized(ws: &str) -> &'static str {
+    match ws.contains('\n') {
+        true => "\n",
+        false => " ",
+    }
+}
+
+fn not_display(value: &Value) -> Unsupported {
+    Unsupported(format!("cannot render {}", value.kind()).into())
+}
+
+fn loop_value(index: usize, last: bool) -> Value {
+    let entry = |key: &str, value| (Value::Str(key.into()), value);
+    Value::Map(Arc::new([
+        entry("index", Value::Int(index as i128 + 1)),
+        entry("index0", Value::Int(index as i128)),
+        entry("first", Value::Bool(index == 0)),
+        entry("last", Value::Bool(last)),
+    ]))
+}
+
+fn match_any<'n>(
+    targets: &'n [Target<'n>],
+    value: &Value,
+    bindings: &mut Vec<(&'n str, Value)>,
+) -> Result<bool> {
+    for target in targets {
+        let len = bindings.len();
+        if match_target(target, value, bindings)? {
+            return Ok(true);
+        }
+        bindings.truncate(len);
+    }
+    Ok(false)
+}
+
+/// Checks if `value` matches the pattern `target`, and collects the variables it binds
+fn match_target<'n>(
+    target: &'n Target<'n>,
+    value: &Value,
+    bindings: &mut Vec<(&'n str, Value)>,
+) -> Result<bool> {
+    let same = |other: Value| {
+        value.same_kind_eq(&other).ok_or(Unsupported::new(
+            "the literal has a different type than the value",
+        ))
+    };
+    match target {
+        Target::Name(name) => {
+            bindings.push((name, value.clone()));
+            Ok(true)
+        }
+        Target::Placeholder(_) => Ok(true),
+        Target::NumLit(_, num) => same(eval_num(num)?),
+        Target::StrLit(StrLit {
+            prefix: None,
+            content,
+        })
+        | Target::CharLit(CharLit {
+            prefix: None,
+            content,
+        }) => same(Value::Str(unescape(content)?.into())),
+        Target::BoolLit(lit) => same(Value::Bool(*lit == "true")),
+        Target::Path(path) if path.as_slice() == ["None"] => Ok(matches!(value, Value::None)),
+        Target::Tuple(path, targets) if path.as_slice() == ["Some"] => match (value, &**targets) {
+            (Value::None, _) => Ok(false),
+            (value, [target]) => match_target(target, value, bindings),
+            _ => Err(Unsupported::new("`Some` has exactly one field")),
+        },
+        Target::Tuple(path, targets) | Target::Array(path, targets) if path.is_empty() => {
+            let Value::List(items) = value else {
+                return Err(Unsupported::new("only lists can be destructured"));
+            };
+            match_items(targets, items, bindings)
+        }
+        Target::OrChain(targets) => match_any(targets, value, bindings),
+        Target::Rest(_) => Err(Unsupported::new(
+            "`..` is only allowed in tuples and arrays",
+        )),
+        _ => Err(Unsupported::new("the pattern is not supported")),
+    }
+}
+
+fn match_items<'n>(
+    targets: &'n [Target<'n>],
+    items: &[Value],
+    bindings: &mut Vec<(&'n str, Value)>,
+) -> Result<bool> {
+    let rest = targets.iter().position(|t| matches!(t, Target::Rest(_)));
+    let (head, tail) = match rest {
+        Some(rest) => (&targets[..rest], &targets[rest + 1..]),
+        None => (targets, &[][..]),
+    };
+    let fits = match rest {
+        Some(_) => items.len() >= head.len() + tail.len(),
+        None => items.len() == head.len(),
+    };
+    if !fits {
+        return Ok(false);
+    }
+
+    if let Some(Target::Rest(name)) = rest.map(|rest| &targets[rest]) {
+        if let Some(name) = **name {
+            let rest = &items[head.len()..items.len() - tail.len()];
+            bindings.push((name, Value::List(rest.into())));
+        }
+    }
+    let tail_items = &items[items.len() - tail.len()..];
+    for (target, item) in head.iter().zip(items).chain(tail.iter().zip(tail_items)) {
+        if !match_target(target, item, bindings)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn cast(value: Value, ty: &str) -> Result<Value> {
+    Ok(match (value, ty) {
+        (Value::Bool(v), "i8" | "i16" | "i32" | "i64" | "i128" | "isize")
+        | (Value::Bool(v), "u8" | "u16" | "u32" | "u64" | "u128" | "usize") => Value::Int(v.into()),
+        (Value::Int(v), "i8" | "i16" | "i32" | "i64" | "i128" | "isize")
+        | (Value::Int(v), "u8" | "u16" | "u32" | "u64" | "u128" | "usize") => Value::Int(v),
+        (Value::Float(v), "i8" | "i16" | "i32" | "i64" | "i128" | "isize")
+        | (Value::Float(v), "u8" | "u16" | "u32" | "u64" | "u128" | "usize") => {
+            Value::Int(v as i128)
+        }
+        (Value::Int(v), "f32" | "f64") => Value::Float(v as f64),
+        (Value::Float(v), "f32" | "f64") => Value::Float(v),
+        (value, ty) => {
+            return Err(Unsupported(
+                format!("cannot cast {} to `{ty}`", value.kind()).into(),
+            ));
+        }
+    })
+}
+
+fn eval_num(num: &Num<'_>) -> Result<Value> {
+    let invalid = |_| Unsupported::new("invalid number");
+    match *num {
+        Num::Int(value, kind) => {
+            if matches!(kind, Some(IntKind::U128)) {
+                return Err(Unsupported::new("`u128` literals are not supported"));
+            }
+            let value = value.replace('_', "");
+            let (radix, digits) = match value.get(..2) {
+                Some("0x") => (16, &value[2..]),
+                Some("0o") => (8, &value[2..]),
+                Some("0b") => (2, &value[2..]),
+                _ => (10, value.as_str()),
+            };
+            i128::from_str_radix(digits, radix)
+                .map(Value::Int)
+                .map_err(invalid)
+        }
+        Num::Float(value, None | Some(rinja_parser::FloatKind::F64)) => value
+            .replace('_', "")
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| Unsupported::new("invalid number")),
+        Num::Float(..) => Err(Unsupported::new("only `f64` literals are supported")),
+    }
+}
+
+/// Resolves the escape sequences in a string or char literal
+fn unescape(content: &str) -> Result<Cow<'_, str>> {
+    if !content.contains('\\') {
+        return Ok(Cow::Borrowed(content));
+    }
+
+    let invalid = Unsupported::new("invalid escape sequence");
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next().ok_or(invalid.clone())? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            c @ ('\\' | '\'' | '"') => c,
+            'x' => {
+                let digits = chars.as_str().get(..2).ok_or(invalid.clone())?;
+                let c = u8::from_str_radix(digits, 16).map_err(|_| invalid.clone())?;
+                chars.nth(1);
+                match c.is_ascii() {
+                    true => c as char,
+                    false => return Err(invalid),
+                }
+            }
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{').ok_or(invalid.clone())?;
+                let (digits, _) = rest.split_once('}').ok_or(invalid.clone())?;
+                let c = u32::from_str_radix(&digits.replace('_', ""), 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(invalid.clone())?;
+                chars.nth(digits.len() + 1);
+                c
+            }
+            _ => return Err(invalid),
+        });
+    }
+    Ok(Cow::Owned(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use rinja_parser::{Ast, Syntax};
+
+    use super::*;
+    use crate::filters::Html;
+
+    fn render(source: &str, fields: &dyn Fn(&str) -> Option<Value>) -> Result<String> {
+        let ast = Ast::from_str(source, None, &Syntax::default()).unwrap();
+        Interpreter::new(Mode::Mirror, &Html, fields, Whitespace::Preserve).render(ast.nodes())
+    }
+
+    #[test]
+    fn test_interpreter() {
+        let fields = |name: &str| match name {
+            "name" => Some(Value::Str("<world>".into())),
+            "items" => Some(Value::List(
+                [Value::Int(1), Value::Int(2), Value::Int(3)].into(),
+            )),
+            _ => None,
+        };
+
+        assert_eq!(
+            render("Hello, {{ name }}!", &fields).unwrap(),
+            "Hello, &#60;world&#62;!"
+        );
+        assert_eq!(render("{{ name|upper|safe }}", &fields).unwrap(), "<WORLD>");
+        assert_eq!(
+            render(
+                "{% for i in items if i != 2 %}{{ loop.index }}:{{ i * 10 }}{% if !loop.last %}, {% endif %}{% endfor %}",
+                &fields,
+            )
+            .unwrap(),
+            "1:10, 2:30",
+        );
+        assert_eq!(
+            render(
+                "{% for i in 0..0 %}{{ i }}{% else %}empty{% endfor %}",
+                &fields
+            )
+            .unwrap(),
+            "empty",
+        );
+        assert_eq!(
+            render("{% let x = \"a\\tb\" %}{{ x ~ 'c' }}", &fields).unwrap(),
+            "a\tbc",
+        );
+
+        assert!(render("{{ unknown }}", &fields).is_err());
+        assert!(render("{{ items }}", &fields).is_err());
+        assert!(render("{{ name.len() }}", &fields).is_err());
+        assert!(render("{% include \"a.html\" %}", &fields).is_err());
+        // Not reproduced exactly by the interpreter, so the compiled code is used instead
+        assert!(render("{% if name is defined %}{% endif %}", &fields).is_err());
+        assert!(render("{% match name %}{% when _ %}{% endmatch %}", &fields).is_err());
+        assert!(render("{{ name|center(9) }}", &fields).is_err());
+    }
+
+    #[test]
+    fn test_field_cache() {
+        let calls = core::cell::Cell::new(0);
+        let fields = |name: &str| {
+            calls.set(calls.get() + 1);
+            match name {
+                "items" => Some(Value::List([Value::Int(1), Value::Int(2)].into())),
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            render(
+                "{% for i in items %}{{ items[i - 1] }}{{ self.items[0] }}{% endfor %}",
+                &fields,
+            )
+            .unwrap(),
+            "1121",
+        );
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let fields = |name: &str| match name {
+            "cond" => Some(Value::Bool(true)),
+            "items" => Some(Value::List([Value::Int(1), Value::Int(2)].into())),
+            _ => None,
+        };
+
+        assert_eq!(
+            render("a  {%- if cond -%}  b  {%- endif %}  c", &fields).unwrap(),
+            "ab  c",
+        );
+        assert_eq!(
+            render(" {% for i in items -%}\n  {{ i }}\n{%- endfor %} ", &fields).unwrap(),
+            " 12 ",
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! Templates that are parsed and interpreted at runtime
+//!
+//! Usually rinja templates are compiled into rust code by the [`Template`][crate::Template]
+//! derive macro. If the template source is only known at runtime, e.g. because it is stored in
+//! a database or edited by your users, then you can use [`dynamic::Template`][Template] instead.
+//! It uses the same parser, the same syntax and the same [filters][crate::filters], but it
+//! renders the template against a [`Value`] tree instead of a rust struct.
+//!
+//! ```
+//! # use std::collections::BTreeMap;
+//! use rinja::dynamic::{Template, ToValue};
+//!
+//! let template = Template::new("Hello, {{ user.name|capitalize }}!")?;
+//! let user = BTreeMap::from([("name", "<world>")]);
+//! let context = BTreeMap::from([("user", user)]);
+//! assert_eq!(template.render(&context)?, "Hello, &#60;world&#62;!");
+//! # Ok::<(), rinja::Error>(())
+//! ```
+//!
+//! With the feature `"serde_json"`, a [`serde_json::Value`] can be used as
+//! context, too, so any type that implements `Serialize` can be rendered.
+//!
+//! As there is no rust code that could be called, the interpreter does not support method and
+//! function calls, custom filters, `{% extends %}`, `{% include %}` and `{% import %}`.
+//! Everything else works like in compiled templates, including `{% match %}`, macros, and
+//! filter blocks.
+
+mod filters;
+pub(crate) mod interpreter;
+mod value;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::TypeId;
+use core::fmt;
+use core::str::FromStr;
+
+pub use rinja_parser::node::Whitespace;
+use rinja_parser::{Parsed, Syntax};
+
+use self::interpreter::{Interpreter, Mode, Unsupported};
+pub use self::value::{ToValue, Value};
+use crate::filters::{Escaper, Html};
+use crate::{Error, Limits, Result};
+
+/// Object safe version of [`Escaper`]
+pub trait DynEscaper {
+    /// Escapes `string` into `dest`
+    fn write_escaped_str(&self, dest: &mut String, string: &str) -> fmt::Result;
+
+    /// Returns `true` for the [`Html`] escaper, which does not escape HTML safe output
+    fn is_html(&self) -> bool;
+}
+
+impl<E: Escaper + 'static> DynEscaper for E {
+    #[inline]
+    fn write_escaped_str(&self, dest: &mut String, string: &str) -> fmt::Result {
+        Escaper::write_escaped_str(self, dest, string)
+    }
+
+    #[inline]
+    fn is_html(&self) -> bool {
+        TypeId::of::<E>() == TypeId::of::<Html>()
+    }
+}
+
+/// A template that is parsed at runtime
+///
+/// By default the output is escaped with [`Html`], whitespace is preserved, and the rendering is
+/// not limited. Use [`with_escaper()`][Self::with_escaper],
+/// [`with_whitespace()`][Self::with_whitespace] and [`with_limits()`][Self::with_limits] to
+/// change that.
+pub struct Template {
+    parsed: Parsed,
+    escaper: Box<dyn DynEscaper + Send + Sync>,
+    whitespace: Whitespace,
+    limits: Limits,
+}
+
+impl Template {
+    /// Parses `source` with the default syntax
+    pub fn new(source: &str) -> Result<Self> {
+        let parsed = Parsed::new(source.into(), None, &Syntax::default()).map_err(Error::custom)?;
+        Ok(Self {
+            parsed,
+            escaper: Box::new(Html),
+            whitespace: Whitespace::Preserve,
+            limits: Limits::default(),
+        })
+    }
+
+    /// Escapes the output of expressions with `escaper`
+    pub fn with_escaper(mut self, escaper: impl Escaper + Send + Sync + 'static) -> Self {
+        self.escaper = Box::new(escaper);
+        self
+    }
+
+    /// Sets the whitespace handling for blocks without `-`, `+` or `~` markers
+    pub fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// Aborts the rendering with [`Error::LimitExceeded`] if it exceeds one of the `limits`
+    ///
+    /// Use it if the template or the context is supplied by your users. Unlike in compiled
+    /// templates, items that are skipped by the `if` condition of a `{% for %}` loop count as
+    /// iterations, too.
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// use rinja::dynamic::Template;
+    /// use rinja::{Error, Limit, Limits};
+    ///
+    /// let template = Template::new("{% for i in 0..n %}{{ i }}{% endfor %}")?.with_limits(Limits {
+    ///     max_bytes: 1024,
+    ///     max_loop_iterations: 100,
+    /// });
+    /// assert_eq!(template.render(&BTreeMap::from([("n", 10)]))?, "0123456789");
+    /// assert!(matches!(
+    ///     template.render(&BTreeMap::from([("n", 1_000_000)])),
+    ///     Err(Error::LimitExceeded(Limit::LoopIterations)),
+    /// ));
+    /// # Ok::<(), rinja::Error>(())
+    /// ```
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Renders the template into a [`String`]
+    ///
+    /// The variables of the template are looked up in `context`, which must convert into a
+    /// [`Value::Map`] with string keys.
+    pub fn render<C: ToValue + ?Sized>(&self, context: &C) -> Result<String> {
+        let context = context.to_value();
+        if !matches!(context, Value::Map(_)) {
+            return Err(Error::custom("the context of a template must be a map"));
+        }
+        let fields = |name: &str| context.attr(name).cloned();
+        let mut interpreter =
+            Interpreter::new(Mode::Standalone, &*self.escaper, &fields, self.whitespace)
+                .with_limits(self.limits);
+        interpreter
+            .render(self.parsed.nodes())
+            .map_err(|Unsupported(msg)| match interpreter.exceeded() {
+                Some(limit) => Error::LimitExceeded(limit),
+                None => Error::custom(String::from(msg)),
+            })
+    }
+
+    /// Renders the template into `writer`
+    pub fn render_into<C: ToValue + ?Sized, W: fmt::Write + ?Sized>(
+        &self,
+        writer: &mut W,
+        context: &C,
+    ) -> Result<()> {
+        writer.write_str(&self.render(context)?)?;
+        Ok(())
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(source: &str) -> Result<Self> {
+        Self::new(source)
+    }
+}
+
+impl fmt::Debug for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Template")
+            .field("source", &self.parsed.source())
+            .field("whitespace", &self.whitespace)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::Limit;
+    use crate::filters::Text;
+
+    fn render(source: &str, context: &BTreeMap<&str, Value>) -> Result<String> {
+        source.parse::<Template>()?.render(context)
+    }
+
+    #[test]
+    fn test_render() {
+        let context = BTreeMap::from([
+            ("name", "<world>".to_value()),
+            ("items", vec![1, 2, 3].to_value()),
+            ("missing", Option::<u8>::None.to_value()),
+        ]);
+
+        assert_eq!(
+            render("Hello, {{ name }}!", &context).unwrap(),
+            "Hello, &#60;world&#62;!"
+        );
+        assert_eq!(
+            render("{{ items|join(\", \") }}", &context).unwrap(),
+            "1, 2, 3"
+        );
+        assert_eq!(
+            render(
+                "{% if x is defined %}x{% else %}{{ name|center(9) }}{% endif %}",
+                &context
+            )
+            .unwrap(),
+            " &#60;world&#62; ",
+        );
+        assert_eq!(
+            render(
+                "{% match missing %}{% when Some(x) %}{{ x }}{% when None %}none{% endmatch %}",
+                &context,
+            )
+            .unwrap(),
+            "none",
+        );
+        assert_eq!(
+            render(
+                "{% for i in items %}{% match i %}\
+                    {% when 1 | 2 %}small{% else %}{{ i }}\
+                {% endmatch %} {% endfor %}",
+                &context,
+            )
+            .unwrap(),
+            "small small 3 ",
+        );
+        assert_eq!(
+            render(
+                "{% let (first, ..) = (1, 2, 3) %}{% let x %}\
+                {% if first == 1 %}{% let x = \"one\" %}{% else %}{% let x = \"?\" %}{% endif %}\
+                {{ x }}",
+                &context,
+            )
+            .unwrap(),
+            "one",
+        );
+    }
+
+    #[test]
+    fn test_macros_and_filter_blocks() {
+        let context = BTreeMap::from([("name", "world".to_value())]);
+
+        assert_eq!(
+            render(
+                "{% macro greet(greeting, name = \"you\") %}{{ greeting }}, {{ name }}!{% endmacro %}\
+                {% call greet(\"Hello\") %} {% call greet(\"Hi\", name = name) %}",
+                &context,
+            )
+            .unwrap(),
+            "Hello, you! Hi, world!",
+        );
+        assert_eq!(
+            render("{% filter upper %}<{{ name }}>{% endfilter %}", &context).unwrap(),
+            "&#60;WORLD&#62;",
+        );
+        assert_eq!(
+            render("{{ \"a\\nb\"|linebreaksbr }}", &context).unwrap(),
+            "a<br/>b"
+        );
+        assert_eq!(
+            render("{{ \"<a>\"|escape(\"txt\") }}", &context).unwrap(),
+            "<a>"
+        );
+        assert_eq!(
+            render("{{ \"'<a>'\"|escapejs }} {{ \"\\n\"|e(\"js\") }}", &context).unwrap(),
+            "\\u0027\\u003Ca\\u003E\\u0027 \\u000A"
+        );
+        assert_eq!(
+            render("{{ \"a:b\"|escape(\"css\") }}", &context).unwrap(),
+            "a\\3a b"
+        );
+        assert_eq!(
+            render(
+                "{{ 1234567|numberformat }} {{ 0.256|percent(1, \"de\") }} \
+                {{ -1234.5|currency(\"EUR\") }}",
+                &context,
+            )
+            .unwrap(),
+            "1,234,567 25,6\u{a0}% -€1,234.50"
+        );
+        assert!(render("{{ 1|numberformat(decimals=2) }}", &context).is_err());
+        assert_eq!(
+            render(
+                "{{ [3, 1, 2]|sort|join(\",\") }} {{ [3, 1, 2]|sort(true)|first }} \
+                {{ [1, 2, 1]|unique|length }} {{ [2, 5, 1]|max }} {{ [1.5, 2.0]|sum }}",
+                &context,
+            )
+            .unwrap(),
+            "1,2,3 3 2 5 3.5"
+        );
+        assert_eq!(
+            render(
+                "{{ [1, 2, 3, 4]|select(\"odd\")|join(\",\") }} \
+                {{ [true, false]|reject|length }} \
+                {% for group in [(1, \"a\"), (2, \"b\"), (1, \"c\")]|groupby(\"0\") -%}
+                    [{{ group.0 }}:{{ group.1|length }}]
+                {%- endfor %}",
+                &context,
+            )
+            .unwrap(),
+            "1,3 1 [1:2][2:1]"
+        );
+        assert_eq!(
+            render(
+                "{% for row in [1, 2, 3]|batch(2, 0) %}[{{ row|join(\",\") }}]{% endfor %} \
+                {% for column in [1, 2, 3]|slice(2) %}[{{ column|join(\",\") }}]{% endfor %}",
+                &context,
+            )
+            .unwrap(),
+            "[1,2][3,0] [1,2][3]"
+        );
+    }
+
+    #[test]
+    fn test_escaper_and_whitespace() {
+        let template = Template::new("  {{ name|linebreaksbr }}  {%- if true %} !{% endif %}")
+            .unwrap()
+            .with_escaper(Text)
+            .with_whitespace(Whitespace::Minimize);
+        let context = BTreeMap::from([("name", "<a>\nb")]);
+        assert_eq!(template.render(&context).unwrap(), " &#60;a&#62;<br/>b !");
+    }
+
+    #[test]
+    fn test_errors() {
+        let context = BTreeMap::new();
+        let error = |source: &str| render(source, &context).unwrap_err().to_string();
+
+        assert!(Template::new("{% if %}").is_err());
+        assert_eq!(error("{{ x }}"), "unknown variable `x`");
+        assert_eq!(error("{{ 1|nope }}"), "unknown filter `nope`");
+        assert_eq!(error("{{ x.len() }}"), "calling functions is not supported");
+        assert_eq!(
+            error("{% include \"a.html\" %}"),
+            "`{% include %}` is not supported"
+        );
+        assert_eq!(
+            error("{% match 1 %}{% when 2 %}{% endmatch %}"),
+            "no arm of `{% match %}` matches the value",
+        );
+        assert_eq!(
+            error("{{ [170141183460469231731687303715884105727, 1]|sum }}"),
+            "the `sum` of the items overflows",
+        );
+        assert!(Template::new("").unwrap().render(&1).is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let context = BTreeMap::new();
+
+        assert_eq!(
+            render(
+                "{% macro m() %}{% call m() %}{% endmacro %}{% call m() %}",
+                &context
+            )
+            .unwrap_err()
+            .to_string(),
+            "macro calls are nested too deeply in `m`",
+        );
+        assert_eq!(
+            render(
+                "{% for i in 0..100000000000 %}{{ i }}{% if loop.index == 3 %}{% break %}{% endif %}\
+                {% endfor %} {% for i in 0..=2 if i != 1 %}{{ loop.last }}{% endfor %}",
+                &context,
+            )
+            .unwrap(),
+            "012 falsetrue",
+        );
+        assert!(render("{{ (0..100000000000)|length }}", &context).is_err());
+
+        let limits = Limits {
+            max_bytes: 8,
+            max_loop_iterations: 5,
+        };
+        let render = |source: &str| {
+            Template::new(source)
+                .unwrap()
+                .with_limits(limits)
+                .render(&context)
+        };
+        assert_eq!(
+            render("{% for i in 0..5 %}{{ i }}{% endfor %}").unwrap(),
+            "01234"
+        );
+        assert!(matches!(
+            render("{% for i in 0..100000000000 %}{% endfor %}"),
+            Err(Error::LimitExceeded(Limit::LoopIterations)),
+        ));
+        assert!(matches!(
+            render("{% for i in 0..100000000000 if false %}{% endfor %}"),
+            Err(Error::LimitExceeded(Limit::LoopIterations)),
+        ));
+        assert!(matches!(
+            render("{{ \"123456789\" }}"),
+            Err(Error::LimitExceeded(Limit::Bytes)),
+        ));
+        assert!(matches!(
+            render("1234{% filter upper %}abcde{% endfilter %}"),
+            Err(Error::LimitExceeded(Limit::Bytes)),
+        ));
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_serde_json() {
+        let context = serde_json::json!({
+            "user": { "name": "world", "admin": true, "tags": ["a", "b"], "age": null },
+        });
+        let template = Template::new(
+            "{% if user.admin %}{{ user.name }}{% endif %} {{ user.tags|json }} {{ user.age|json }}",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "world [&#34;a&#34;,&#34;b&#34;] null"
+        );
+    }
+}
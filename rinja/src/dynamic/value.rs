@@ -0,0 +1,346 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::filters::Safe;
+
+/// A value that can be inspected by the template interpreter
+///
+/// Strings and containers are reference counted, so cloning a value is cheap.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A boolean
+    Bool(bool),
+    /// Any integer that fits into an `i128`
+    Int(i128),
+    /// A 64 bit float
+    Float(f64),
+    /// A string that needs escaping
+    Str(Arc<str>),
+    /// A string that must not be escaped
+    Safe(Arc<str>),
+    /// A sequence, a slice or a tuple
+    List(Arc<[Value]>),
+    /// A sequence of key-value pairs, in iteration order
+    Map(Arc<[(Value, Value)]>),
+    /// A missing value, i.e. `None` or `null`
+    None,
+}
+
+impl Value {
+    /// A short description of the type of the value, used in error messages
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "a boolean",
+            Value::Int(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Str(_) | Value::Safe(_) => "a string",
+            Value::List(_) => "a list",
+            Value::Map(_) => "a map",
+            Value::None => "`None`",
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) | Value::Safe(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the string representation of a value, if it implements `Display` in rust
+    pub(crate) fn to_display(&self) -> Option<Cow<'_, str>> {
+        Some(match self {
+            Value::Bool(true) => Cow::Borrowed("true"),
+            Value::Bool(false) => Cow::Borrowed("false"),
+            Value::Int(v) => Cow::Owned(v.to_string()),
+            Value::Float(v) => Cow::Owned(v.to_string()),
+            Value::Str(s) | Value::Safe(s) => Cow::Borrowed(s),
+            Value::List(_) | Value::Map(_) | Value::None => return None,
+        })
+    }
+
+    pub(crate) fn attr(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::List(items) => items.get(name.parse::<usize>().ok()?),
+            Value::Map(items) => items
+                .iter()
+                .find_map(|(key, value)| (key.as_str() == Some(name)).then_some(value)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn index(&self, index: &Value) -> Option<&Value> {
+        match (self, index) {
+            (Value::List(items), &Value::Int(index)) => items.get(usize::try_from(index).ok()?),
+            (Value::Map(items), index) => items
+                .iter()
+                .find_map(|(key, value)| key.same_kind_eq(index)?.then_some(value)),
+            _ => None,
+        }
+    }
+
+    /// Compares two values, if the comparison would compile in rust
+    pub(crate) fn same_kind_eq(&self, other: &Value) -> Option<bool> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => Some(a == b),
+            (Value::Int(a), Value::Int(b)) => Some(a == b),
+            (Value::Float(a), Value::Float(b)) => Some(a == b),
+            (Value::Str(a) | Value::Safe(a), Value::Str(b) | Value::Safe(b)) => Some(a == b),
+            (Value::List(a), Value::List(b)) => {
+                if a.len() != b.len() {
+                    return Some(false);
+                }
+                for (a, b) in a.iter().zip(b.iter()) {
+                    if !a.same_kind_eq(b)? {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            (Value::None, Value::None) => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Orders two values, if the comparison would compile in rust
+    pub(crate) fn same_kind_cmp(&self, other: &Value) -> Option<core::cmp::Ordering> {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Str(a) | Value::Safe(a), Value::Str(b) | Value::Safe(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+/// Types that can be converted into a [`Value`] losslessly
+pub trait ToValue {
+    /// Converts `self` into a [`Value`]
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! impl_for_int {
+    ($($ty:ty)*) => { $(
+        impl ToValue for $ty {
+            #[inline]
+            fn to_value(&self) -> Value {
+                Value::Int((*self).into())
+            }
+        }
+    )* };
+}
+
+impl_for_int!(i8 i16 i32 i64 i128 u8 u16 u32 u64);
+
+impl ToValue for isize {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Int(*self as i128)
+    }
+}
+
+impl ToValue for usize {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Int(*self as i128)
+    }
+}
+
+impl ToValue for bool {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToValue for f64 {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Float(*self)
+    }
+}
+
+impl ToValue for char {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Str(self.to_string().into())
+    }
+}
+
+impl ToValue for str {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Str(self.into())
+    }
+}
+
+impl ToValue for String {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Str(self.as_str().into())
+    }
+}
+
+impl ToValue for Cow<'_, str> {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Str(self.as_ref().into())
+    }
+}
+
+impl<T: fmt::Display> ToValue for Safe<T> {
+    #[inline]
+    fn to_value(&self) -> Value {
+        Value::Safe(self.0.to_string().into())
+    }
+}
+
+impl<T: ToValue> ToValue for [T] {
+    fn to_value(&self) -> Value {
+        Value::List(self.iter().map(T::to_value).collect())
+    }
+}
+
+impl<T: ToValue, const N: usize> ToValue for [T; N] {
+    #[inline]
+    fn to_value(&self) -> Value {
+        self.as_slice().to_value()
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    #[inline]
+    fn to_value(&self) -> Value {
+        self.as_slice().to_value()
+    }
+}
+
+impl<T: ToValue> ToValue for VecDeque<T> {
+    fn to_value(&self) -> Value {
+        Value::List(self.iter().map(T::to_value).collect())
+    }
+}
+
+impl<K: ToValue, V: ToValue> ToValue for BTreeMap<K, V> {
+    fn to_value(&self) -> Value {
+        Value::Map(
+            self.iter()
+                .map(|(k, v)| (k.to_value(), v.to_value()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: ToValue, V: ToValue, S> ToValue for std::collections::HashMap<K, V, S> {
+    fn to_value(&self) -> Value {
+        Value::Map(
+            self.iter()
+                .map(|(k, v)| (k.to_value(), v.to_value()))
+                .collect(),
+        )
+    }
+}
+
+impl ToValue for Value {
+    #[inline]
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    #[inline]
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::None,
+        }
+    }
+}
+
+macro_rules! impl_for_ref {
+    ($($ty:ty)*) => { $(
+        impl<T: ToValue + ?Sized> ToValue for $ty {
+            #[inline]
+            fn to_value(&self) -> Value {
+                T::to_value(self)
+            }
+        }
+    )* };
+}
+
+impl_for_ref!(&T &mut T Box<T> Rc<T> Arc<T>);
+
+macro_rules! impl_for_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: ToValue),+> ToValue for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn to_value(&self) -> Value {
+                let ($($name,)+) = self;
+                Value::List(Arc::new([$($name.to_value()),+]))
+            }
+        }
+    };
+}
+
+impl_for_tuple!(A);
+impl_for_tuple!(A B);
+impl_for_tuple!(A B C);
+impl_for_tuple!(A B C D);
+
+#[cfg(feature = "serde_json")]
+impl ToValue for serde_json::Value {
+    fn to_value(&self) -> Value {
+        match self {
+            serde_json::Value::Null => Value::None,
+            serde_json::Value::Bool(value) => Value::Bool(*value),
+            serde_json::Value::Number(value) => match (value.as_i64(), value.as_u64()) {
+                (Some(value), _) => Value::Int(value.into()),
+                (_, Some(value)) => Value::Int(value.into()),
+                _ => Value::Float(value.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(value) => Value::Str(value.as_str().into()),
+            serde_json::Value::Array(items) => items.as_slice().to_value(),
+            serde_json::Value::Object(items) => Value::Map(
+                items
+                    .iter()
+                    .map(|(key, value)| (key.to_value(), value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        match self {
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Int(value) => serializer.serialize_i128(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::Str(value) | Value::Safe(value) => serializer.serialize_str(value),
+            Value::List(items) => serializer.collect_seq(items.iter()),
+            Value::Map(items) => {
+                let mut map = serializer.serialize_map(Some(items.len()))?;
+                for (key, value) in items.iter() {
+                    let key = key
+                        .to_display()
+                        .ok_or_else(|| S::Error::custom("map keys must be displayable"))?;
+                    map.serialize_entry(&*key, value)?;
+                }
+                map.end()
+            }
+            Value::None => serializer.serialize_none(),
+        }
+    }
+}
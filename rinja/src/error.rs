@@ -1,9 +1,17 @@
-use std::convert::Infallible;
-use std::error::Error as StdError;
-use std::{fmt, io};
+use alloc::boxed::Box;
+use core::convert::Infallible;
+#[cfg(not(feature = "std"))]
+pub(crate) use core::error::Error as StdError;
+use core::fmt;
+#[cfg(feature = "std")]
+pub(crate) use std::error::Error as StdError;
+#[cfg(feature = "std")]
+use std::io;
 
-/// The [`Result`](std::result::Result) type with [`Error`] as default error type
-pub type Result<I, E = Error> = std::result::Result<I, E>;
+use crate::Limit;
+
+/// The [`Result`](core::result::Result) type with [`Error`] as default error type
+pub type Result<I, E = Error> = core::result::Result<I, E>;
 
 /// rinja's error type
 ///
@@ -15,10 +23,16 @@ pub enum Error {
     /// Generic, unspecified formatting error
     Fmt,
     /// An error raised by using `?` in a template
+    ///
+    /// If the [`Location`] of the error is known, the error is wrapped in a [`Located`].
+    /// Use [`Error::downcast_ref()`] to access the original error in either case.
     Custom(Box<dyn StdError + Send + Sync>),
     /// JSON conversion error
     #[cfg(feature = "serde_json")]
     Json(serde_json::Error),
+    /// A budget of [`Template::render_with_limits()`][crate::Template::render_with_limits()]
+    /// was exceeded
+    LimitExceeded(Limit),
 }
 
 impl Error {
@@ -28,6 +42,53 @@ impl Error {
         Self::Custom(err.into())
     }
 
+    /// Attach the [`Location`] in a template where this [`Error`] occurred
+    ///
+    /// Only [`Error::Custom`] can store a location, the variant of the error is not changed.
+    /// If the error already has a location, then the innermost location is retained.
+    #[must_use]
+    pub fn with_location(self, location: &'static Location) -> Self {
+        match self {
+            Error::Custom(err) if !err.is::<Located>() => {
+                Error::Custom(Box::new(Located { err, location }))
+            }
+            err => err,
+        }
+    }
+
+    /// The [`Location`] in a template where this [`Error`] occurred, if known
+    ///
+    /// Errors raised by `?` in a template expression, or by a custom filter, know their location.
+    #[inline]
+    pub fn location(&self) -> Option<&'static Location> {
+        match self {
+            Error::Custom(err) => Some(err.downcast_ref::<Located>()?.location),
+            _ => None,
+        }
+    }
+
+    /// Returns the error of an [`Error::Custom`] if it is of type `T`
+    ///
+    /// Unlike calling `downcast_ref()` on the boxed error, this method looks through the
+    /// [`Located`] wrapper that stores the [`Location`] of the error.
+    pub fn downcast_ref<T: StdError + 'static>(&self) -> Option<&T> {
+        match self {
+            Error::Custom(err) => match err.downcast_ref::<Located>() {
+                Some(located) => located.inner().downcast_ref(),
+                None => err.downcast_ref(),
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is an [`Error::Custom`] with an error of type `T`
+    ///
+    /// See [`Error::downcast_ref()`].
+    #[inline]
+    pub fn is<T: StdError + 'static>(&self) -> bool {
+        self.downcast_ref::<T>().is_some()
+    }
+
     /// Convert this [`Error`] into a
     /// <code>[Box]&lt;dyn [StdError] + [Send] + [Sync]&gt;</code>
     pub fn into_box(self) -> Box<dyn StdError + Send + Sync> {
@@ -36,18 +97,29 @@ impl Error {
             Error::Custom(err) => err,
             #[cfg(feature = "serde_json")]
             Error::Json(err) => err.into(),
+            Error::LimitExceeded(limit) => limit.into(),
         }
     }
 
     /// Convert this [`Error`] into an [`io::Error`]
     ///
     /// Not this error itself, but the contained [`source`][StdError::source] is returned.
+    /// If the source is an [`io::Error`], then it is returned as is, without its [`Location`],
+    /// so its [`kind()`][io::Error::kind] is retained.
+    #[cfg(feature = "std")]
     pub fn into_io_error(self) -> io::Error {
         io::Error::other(match self {
-            Error::Custom(err) => match err.downcast() {
-                Ok(err) => return *err,
-                Err(err) => err,
-            },
+            Error::Custom(err) => {
+                let err = match err.downcast::<Located>() {
+                    Ok(located) if located.err.is::<io::Error>() => located.err,
+                    Ok(located) => located,
+                    Err(err) => err,
+                };
+                match err.downcast() {
+                    Ok(err) => return *err,
+                    Err(err) => err,
+                }
+            }
             err => err.into_box(),
         })
     }
@@ -60,6 +132,7 @@ impl StdError for Error {
             Error::Custom(err) => Some(err.as_ref()),
             #[cfg(feature = "serde_json")]
             Error::Json(err) => Some(err),
+            Error::LimitExceeded(limit) => Some(limit),
         }
     }
 }
@@ -71,10 +144,108 @@ impl fmt::Display for Error {
             Error::Custom(err) => err.fmt(f),
             #[cfg(feature = "serde_json")]
             Error::Json(err) => err.fmt(f),
+            Error::LimitExceeded(limit) => limit.fmt(f),
         }
     }
 }
 
+/// The payload of an [`Error::Custom`] that was raised at a known [`Location`]
+#[derive(Debug)]
+pub struct Located {
+    err: Box<dyn StdError + Send + Sync>,
+    location: &'static Location,
+}
+
+impl Located {
+    /// The error that was raised
+    #[inline]
+    pub fn inner(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self.err.as_ref()
+    }
+
+    /// Returns the error that was raised, without its location
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn StdError + Send + Sync> {
+        self.err
+    }
+
+    /// The [`Location`] in a template where the error was raised
+    #[inline]
+    pub fn location(&self) -> &'static Location {
+        self.location
+    }
+}
+
+impl StdError for Located {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.err.as_ref())
+    }
+}
+
+impl fmt::Display for Located {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.err)
+    }
+}
+
+/// The position of an expression in a template, used to locate an [`Error`]
+///
+/// Its [`Display`][fmt::Display] implementation looks like
+/// `templates/invoice.html:42:7 in {{ total()? }}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    path: &'static str,
+    row: usize,
+    column: usize,
+    source: &'static str,
+}
+
+impl Location {
+    #[doc(hidden)]
+    pub const fn new(path: &'static str, row: usize, column: usize, source: &'static str) -> Self {
+        Self {
+            path,
+            row,
+            column,
+            source,
+        }
+    }
+
+    /// The path of the template file, relative to the crate that was being compiled
+    #[inline]
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The line in the template file, starting at `1`
+    #[inline]
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// The column of the expression in its line, starting at `1`
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The source snippet of the tag or expression that contains the erroneous expression
+    #[inline]
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{} in {}",
+            self.path, self.row, self.column, self.source
+        )
+    }
+}
+
 impl From<Error> for fmt::Error {
     #[inline]
     fn from(_: Error) -> Self {
@@ -82,6 +253,7 @@ impl From<Error> for fmt::Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Error> for io::Error {
     #[inline]
     fn from(err: Error) -> Self {
@@ -105,6 +277,7 @@ impl From<Box<dyn StdError + Send + Sync>> for Error {
 }
 
 /// This conversion inspects the argument and chooses the best fitting [`Error`] variant
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     #[inline]
     fn from(err: io::Error) -> Self {
@@ -114,6 +287,7 @@ impl From<io::Error> for Error {
 
 const MAX_ERROR_UNWRAP_COUNT: usize = 5;
 
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
 fn error_from_stderror(err: Box<dyn StdError + Send + Sync>, unwraps: usize) -> Error {
     let Some(unwraps) = unwraps.checked_sub(1) else {
         return Error::Custom(err);
@@ -126,6 +300,7 @@ fn error_from_stderror(err: Box<dyn StdError + Send + Sync>, unwraps: usize) ->
             Ok(err) => Error::Json(*err),
             Err(_) => Error::Fmt, // unreachable
         },
+        #[cfg(feature = "std")]
         ErrorKind::Io => match err.downcast() {
             Ok(err) => from_from_io_error(*err, unwraps),
             Err(_) => Error::Fmt, // unreachable
@@ -137,6 +312,7 @@ fn error_from_stderror(err: Box<dyn StdError + Send + Sync>, unwraps: usize) ->
     }
 }
 
+#[cfg(feature = "std")]
 fn from_from_io_error(err: io::Error, unwraps: usize) -> Error {
     let Some(inner) = err.get_ref() else {
         return Error::custom(err);
@@ -174,6 +350,7 @@ enum ErrorKind {
     Custom,
     #[cfg(feature = "serde_json")]
     Json,
+    #[cfg(feature = "std")]
     Io,
     Rinja,
 }
@@ -182,11 +359,13 @@ impl ErrorKind {
     fn inspect(err: &(dyn StdError + 'static)) -> ErrorKind {
         if err.is::<fmt::Error>() {
             ErrorKind::Fmt
-        } else if err.is::<io::Error>() {
-            ErrorKind::Io
         } else if err.is::<Error>() {
             ErrorKind::Rinja
         } else {
+            #[cfg(feature = "std")]
+            if err.is::<io::Error>() {
+                return ErrorKind::Io;
+            }
             #[cfg(feature = "serde_json")]
             if err.is::<serde_json::Error>() {
                 return ErrorKind::Json;
@@ -211,8 +390,18 @@ impl From<Infallible> for Error {
     }
 }
 
+/// Render errors are reported as `500 Internal Server Error` with an empty body, so that the
+/// error message does not leak to the client.
+#[cfg(feature = "with-actix-web")]
+impl actix_web::ResponseError for Error {
+    #[inline]
+    fn error_response(&self) -> actix_web::HttpResponse {
+        actix_web::HttpResponse::InternalServerError().finish()
+    }
+}
+
 #[cfg(test)]
 const _: () = {
-    trait AssertSendSyncStatic: Send + Sync + 'static {}
-    impl AssertSendSyncStatic for Error {}
+    const fn assert_send_sync_static<T: Send + Sync + 'static>() {}
+    assert_send_sync_static::<Error>();
 };
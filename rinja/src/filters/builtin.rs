@@ -1,8 +1,11 @@
-use std::cell::Cell;
-use std::convert::Infallible;
-use std::fmt::{self, Write};
-use std::ops::Deref;
-use std::pin::Pin;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::fmt::{self, Write};
+use core::ops::Deref;
+use core::pin::Pin;
 
 use super::escape::{FastWritable, HtmlSafeOutput};
 use crate::{Error, Result};
@@ -930,7 +933,7 @@ const _: () = {
     /// implement `PluralizeCount` for non-zero integer types
     macro_rules! impl_pluralize_for_non_zero {
         ($($ty:ident)*) => { $(
-            impl PluralizeCount for std::num::$ty {
+            impl PluralizeCount for core::num::$ty {
                 type Error = Infallible;
 
                 #[inline]
@@ -974,6 +977,10 @@ impl<S: FastWritable, P: FastWritable> FastWritable for Pluralize<S, P> {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
     use super::*;
 
     #[test]
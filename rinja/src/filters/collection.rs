@@ -0,0 +1,1087 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::Infallible;
+use core::iter::{FusedIterator, Take};
+
+use crate::helpers::{CheckedAdd, PrimitiveType};
+
+/// Returns the number of items in a collection
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ items|length }} items</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     items: &'a [&'a str],
+/// }
+///
+/// assert_eq!(
+///     Example { items: &["a", "b", "c"] }.to_string(),
+///     "<div>3 items</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn length<I: IntoIterator>(iter: I) -> Result<usize, Infallible> {
+    let iter = iter.into_iter();
+    Ok(match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => lower,
+        _ => iter.count(),
+    })
+}
+
+/// Returns the first item of a collection, or `None` if it is empty
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% if let Some(user) = users|first -%}
+/// ///     <div>First: {{ user }}</div>
+/// /// {%- endif %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     users: &'a [&'a str],
+/// }
+///
+/// assert_eq!(
+///     Example { users: &["alice", "bob"] }.to_string(),
+///     "<div>First: alice</div>"
+/// );
+/// assert_eq!(Example { users: &[] }.to_string(), "");
+/// # }
+/// ```
+#[inline]
+pub fn first<I: IntoIterator>(iter: I) -> Result<Option<I::Item>, Infallible> {
+    Ok(iter.into_iter().next())
+}
+
+/// Returns the last item of a collection, or `None` if it is empty
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% if let Some(user) = users|last -%}
+/// ///     <div>Last: {{ user }}</div>
+/// /// {%- endif %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     users: &'a [&'a str],
+/// }
+///
+/// assert_eq!(
+///     Example { users: &["alice", "bob"] }.to_string(),
+///     "<div>Last: bob</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn last<I: IntoIterator>(iter: I) -> Result<Option<I::Item>, Infallible> {
+    Ok(iter.into_iter().last())
+}
+
+/// Iterates over a collection in reverse order
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <ul>{% for step in steps|reverse %}<li>{{ step }}</li>{% endfor %}</ul>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     steps: Vec<u32>,
+/// }
+///
+/// assert_eq!(
+///     Example { steps: vec![1, 2, 3] }.to_string(),
+///     "<ul><li>3</li><li>2</li><li>1</li></ul>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn reverse<I>(iter: I) -> Result<Reverse<I::IntoIter>, Infallible>
+where
+    I: IntoIterator,
+    I::IntoIter: DoubleEndedIterator,
+{
+    Ok(Reverse(iter.into_iter()))
+}
+
+/// Result of the filter [`reverse()`]
+#[derive(Debug, Clone)]
+pub struct Reverse<I>(I);
+
+impl<I: DoubleEndedIterator> Iterator for Reverse<I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Reverse<I> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<I: DoubleEndedIterator + ExactSizeIterator> ExactSizeIterator for Reverse<I> {}
+
+impl<I: DoubleEndedIterator + FusedIterator> FusedIterator for Reverse<I> {}
+
+impl<I: DoubleEndedIterator + Clone> IntoIterator for &Reverse<I> {
+    type Item = I::Item;
+    type IntoIter = Reverse<I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// Returns the items of a collection in ascending order, or descending if `reverse` is `true`
+///
+/// The sort is stable, i.e. equal items keep their order. Items that cannot be compared,
+/// e.g. a `NaN` float, are treated as equal.
+///
+/// In a template, you can pass `reverse` as named argument, and you can pass the name of an
+/// `attribute` to sort by, e.g. `sort(attribute="user.name")`. Then [`sort_by_key()`] is used.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ scores|sort|join(", ") }} / {{ scores|sort(reverse=true)|join(", ") }}</div>
+/// /// <div>{% for p in players|sort(attribute="name") %}{{ p.name }} {% endfor %}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     scores: &'a [u32],
+///     players: &'a [Player<'a>],
+/// }
+///
+/// struct Player<'a> {
+///     name: &'a str,
+/// }
+///
+/// assert_eq!(
+///     Example {
+///         scores: &[3, 1, 2],
+///         players: &[Player { name: "bob" }, Player { name: "alice" }],
+///     }
+///     .to_string(),
+///     "<div>1, 2, 3 / 3, 2, 1</div>\n<div>alice bob </div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn sort<I>(iter: I, reverse: bool) -> Result<Vec<I::Item>, Infallible>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    sort_by_key(iter, |item| item, reverse)
+}
+
+/// Returns the items of a collection ordered by a key, which is used by `sort(attribute=...)`
+///
+/// Otherwise, this function works like [`sort()`].
+pub fn sort_by_key<I, F, K>(iter: I, key: F, reverse: bool) -> Result<Vec<I::Item>, Infallible>
+where
+    I: IntoIterator,
+    F: Fn(&I::Item) -> &K,
+    K: PartialOrd + ?Sized,
+{
+    let mut items: Vec<_> = iter.into_iter().collect();
+    let compare = |a: &I::Item, b: &I::Item| key(a).partial_cmp(key(b)).unwrap_or(Ordering::Equal);
+    match reverse {
+        false => items.sort_by(|a, b| compare(a, b)),
+        true => items.sort_by(|a, b| compare(b, a)),
+    }
+    Ok(items)
+}
+
+/// Returns the items of a collection without duplicates, keeping the first occurrence
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ tags|unique|join(", ") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     tags: &'a [&'a str],
+/// }
+///
+/// assert_eq!(
+///     Example { tags: &["rust", "web", "rust", "html", "web"] }.to_string(),
+///     "<div>rust, web, html</div>"
+/// );
+/// # }
+/// ```
+pub fn unique<I>(iter: I) -> Result<Vec<I::Item>, Infallible>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    let items: Vec<_> = iter.into_iter().collect();
+    // The sort is stable, so the first occurrence of each item comes first in `order`.
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| items[a].cmp(&items[b]));
+    let mut keep = vec![true; items.len()];
+    for pair in order.windows(2) {
+        if items[pair[0]] == items[pair[1]] {
+            keep[pair[1]] = false;
+        }
+    }
+    Ok(items
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(item))
+        .collect())
+}
+
+/// Returns the smallest item of a collection, or `None` if it is empty
+///
+/// If there are multiple smallest items, the first one is returned.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% if let (Some(min), Some(max)) = (prices|min, prices|max) -%}
+/// ///     <div>{{ min }} – {{ max }}</div>
+/// /// {%- endif %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     prices: &'a [f32],
+/// }
+///
+/// assert_eq!(
+///     Example { prices: &[4.5, 1.25, 9.0] }.to_string(),
+///     "<div>1.25 – 9</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn min<I>(iter: I) -> Result<Option<I::Item>, Infallible>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    Ok(iter
+        .into_iter()
+        .reduce(|min, item| if item < min { item } else { min }))
+}
+
+/// Returns the largest item of a collection, or `None` if it is empty
+///
+/// If there are multiple largest items, the first one is returned.
+/// See [`min()`] for an example.
+#[inline]
+pub fn max<I>(iter: I) -> Result<Option<I::Item>, Infallible>
+where
+    I: IntoIterator,
+    I::Item: PartialOrd,
+{
+    Ok(iter
+        .into_iter()
+        .reduce(|max, item| if item > max { item } else { max }))
+}
+
+/// Returns the sum of all [primitive](PrimitiveType) numbers in a collection
+///
+/// Fails if the sum of integers overflows their type.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>Total: {{ items|sum }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     items: Vec<u64>,
+/// }
+///
+/// assert_eq!(
+///     Example { items: vec![10, 20, 12] }.to_string(),
+///     "<div>Total: 42</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn sum<I>(iter: I) -> crate::Result<<I::Item as PrimitiveType>::Value>
+where
+    I: IntoIterator,
+    I::Item: PrimitiveType,
+    <I::Item as PrimitiveType>::Value: CheckedAdd,
+{
+    iter.into_iter()
+        .map(|item| item.get())
+        .try_fold(CheckedAdd::ZERO, CheckedAdd::checked_add)
+        .ok_or_else(|| crate::Error::custom("the `sum` of the items overflows"))
+}
+
+/// Applies a function to every item of a collection, lazily
+///
+/// In a template, you can pass a function, e.g. `map(crate::slugify)`, or the name of an
+/// `attribute` to extract from every item, e.g. `map(attribute="user.email")`. If you pass both,
+/// the function is applied to a reference to the attribute. Without a function, the items must be
+/// references, e.g. the items of a field or of a variable, because the attribute is borrowed.
+///
+/// The result can be iterated over, or passed on to other filters like [`join()`]:
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ users|map(attribute="email")|join(", ") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     users: &'a [User<'a>],
+/// }
+///
+/// struct User<'a> {
+///     email: &'a str,
+/// }
+///
+/// assert_eq!(
+///     Example {
+///         users: &[User { email: "ann@example.com" }, User { email: "bob@example.com" }],
+///     }
+///     .to_string(),
+///     "<div>ann@example.com, bob@example.com</div>"
+/// );
+/// # }
+/// ```
+///
+/// [`join()`]: super::join
+#[inline]
+pub fn map<I, F, T>(iter: I, f: F) -> Result<Map<I::IntoIter, F>, Infallible>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> T,
+{
+    Ok(Map {
+        iter: iter.into_iter(),
+        f,
+    })
+}
+
+/// Result of the filter [`map()`]
+#[derive(Debug, Clone)]
+pub struct Map<I, F> {
+    iter: I,
+    f: F,
+}
+
+impl<I, F, T> Iterator for Map<I, F>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> T,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(&mut self.f)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, F, T> DoubleEndedIterator for Map<I, F>
+where
+    I: DoubleEndedIterator,
+    F: FnMut(I::Item) -> T,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(&mut self.f)
+    }
+}
+
+impl<I, F, T> ExactSizeIterator for Map<I, F>
+where
+    I: ExactSizeIterator,
+    F: FnMut(I::Item) -> T,
+{
+}
+
+impl<I, F, T> FusedIterator for Map<I, F>
+where
+    I: FusedIterator,
+    F: FnMut(I::Item) -> T,
+{
+}
+
+impl<I, F, T> IntoIterator for &Map<I, F>
+where
+    I: Iterator + Clone,
+    F: FnMut(I::Item) -> T + Clone,
+{
+    type Item = T;
+    type IntoIter = Map<I, F>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// Keeps the items of a collection that pass a test, lazily
+///
+/// In a template, you can pass the name of a test, or a function that returns a `bool`.
+/// The known tests are `"even"` and `"odd"` for integers, `"some"` and `"none"` for [`Option`]s,
+/// and `"true"` and `"false"` for `bool`s. Without a test, the items are tested for being `true`.
+/// Like in [`map()`], you can pass the name of an `attribute` to test instead of the item.
+///
+/// [`reject()`] keeps the items that fail the test instead.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ numbers|select("odd")|join(", ") }} / {{ numbers|reject("odd")|join(", ") }}</div>
+/// /// <div>{% for task in tasks|select(attribute="done") %}{{ task.name }} {% endfor %}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     numbers: &'a [u32],
+///     tasks: &'a [Task<'a>],
+/// }
+///
+/// struct Task<'a> {
+///     name: &'a str,
+///     done: bool,
+/// }
+///
+/// assert_eq!(
+///     Example {
+///         numbers: &[1, 2, 3, 4],
+///         tasks: &[Task { name: "write", done: true }, Task { name: "test", done: false }],
+///     }
+///     .to_string(),
+///     "<div>1, 3 / 2, 4</div>\n<div>write </div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn select<I, P>(iter: I, predicate: P) -> Result<Select<I::IntoIter, P>, Infallible>
+where
+    I: IntoIterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    Ok(Select {
+        iter: iter.into_iter(),
+        predicate,
+        keep: true,
+    })
+}
+
+/// Removes the items of a collection that pass a test, lazily
+///
+/// This is the opposite of [`select()`].
+#[inline]
+pub fn reject<I, P>(iter: I, predicate: P) -> Result<Select<I::IntoIter, P>, Infallible>
+where
+    I: IntoIterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    Ok(Select {
+        iter: iter.into_iter(),
+        predicate,
+        keep: false,
+    })
+}
+
+/// Result of the filters [`select()`] and [`reject()`]
+#[derive(Debug, Clone)]
+pub struct Select<I, P> {
+    iter: I,
+    predicate: P,
+    keep: bool,
+}
+
+impl<I, P> Iterator for Select<I, P>
+where
+    I: Iterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let Self {
+            iter,
+            predicate,
+            keep,
+        } = self;
+        iter.find(|item| predicate(item) == *keep)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<I, P> DoubleEndedIterator for Select<I, P>
+where
+    I: DoubleEndedIterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let Self {
+            iter,
+            predicate,
+            keep,
+        } = self;
+        iter.rfind(|item| predicate(item) == *keep)
+    }
+}
+
+impl<I, P> FusedIterator for Select<I, P>
+where
+    I: FusedIterator,
+    P: FnMut(&I::Item) -> bool,
+{
+}
+
+impl<I, P> IntoIterator for &Select<I, P>
+where
+    I: Iterator + Clone,
+    P: FnMut(&I::Item) -> bool + Clone,
+{
+    type Item = I::Item;
+    type IntoIter = Select<I, P>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// Groups the items of a collection by a key, which is used by `groupby("attribute")`
+///
+/// The groups are `(key, items)` tuples, ordered by their key. The items in a group keep their
+/// order. Keys that cannot be compared, e.g. a `NaN` float, end up in groups of their own.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% for (city, people) in people|groupby("address.city") -%}
+/// ///     <p>{{ city }}: {{ people|map(attribute="name")|join(", ") }}</p>
+/// /// {% endfor %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     people: &'a [Person<'a>],
+/// }
+///
+/// struct Person<'a> {
+///     name: &'a str,
+///     address: Address<'a>,
+/// }
+///
+/// struct Address<'a> {
+///     city: &'a str,
+/// }
+///
+/// let person = |name, city| Person { name, address: Address { city } };
+/// assert_eq!(
+///     Example {
+///         people: &[person("ann", "Paris"), person("bob", "Oslo"), person("eve", "Paris")],
+///     }
+///     .to_string(),
+///     "<p>Oslo: bob</p>\n<p>Paris: ann, eve</p>\n"
+/// );
+/// # }
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn groupby<I, F, K>(iter: I, key: F) -> Result<Vec<(K, Vec<I::Item>)>, Infallible>
+where
+    I: IntoIterator,
+    F: Fn(&I::Item) -> &K,
+    K: PartialOrd + Clone,
+{
+    let mut groups: Vec<(K, Vec<I::Item>)> = Vec::new();
+    for item in sort_by_key(iter, &key, false)? {
+        match groups.last_mut() {
+            Some((group, items)) if group == key(&item) => items.push(item),
+            _ => groups.push((key(&item).clone(), vec![item])),
+        }
+    }
+    Ok(groups)
+}
+
+/// Splits a collection into rows of `size` items, lazily
+///
+/// If `fill_with` is given, the last row is padded with it to `size` items. Like the items,
+/// `fill_with` is borrowed in a template, e.g. `batch(3, fill_with=placeholder)`.
+///
+/// The rows are [`Chunk`]s that iterate over the items again, so no items are collected.
+/// This makes the filter suitable for `{% for %}` loops, including `loop.last`.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% for row in products|batch(2) -%}
+/// ///     <tr>{% for product in row %}<td>{{ product }}</td>{% endfor %}</tr>
+/// ///     {%- if !loop.last %}|{% endif %}
+/// /// {% endfor -%}
+/// /// {% for row in products|batch(4, fill_with="-") %}<p>{{ row|join(" ") }}</p>{% endfor %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     products: &'a [&'a str],
+/// }
+///
+/// assert_eq!(
+///     Example { products: &["tea", "coffee", "cake"] }.to_string(),
+///     "<tr><td>tea</td><td>coffee</td></tr>|\n<tr><td>cake</td></tr>\n<p>tea coffee cake -</p>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn batch<I>(
+    iter: I,
+    size: usize,
+    fill_with: Option<I::Item>,
+) -> crate::Result<Batch<I::IntoIter>>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: Clone,
+{
+    if size == 0 {
+        return Err(crate::Error::custom(
+            "the size of a `batch` must not be zero",
+        ));
+    }
+    Ok(Batch {
+        iter: iter.into_iter(),
+        size,
+        fill_with,
+    })
+}
+
+/// Result of the filter [`batch()`]
+#[derive(Debug, Clone)]
+pub struct Batch<I: Iterator> {
+    iter: I,
+    size: usize,
+    fill_with: Option<I::Item>,
+}
+
+impl<I> Iterator for Batch<I>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    type Item = Chunk<I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.iter.clone();
+        let len = advance(&mut self.iter, self.size);
+        if len == 0 {
+            return None;
+        }
+        let missing = match self.fill_with {
+            Some(_) => self.size - len,
+            None => 0,
+        };
+        Some(Chunk {
+            iter: start.take(len),
+            fill_with: self.fill_with.clone(),
+            missing,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (
+            lower.div_ceil(self.size),
+            upper.map(|upper| upper.div_ceil(self.size)),
+        )
+    }
+}
+
+impl<I> FusedIterator for Batch<I>
+where
+    I: FusedIterator + Clone,
+    I::Item: Clone,
+{
+}
+
+impl<I> IntoIterator for &Batch<I>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    type Item = Chunk<I>;
+    type IntoIter = Batch<I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// Splits a collection into `count` columns of (almost) equal length, lazily
+///
+/// The first columns get an extra item if the items cannot be distributed evenly.
+/// There are always `count` columns, even if some are empty.
+///
+/// Like [`batch()`], the columns are [`Chunk`]s that iterate over the items again.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// {% for column in letters|slice(3) -%}
+/// ///     <ul>{% for letter in column %}<li>{{ letter }}</li>{% endfor %}</ul>
+/// /// {% endfor %}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     letters: &'a [char],
+/// }
+///
+/// assert_eq!(
+///     Example { letters: &['a', 'b', 'c', 'd'] }.to_string(),
+///     "<ul><li>a</li><li>b</li></ul>\n<ul><li>c</li></ul>\n<ul><li>d</li></ul>\n"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn slice<I>(iter: I, count: usize) -> crate::Result<Slice<I::IntoIter>>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+{
+    if count == 0 {
+        return Err(crate::Error::custom(
+            "the number of `slice`s must not be zero",
+        ));
+    }
+    let iter = iter.into_iter();
+    let len = iter.clone().count();
+    Ok(Slice {
+        iter,
+        remaining: count,
+        len: len / count,
+        extra: len % count,
+    })
+}
+
+/// Result of the filter [`slice()`]
+#[derive(Debug, Clone)]
+pub struct Slice<I> {
+    iter: I,
+    /// The number of columns that were not returned yet
+    remaining: usize,
+    /// The number of items in a column, without the extra item
+    len: usize,
+    /// The number of columns that get an extra item
+    extra: usize,
+}
+
+impl<I: Iterator + Clone> Iterator for Slice<I> {
+    type Item = Chunk<I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        let len = match self.extra.checked_sub(1) {
+            Some(extra) => {
+                self.extra = extra;
+                self.len + 1
+            }
+            None => self.len,
+        };
+        let start = self.iter.clone();
+        advance(&mut self.iter, len);
+        Some(Chunk {
+            iter: start.take(len),
+            fill_with: None,
+            missing: 0,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I: Iterator + Clone> ExactSizeIterator for Slice<I> {}
+
+impl<I: Iterator + Clone> FusedIterator for Slice<I> {}
+
+impl<I: Iterator + Clone> IntoIterator for &Slice<I> {
+    type Item = Chunk<I>;
+    type IntoIter = Slice<I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// A row of [`batch()`] or a column of [`slice()`]
+///
+/// A chunk can be iterated over by reference, too, e.g. to get its `length` before looping
+/// over it.
+#[derive(Debug, Clone)]
+pub struct Chunk<I: Iterator> {
+    iter: Take<I>,
+    fill_with: Option<I::Item>,
+    /// The number of times `fill_with` is returned after the items
+    missing: usize,
+}
+
+impl<I> Iterator for Chunk<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.iter.next() {
+            return Some(item);
+        }
+        self.missing = self.missing.checked_sub(1)?;
+        self.fill_with.clone()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (
+            lower.saturating_add(self.missing),
+            upper.and_then(|upper| upper.checked_add(self.missing)),
+        )
+    }
+}
+
+impl<I> FusedIterator for Chunk<I>
+where
+    I: FusedIterator,
+    I::Item: Clone,
+{
+}
+
+impl<I> IntoIterator for &Chunk<I>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+    type IntoIter = Chunk<I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.clone()
+    }
+}
+
+/// Advances `iter` by up to `n` items, and returns the number of skipped items
+fn advance<I: Iterator>(iter: &mut I, n: usize) -> usize {
+    iter.take(n).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        assert_eq!(length([1, 2, 3]).unwrap(), 3);
+        assert_eq!(length((0..10).filter(|i| i % 2 == 0)).unwrap(), 5);
+        assert_eq!(length(Vec::<u8>::new()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_first_last_reverse() {
+        assert_eq!(first(&[1, 2, 3]).unwrap(), Some(&1));
+        assert_eq!(last(&[1, 2, 3]).unwrap(), Some(&3));
+        assert_eq!(first(&[0_u8; 0]).unwrap(), None);
+        assert_eq!(last(0..0).unwrap(), None);
+        assert_eq!(
+            reverse(&[1, 2, 3]).unwrap().collect::<Vec<_>>(),
+            [&3, &2, &1]
+        );
+    }
+
+    #[test]
+    fn test_sort() {
+        assert_eq!(sort(vec![3, 1, 2], false).unwrap(), [1, 2, 3]);
+        assert_eq!(sort(vec![3, 1, 2], true).unwrap(), [3, 2, 1]);
+        assert_eq!(sort(&[2.5, f64::NAN, 1.5], false).unwrap().len(), 3);
+
+        let pairs = [("b", 1), ("a", 2), ("b", 0), ("a", 1)];
+        assert_eq!(
+            sort_by_key(&pairs, |pair| &pair.0, false).unwrap(),
+            [&("a", 2), &("a", 1), &("b", 1), &("b", 0)]
+        );
+        assert_eq!(
+            sort_by_key(&pairs, |pair| &pair.0, true).unwrap(),
+            [&("b", 1), &("b", 0), &("a", 2), &("a", 1)]
+        );
+    }
+
+    #[test]
+    fn test_unique() {
+        assert_eq!(unique([3, 1, 3, 2, 1]).unwrap(), [3, 1, 2]);
+        assert_eq!(unique(["a", "a"]).unwrap(), ["a"]);
+        assert!(unique(Vec::<u8>::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_min_max_sum() {
+        let values = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        let numbers = values.iter().map(|(n, _)| n);
+        assert_eq!(min(&[3, 1, 2]).unwrap(), Some(&1));
+        assert_eq!(max(&[3, 1, 2]).unwrap(), Some(&3));
+        assert_eq!(min(&[0.5, -1.5]).unwrap(), Some(&-1.5));
+        assert_eq!(max(0..0).unwrap(), None);
+        assert_eq!(min(numbers.clone()).unwrap(), Some(&1));
+        assert_eq!(max(numbers.clone()).unwrap(), Some(&2));
+        assert_eq!(sum(numbers).unwrap(), 6);
+        assert_eq!(sum(&[0.5_f32, 0.25]).unwrap(), 0.75);
+        assert_eq!(sum(&[0_u8; 0]).unwrap(), 0);
+        assert!(sum([200_u8, 100]).is_err());
+        assert_eq!(sum([i128::MAX, -1]).unwrap(), i128::MAX - 1);
+    }
+
+    #[test]
+    fn test_map_select_reject() {
+        let pairs = [("a", 1), ("b", 2), ("c", 3)];
+        assert_eq!(
+            map(&pairs, |pair| pair.0).unwrap().collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            select(&pairs, |pair| pair.1 % 2 != 0)
+                .unwrap()
+                .map(|pair| pair.0)
+                .collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+        assert_eq!(
+            reject(&pairs, |pair| pair.1 % 2 != 0)
+                .unwrap()
+                .map(|pair| pair.0)
+                .collect::<Vec<_>>(),
+            ["b"]
+        );
+        assert_eq!(
+            select(1..=6, |i| i % 3 == 0)
+                .unwrap()
+                .rev()
+                .collect::<Vec<_>>(),
+            [6, 3]
+        );
+    }
+
+    #[test]
+    fn test_groupby() {
+        let pairs = [("b", 1), ("a", 2), ("b", 3), ("a", 4), ("c", 5)];
+        assert_eq!(
+            groupby(&pairs, |pair| &pair.0).unwrap(),
+            [
+                ("a", vec![&("a", 2), &("a", 4)]),
+                ("b", vec![&("b", 1), &("b", 3)]),
+                ("c", vec![&("c", 5)]),
+            ]
+        );
+        assert!(groupby(&pairs[..0], |pair| &pair.1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_batch() {
+        let rows = |size, fill_with| {
+            batch(&[1, 2, 3, 4, 5], size, fill_with)
+                .unwrap()
+                .map(|row| row.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(2, None), [vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(rows(3, Some(&0)), [vec![1, 2, 3], vec![4, 5, 0]]);
+        assert_eq!(rows(5, Some(&0)), [vec![1, 2, 3, 4, 5]]);
+        assert_eq!(rows(7, None), [vec![1, 2, 3, 4, 5]]);
+        assert_eq!(
+            batch(&[1, 2, 3], 2, None).unwrap().size_hint(),
+            (2, Some(2))
+        );
+        assert_eq!(batch(0..0, 2, None).unwrap().count(), 0);
+        assert!(batch(0..2, 0, None).is_err());
+
+        let row = batch(0..5, 3, Some(9)).unwrap().nth(1).unwrap();
+        assert_eq!(row.size_hint(), (3, Some(3)));
+        assert_eq!((&row).into_iter().collect::<Vec<_>>(), [3, 4, 9]);
+        assert_eq!(row.collect::<Vec<_>>(), [3, 4, 9]);
+    }
+
+    #[test]
+    fn test_slice() {
+        let columns = |count| {
+            slice(1..=5, count)
+                .unwrap()
+                .map(|column| column.collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(columns(1), [vec![1, 2, 3, 4, 5]]);
+        assert_eq!(columns(2), [vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(columns(3), [vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(
+            columns(6),
+            [vec![1], vec![2], vec![3], vec![4], vec![5], vec![]]
+        );
+        assert_eq!(slice(1..=5, 3).unwrap().len(), 3);
+        assert!(slice(0..2, 0).is_err());
+    }
+}
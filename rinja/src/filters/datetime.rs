@@ -0,0 +1,519 @@
+use core::convert::Infallible;
+use core::fmt;
+use core::ops::Deref;
+use core::pin::Pin;
+
+use super::{FastWritable, HtmlSafe};
+
+/// The format of the [`date()`] filter if none is given, e.g. `2024-12-31`
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The format of the [`datetime()`] filter if none is given, e.g. `2024-12-31 23:59:59`
+pub const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Formats a date with a `strftime`-like format string
+///
+/// Values of the types of [`chrono`](https://docs.rs/chrono) (with the `chrono` feature) and
+/// [`time`](https://docs.rs/time) (with the `time` feature) can be formatted. Both use the same
+/// format specifiers, e.g. `%Y` for the year, `%m` for the month, and `%d` for the day.
+/// If the format is a string literal, it is checked at compile time.
+///
+/// If no format is given, [`DEFAULT_DATE_FORMAT`] is used.
+///
+/// ```
+/// # #[cfg(all(feature = "code-in-doc", feature = "chrono"))] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <time>{{ published|date }}</time> <span>{{ published|date("%-d %B %Y") }}</span>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     published: chrono::NaiveDate,
+/// }
+///
+/// let published = chrono::NaiveDate::from_ymd_opt(2024, 12, 3).unwrap();
+/// assert_eq!(
+///     Example { published }.to_string(),
+///     "<time>2024-12-03</time> <span>3 December 2024</span>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn date<T: DateTimeValue>(value: T, format: &str) -> Result<DateTimeFormat<'_, T>, Infallible> {
+    Ok(DateTimeFormat { value, format })
+}
+
+/// Formats a date and time with a `strftime`-like format string
+///
+/// This filter is the same as [`date()`], but if no format is given,
+/// [`DEFAULT_DATETIME_FORMAT`] is used.
+///
+/// ```
+/// # #[cfg(all(feature = "code-in-doc", feature = "time"))] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ updated|datetime }}</div> <div>{{ updated|datetime("%H:%M") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     updated: time::PrimitiveDateTime,
+/// }
+///
+/// let date = time::Date::from_calendar_date(2024, time::Month::December, 3).unwrap();
+/// let updated = date.with_hms(14, 5, 0).unwrap();
+/// assert_eq!(
+///     Example { updated }.to_string(),
+///     "<div>2024-12-03 14:05:00</div> <div>14:05</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn datetime<T: DateTimeValue>(
+    value: T,
+    format: &str,
+) -> Result<DateTimeFormat<'_, T>, Infallible> {
+    Ok(DateTimeFormat { value, format })
+}
+
+/// Returns how long ago a date was, e.g. `"3 hours ago"`
+///
+/// The time is rounded down to the largest unit: years, months, weeks, days, hours, or minutes.
+/// If less than a minute passed, or if the date is in the future, the output is `"just now"`.
+///
+/// By default, the date is compared to the current time. Optionally, you can provide the
+/// date to compare to, which does not need to be of the same type.
+///
+/// ```
+/// # #[cfg(all(feature = "code-in-doc", feature = "chrono"))] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>Posted {{ posted|timesince(now) }}.</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     posted: chrono::NaiveDateTime,
+///     now: chrono::NaiveDateTime,
+/// }
+///
+/// let now = chrono::NaiveDate::from_ymd_opt(2024, 12, 3)
+///     .unwrap()
+///     .and_hms_opt(14, 5, 0)
+///     .unwrap();
+/// let posted = now - chrono::TimeDelta::minutes(190);
+/// assert_eq!(
+///     Example { posted, now }.to_string(),
+///     "<div>Posted 3 hours ago.</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn timesince<T: DateTimeValue, N: DateTimeValue>(
+    value: T,
+    now: N,
+) -> Result<RelativeTime, Infallible> {
+    let seconds = now.unix_timestamp().saturating_sub(value.unix_timestamp());
+    Ok(RelativeTime::Past(seconds.try_into().unwrap_or(0)))
+}
+
+/// Returns how long it is until a date, e.g. `"in 3 hours"`
+///
+/// This filter is the reverse of [`timesince()`]. If the date is less than a minute away,
+/// or if the date is in the past, the output is `"just now"`.
+///
+/// ```
+/// # #[cfg(all(feature = "code-in-doc", feature = "time"))] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>The sale starts {{ start|timeuntil(now) }}.</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     start: time::Date,
+///     now: time::Date,
+/// }
+///
+/// let now = time::Date::from_calendar_date(2024, time::Month::December, 3).unwrap();
+/// let start = now + time::Duration::days(15);
+/// assert_eq!(
+///     Example { start, now }.to_string(),
+///     "<div>The sale starts in 2 weeks.</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn timeuntil<T: DateTimeValue, N: DateTimeValue>(
+    value: T,
+    now: N,
+) -> Result<RelativeTime, Infallible> {
+    let seconds = value.unix_timestamp().saturating_sub(now.unix_timestamp());
+    Ok(RelativeTime::Future(seconds.try_into().unwrap_or(0)))
+}
+
+/// The result of [`date()`] and [`datetime()`]
+#[derive(Debug, Clone, Copy)]
+pub struct DateTimeFormat<'a, T> {
+    value: T,
+    format: &'a str,
+}
+
+impl<T: DateTimeValue> fmt::Display for DateTimeFormat<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(self.write_into(f)?)
+    }
+}
+
+impl<T: DateTimeValue> FastWritable for DateTimeFormat<'_, T> {
+    #[inline]
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        self.value.write_formatted(dest, self.format)
+    }
+}
+
+/// The result of [`timesince()`] and [`timeuntil()`], the number of seconds in the past or future
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeTime {
+    /// A date that is this many seconds ago
+    Past(u64),
+    /// A date that is this many seconds in the future
+    Future(u64),
+}
+
+impl fmt::Display for RelativeTime {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(self.write_into(f)?)
+    }
+}
+
+impl FastWritable for RelativeTime {
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        const UNITS: &[(u64, &str, &str)] = &[
+            (365 * 24 * 60 * 60, "year", "years"),
+            (30 * 24 * 60 * 60, "month", "months"),
+            (7 * 24 * 60 * 60, "week", "weeks"),
+            (24 * 60 * 60, "day", "days"),
+            (60 * 60, "hour", "hours"),
+            (60, "minute", "minutes"),
+        ];
+
+        let (RelativeTime::Past(seconds) | RelativeTime::Future(seconds)) = *self;
+        let Some(&(size, singular, plural)) = UNITS.iter().find(|&&(size, ..)| seconds >= size)
+        else {
+            return Ok(dest.write_str("just now")?);
+        };
+        let count = seconds / size;
+        let unit = if count == 1 { singular } else { plural };
+        Ok(match self {
+            RelativeTime::Past(_) => write!(dest, "{count} {unit} ago"),
+            RelativeTime::Future(_) => write!(dest, "in {count} {unit}"),
+        }?)
+    }
+}
+
+impl HtmlSafe for RelativeTime {}
+
+/// The current time, the default second argument of [`timesince()`] and [`timeuntil()`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Now;
+
+/// A date, or a date and time, that can be used with [`date()`], [`datetime()`],
+/// [`timesince()`] and [`timeuntil()`]
+pub trait DateTimeValue {
+    /// Writes the value formatted with a `strftime`-like `format` into `dest`
+    fn write_formatted<W: fmt::Write + ?Sized>(
+        &self,
+        dest: &mut W,
+        format: &str,
+    ) -> crate::Result<()>;
+
+    /// Returns the number of seconds since the Unix epoch
+    ///
+    /// Dates without a time are midnight, and dates without a time zone are UTC.
+    fn unix_timestamp(&self) -> i64;
+}
+
+const _: () = {
+    crate::impl_for_ref! {
+        impl DateTimeValue for T {
+            #[inline]
+            fn write_formatted<W: fmt::Write + ?Sized>(
+                &self,
+                dest: &mut W,
+                format: &str,
+            ) -> crate::Result<()> {
+                <T>::write_formatted(self, dest, format)
+            }
+
+            #[inline]
+            fn unix_timestamp(&self) -> i64 {
+                <T>::unix_timestamp(self)
+            }
+        }
+    }
+
+    impl<T> DateTimeValue for Pin<T>
+    where
+        T: Deref,
+        <T as Deref>::Target: DateTimeValue,
+    {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            self.as_ref().get_ref().write_formatted(dest, format)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.as_ref().get_ref().unix_timestamp()
+        }
+    }
+
+    impl DateTimeValue for Now {
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            #[cfg(feature = "chrono")]
+            let now = chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::now());
+            #[cfg(not(feature = "chrono"))]
+            let now = time::OffsetDateTime::now_utc();
+            now.write_formatted(dest, format)
+        }
+
+        fn unix_timestamp(&self) -> i64 {
+            match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+                Ok(duration) => duration.as_secs().try_into().unwrap_or(i64::MAX),
+                Err(err) => 0i64.saturating_sub_unsigned(err.duration().as_secs()),
+            }
+        }
+    }
+};
+
+#[cfg(feature = "chrono")]
+const _: () = {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+    impl DateTimeValue for NaiveDate {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            Ok(write!(dest, "{}", self.format(format))?)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.and_time(NaiveTime::MIN).and_utc().timestamp()
+        }
+    }
+
+    impl DateTimeValue for NaiveDateTime {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            Ok(write!(dest, "{}", self.format(format))?)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.and_utc().timestamp()
+        }
+    }
+
+    impl<Tz: TimeZone> DateTimeValue for DateTime<Tz>
+    where
+        Tz::Offset: fmt::Display,
+    {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            Ok(write!(dest, "{}", self.format(format))?)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.timestamp()
+        }
+    }
+};
+
+#[cfg(feature = "time")]
+const _: () = {
+    use time::format_description::parse_strftime_borrowed;
+    use time::{Date, OffsetDateTime, PrimitiveDateTime};
+
+    macro_rules! write_formatted {
+        ($value:expr, $dest:expr, $format:expr) => {{
+            let items = parse_strftime_borrowed($format).map_err(crate::Error::custom)?;
+            let formatted = $value.format(&items).map_err(crate::Error::custom)?;
+            Ok($dest.write_str(&formatted)?)
+        }};
+    }
+
+    impl DateTimeValue for Date {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            write_formatted!(self, dest, format)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.midnight().assume_utc().unix_timestamp()
+        }
+    }
+
+    impl DateTimeValue for PrimitiveDateTime {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            write_formatted!(self, dest, format)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            self.assume_utc().unix_timestamp()
+        }
+    }
+
+    impl DateTimeValue for OffsetDateTime {
+        #[inline]
+        fn write_formatted<W: fmt::Write + ?Sized>(
+            &self,
+            dest: &mut W,
+            format: &str,
+        ) -> crate::Result<()> {
+            write_formatted!(self, dest, format)
+        }
+
+        #[inline]
+        fn unix_timestamp(&self) -> i64 {
+            OffsetDateTime::unix_timestamp(*self)
+        }
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_relative_time() {
+        let past = |seconds| RelativeTime::Past(seconds).to_string();
+        let future = |seconds| RelativeTime::Future(seconds).to_string();
+
+        assert_eq!(past(0), "just now");
+        assert_eq!(past(59), "just now");
+        assert_eq!(past(60), "1 minute ago");
+        assert_eq!(past(2 * 60 * 60 - 1), "1 hour ago");
+        assert_eq!(past(2 * 60 * 60), "2 hours ago");
+        assert_eq!(past(24 * 60 * 60), "1 day ago");
+        assert_eq!(past(13 * 24 * 60 * 60), "1 week ago");
+        assert_eq!(past(45 * 24 * 60 * 60), "1 month ago");
+        assert_eq!(past(800 * 24 * 60 * 60), "2 years ago");
+
+        assert_eq!(future(30), "just now");
+        assert_eq!(future(90), "in 1 minute");
+        assert_eq!(future(3 * 24 * 60 * 60), "in 3 days");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let moment = day.and_hms_opt(23, 4, 5).unwrap();
+        assert_eq!(
+            date(day, DEFAULT_DATE_FORMAT).unwrap().to_string(),
+            "2024-02-29"
+        );
+        assert_eq!(
+            datetime(moment, DEFAULT_DATETIME_FORMAT)
+                .unwrap()
+                .to_string(),
+            "2024-02-29 23:04:05"
+        );
+        let zoned = FixedOffset::east_opt(3600)
+            .unwrap()
+            .from_local_datetime(&moment)
+            .unwrap();
+        assert_eq!(
+            datetime(zoned, "%a %-d %b %H:%M %z").unwrap().to_string(),
+            "Thu 29 Feb 23:04 +0100"
+        );
+
+        assert_eq!(timesince(moment, zoned).unwrap(), RelativeTime::Past(0));
+        assert_eq!(timeuntil(day, moment).unwrap(), RelativeTime::Future(0));
+        assert_eq!(
+            timesince(day, moment).unwrap(),
+            RelativeTime::Past(23 * 3600 + 4 * 60 + 5)
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() {
+        use time::{Date, Month, UtcOffset};
+
+        let day = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+        let moment = day.with_hms(23, 4, 5).unwrap();
+        assert_eq!(
+            date(day, DEFAULT_DATE_FORMAT).unwrap().to_string(),
+            "2024-02-29"
+        );
+        assert_eq!(
+            datetime(moment, DEFAULT_DATETIME_FORMAT)
+                .unwrap()
+                .to_string(),
+            "2024-02-29 23:04:05"
+        );
+        let zoned = moment.assume_offset(UtcOffset::from_hms(1, 0, 0).unwrap());
+        assert_eq!(
+            datetime(zoned, "%a %-d %b %H:%M %z").unwrap().to_string(),
+            "Thu 29 Feb 23:04 +0100"
+        );
+        assert!(matches!(
+            datetime(day, "%H")
+                .unwrap()
+                .write_into(&mut alloc::string::String::new()),
+            Err(crate::Error::Custom(_))
+        ));
+
+        assert_eq!(
+            timesince(day, moment).unwrap(),
+            RelativeTime::Past(23 * 3600 + 4 * 60 + 5)
+        );
+    }
+
+    #[test]
+    fn test_now() {
+        assert!(timesince(Now, Now).unwrap() == RelativeTime::Past(0));
+        assert!(Now.unix_timestamp() > 1_700_000_000);
+    }
+}
@@ -1,8 +1,10 @@
-use std::convert::Infallible;
-use std::fmt::{self, Formatter, Write};
-use std::ops::Deref;
-use std::pin::Pin;
-use std::{borrow, str};
+use alloc::borrow;
+use alloc::string::String;
+use core::convert::Infallible;
+use core::fmt::{self, Formatter, Write};
+use core::ops::Deref;
+use core::pin::Pin;
+use core::str;
 
 /// Marks a string (or other `Display` type) as safe
 ///
@@ -85,7 +87,48 @@ impl<T: FastWritable, E: Escaper> FastWritable for EscapeDisplay<T, E> {
     }
 }
 
-struct EscapeWriter<W, E>(W, E);
+/// An [`fmt::Write`] adapter that escapes everything written into it with an [`Escaper`]
+///
+/// Use it to escape the output of a [`FastWritable`] value, e.g. a nested template, without
+/// going through [`fmt::Display`] and a [`Formatter`], and without an intermediate [`String`].
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// use rinja::filters::{EscapeWriter, Html};
+///
+/// /// ```jinja
+/// /// Hello, {{ name }}!
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "txt", in_doc = true)]
+/// struct Greeting<'a> {
+///     name: &'a str,
+/// }
+///
+/// let mut output = String::new();
+/// Greeting { name: "<Tom & Jerry>" }
+///     .render_into(&mut EscapeWriter::new(&mut output, Html))
+///     .unwrap();
+/// assert_eq!(output, "Hello, &#60;Tom &#38; Jerry&#62;!");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EscapeWriter<W, E>(W, E);
+
+impl<W, E> EscapeWriter<W, E> {
+    /// Escapes everything that gets written into `dest` with `escaper`
+    #[inline]
+    pub fn new(dest: W, escaper: E) -> Self {
+        Self(dest, escaper)
+    }
+
+    /// Returns the underlying writer
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
 
 impl<W: Write, E: Escaper> Write for EscapeWriter<W, E> {
     #[inline]
@@ -162,6 +205,385 @@ impl Escaper for Text {
     }
 }
 
+/// Escape characters in a safe way for XML texts and attributes
+///
+/// * `"` => `&quot;`
+/// * `&` => `&amp;`
+/// * `'` => `&apos;`
+/// * `<` => `&lt;`
+/// * `>` => `&gt;`
+///
+/// Characters that are not allowed in XML 1.0 documents, i.e. control characters other than tab,
+/// line feed and carriage return, and U+FFFE and U+FFFF, cannot be escaped. Writing them fails
+/// with [`fmt::Error`], so the rendering of the template fails, too.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xml;
+
+impl Escaper for Xml {
+    fn write_escaped_str<W: Write>(&self, fmt: W, string: &str) -> fmt::Result {
+        escape_chars(
+            fmt,
+            string,
+            |c| matches!(c, '"' | '&' | '\'' | '<' | '>') || !is_xml_char(c),
+            |fmt, c| {
+                fmt.write_str(match c {
+                    '"' => "&quot;",
+                    '&' => "&amp;",
+                    '\'' => "&apos;",
+                    '<' => "&lt;",
+                    '>' => "&gt;",
+                    _ => return Err(fmt::Error),
+                })
+            },
+        )
+    }
+}
+
+/// Returns `true` if `c` is allowed in XML 1.0 documents
+#[inline]
+fn is_xml_char(c: char) -> bool {
+    match c {
+        '\t' | '\n' | '\r' => true,
+        '\0'..='\x1f' | '\u{fffe}' | '\u{ffff}' => false,
+        _ => true,
+    }
+}
+
+/// Escape characters in a safe way for LaTeX documents
+///
+/// * `\` => `\textbackslash{}`
+/// * `~` => `\textasciitilde{}`
+/// * `^` => `\textasciicircum{}`
+/// * `{`, `}`, `%`, `$`, `#`, `_`, `&` => `\{`, `\}`, `\%`, `\$`, `\#`, `\_`, `\&`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Latex;
+
+impl Escaper for Latex {
+    fn write_escaped_str<W: Write>(&self, fmt: W, string: &str) -> fmt::Result {
+        escape_chars(
+            fmt,
+            string,
+            |c| {
+                matches!(
+                    c,
+                    '\\' | '{' | '}' | '%' | '$' | '#' | '_' | '&' | '~' | '^'
+                )
+            },
+            |fmt, c| match c {
+                '\\' => fmt.write_str("\\textbackslash{}"),
+                '~' => fmt.write_str("\\textasciitilde{}"),
+                '^' => fmt.write_str("\\textasciicircum{}"),
+                c => write!(fmt, "\\{c}"),
+            },
+        )
+    }
+}
+
+/// Escape characters in a safe way for roff documents, e.g. man pages
+///
+/// * `\` => `\e`
+/// * `-` => `\-`
+/// * `"` => `\(dq`
+/// * `'` => `\(aq`
+/// * `` ` `` => `\(ga`
+/// * `.` at the start of a line => `\&.`
+///
+/// The escaper cannot know if the string is written at the start of a line, so a leading `.`
+/// is always escaped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Roff;
+
+impl Escaper for Roff {
+    fn write_escaped_str<W: Write>(&self, mut fmt: W, string: &str) -> fmt::Result {
+        for line in string.split_inclusive('\n') {
+            if line.starts_with('.') {
+                fmt.write_str("\\&")?;
+            }
+            escape_chars(
+                &mut fmt,
+                line,
+                |c| matches!(c, '\\' | '-' | '"' | '\'' | '`'),
+                |fmt, c| {
+                    fmt.write_str(match c {
+                        '\\' => "\\e",
+                        '-' => "\\-",
+                        '"' => "\\(dq",
+                        '\'' => "\\(aq",
+                        _ => "\\(ga",
+                    })
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote strings in a safe way for POSIX shell scripts
+///
+/// The string is wrapped in single quotes, and every `'` is replaced with `'\''`, e.g.
+/// `it's` => `'it'\''s'`. If a value is written in multiple pieces, then every piece is quoted on
+/// its own, and the shell joins them into a single word again.
+///
+/// Use the [`shell_quote()`] filter to quote a value in a template of a different format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Shell;
+
+impl Escaper for Shell {
+    fn write_escaped_str<W: Write>(&self, mut fmt: W, string: &str) -> fmt::Result {
+        fmt.write_char('\'')?;
+        write_single_quoted(&mut fmt, string)?;
+        fmt.write_char('\'')
+    }
+}
+
+/// Quotes a value for POSIX shell scripts, like the [`Shell`] escaper does
+///
+/// The whole value is wrapped in single quotes, so an empty value becomes `''`.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// cp {{ src|shell_quote }} {{ dest|shell_quote }}
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "txt", in_doc = true)]
+/// struct Example<'a> {
+///     src: &'a str,
+///     dest: &'a str,
+/// }
+///
+/// assert_eq!(
+///     Example { src: "it's mine.txt", dest: "" }.to_string(),
+///     r"cp 'it'\''s mine.txt' ''"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn shell_quote<T: fmt::Display>(text: T) -> Result<ShellQuote<T>, Infallible> {
+    Ok(ShellQuote(text))
+}
+
+pub struct ShellQuote<T>(T);
+
+impl<T: fmt::Display> fmt::Display for ShellQuote<T> {
+    #[inline]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_char('\'')?;
+        write!(SingleQuoted(&mut *fmt), "{}", &self.0)?;
+        fmt.write_char('\'')
+    }
+}
+
+impl<T: FastWritable> FastWritable for ShellQuote<T> {
+    #[inline]
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        dest.write_char('\'')?;
+        self.0.write_into(&mut SingleQuoted(&mut *dest))?;
+        Ok(dest.write_char('\'')?)
+    }
+}
+
+/// Escapes `'` in the content of a single quoted shell string
+struct SingleQuoted<W>(W);
+
+impl<W: Write> Write for SingleQuoted<W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        write_single_quoted(&mut self.0, s)
+    }
+}
+
+#[inline]
+fn write_single_quoted<W: Write>(fmt: W, string: &str) -> fmt::Result {
+    escape_chars(fmt, string, |c| c == '\'', |fmt, _| fmt.write_str(r"'\''"))
+}
+
+/// Escape characters in a safe way for JavaScript string literals
+///
+/// * `\` => `\\`
+/// * `"`, `'`, `` ` `` => `\u0022`, `\u0027`, `\u0060`
+/// * `&`, `<`, `>` => `\u0026`, `\u003C`, `\u003E`
+/// * U+2028 and U+2029 => `\u2028` and `\u2029`
+/// * control characters, e.g. a newline => `\u000A`
+///
+/// The output can be used in single and double quoted string literals, in a `<script>` element,
+/// and in an event handler attribute like `onclick="..."`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Js;
+
+impl Escaper for Js {
+    fn write_escaped_str<W: Write>(&self, fmt: W, string: &str) -> fmt::Result {
+        escape_chars(fmt, string, js_needs_escaping, |fmt, c| match c {
+            '\\' => fmt.write_str("\\\\"),
+            c => write!(fmt, "\\u{:04X}", c as u32),
+        })
+    }
+}
+
+#[inline]
+fn js_needs_escaping(c: char) -> bool {
+    matches!(
+        c,
+        '\0'..='\x1f'
+            | '\x7f'..='\u{9f}'
+            | '"'
+            | '&'
+            | '\''
+            | '<'
+            | '>'
+            | '\\'
+            | '`'
+            | '\u{2028}'
+            | '\u{2029}'
+    )
+}
+
+/// Escape characters in a safe way for CSS strings and values
+///
+/// Every ASCII character except letters, digits, `-` and `_` is replaced with its CSS escape
+/// sequence `\HH `, e.g. `"` => `\22 `. The output can be used in quoted strings, as an identifier,
+/// in a `<style>` element, and in a `style="..."` attribute.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Css;
+
+impl Escaper for Css {
+    fn write_escaped_str<W: Write>(&self, fmt: W, string: &str) -> fmt::Result {
+        escape_chars(fmt, string, css_needs_escaping, |fmt, c| match c {
+            // NUL cannot be escaped in CSS
+            '\0' => fmt.write_str("\\fffd "),
+            c => write!(fmt, "\\{:x} ", c as u32),
+        })
+    }
+}
+
+#[inline]
+fn css_needs_escaping(c: char) -> bool {
+    match c.is_ascii() {
+        true => !(c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        false => c.is_control(),
+    }
+}
+
+/// Percent-encode a component of a URL, e.g. a path segment or a query parameter
+///
+/// Every byte except ASCII letters, digits, `-`, `.`, `_` and `~` is replaced with `%HH`, e.g.
+/// `/` => `%2F` and `ä` => `%C3%A4`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Url;
+
+impl Escaper for Url {
+    fn write_escaped_str<W: Write>(&self, fmt: W, string: &str) -> fmt::Result {
+        escape_bytes(fmt, string, |b| {
+            !(b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+        })
+    }
+}
+
+/// Escape a URL for an HTML attribute like `href="..."`, and reject URLs with an unsafe scheme
+///
+/// URLs with a scheme other than `http`, `https`, `mailto` and `tel`, e.g. `javascript:` URLs,
+/// are replaced with `about:invalid#rinja`. Relative URLs are kept. Characters that are not
+/// allowed in URLs are percent-encoded, and `&` is HTML escaped to `&#38;`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SafeUrl;
+
+impl Escaper for SafeUrl {
+    fn write_escaped_str<W: Write>(&self, mut fmt: W, string: &str) -> fmt::Result {
+        if !has_safe_scheme(string) {
+            return fmt.write_str("about:invalid#rinja");
+        }
+        escape_bytes(HtmlAmp(fmt), string, |b| {
+            !matches!(
+                b,
+                b'!' | b'#'..=b'&' | b'('..=b';' | b'=' | b'?'..=b'[' | b']' | b'_' | b'a'..=b'z' | b'~'
+            )
+        })
+    }
+}
+
+/// Returns `false` if `url` starts with a scheme that is not known to be safe
+pub(crate) fn has_safe_scheme(url: &str) -> bool {
+    // browsers ignore leading whitespace and control characters
+    let url = url.trim_start_matches(|c| c <= ' ');
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => ["http", "https", "mailto", "tel"]
+            .iter()
+            .any(|scheme| url[..end].eq_ignore_ascii_case(scheme)),
+        _ => true,
+    }
+}
+
+/// Escapes `&` in the output of [`SafeUrl`]
+struct HtmlAmp<W>(W);
+
+impl<W: Write> Write for HtmlAmp<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        escape_chars(
+            &mut self.0,
+            s,
+            |c| c == '&',
+            |fmt, _| fmt.write_str("&#38;"),
+        )
+    }
+}
+
+/// Writes `string` into `fmt`, and calls `write_escaped` instead for every character `c` for
+/// which `needs_escaping(c)` is `true`
+#[inline]
+fn escape_chars<W: Write>(
+    mut fmt: W,
+    string: &str,
+    needs_escaping: impl Fn(char) -> bool,
+    mut write_escaped: impl FnMut(&mut W, char) -> fmt::Result,
+) -> fmt::Result {
+    let mut last = 0;
+    for (index, c) in string.char_indices() {
+        if needs_escaping(c) {
+            if last < index {
+                fmt.write_str(&string[last..index])?;
+            }
+            write_escaped(&mut fmt, c)?;
+            last = index + c.len_utf8();
+        }
+    }
+    if last < string.len() {
+        fmt.write_str(&string[last..])?;
+    }
+    Ok(())
+}
+
+/// Writes `string` into `fmt`, and percent-encodes every byte `b` for which `needs_escaping(b)`
+/// is `true`
+#[inline]
+fn escape_bytes<W: Write>(
+    mut fmt: W,
+    string: &str,
+    needs_escaping: impl Fn(u8) -> bool,
+) -> fmt::Result {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut last = 0;
+    for (index, b) in string.bytes().enumerate() {
+        if needs_escaping(b) {
+            if last < index {
+                // SAFETY: only ASCII bytes are passed through, so `last` and `index` are
+                // always at a char boundary
+                fmt.write_str(unsafe { string.get_unchecked(last..index) })?;
+            }
+            let escaped = [b'%', HEX[(b >> 4) as usize], HEX[(b & 15) as usize]];
+            // SAFETY: the content of `escaped` is pure ASCII
+            fmt.write_str(unsafe { str::from_utf8_unchecked(&escaped) })?;
+            last = index + 1;
+        }
+    }
+    if last < string.len() {
+        // SAFETY: see above
+        fmt.write_str(unsafe { string.get_unchecked(last..) })?;
+    }
+    Ok(())
+}
+
 /// Escapers are used to make generated text safe for printing in some context.
 ///
 /// E.g. in an [`Html`] context, any and all generated text can be used in HTML/XML text nodes and
@@ -439,6 +861,13 @@ impl<T: fmt::Display> fmt::Display for Unsafe<T> {
     }
 }
 
+impl<T: FastWritable> FastWritable for Unsafe<T> {
+    #[inline]
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        self.0.write_into(dest)
+    }
+}
+
 /// Like [`Safe`], but only for HTML output
 pub struct HtmlSafeOutput<T>(pub T);
 
@@ -449,6 +878,13 @@ impl<T: fmt::Display> fmt::Display for HtmlSafeOutput<T> {
     }
 }
 
+impl<T: FastWritable> FastWritable for HtmlSafeOutput<T> {
+    #[inline]
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        self.0.write_into(dest)
+    }
+}
+
 macro_rules! mark_html_safe {
     ($($ty:ty),* $(,)?) => {$(
         impl HtmlSafe for $ty {}
@@ -460,13 +896,13 @@ mark_html_safe! {
     f32, f64,
     i8, i16, i32, i64, i128, isize,
     u8, u16, u32, u64, u128, usize,
-    std::num::NonZeroI8, std::num::NonZeroI16, std::num::NonZeroI32,
-    std::num::NonZeroI64, std::num::NonZeroI128, std::num::NonZeroIsize,
-    std::num::NonZeroU8, std::num::NonZeroU16, std::num::NonZeroU32,
-    std::num::NonZeroU64, std::num::NonZeroU128, std::num::NonZeroUsize,
+    core::num::NonZeroI8, core::num::NonZeroI16, core::num::NonZeroI32,
+    core::num::NonZeroI64, core::num::NonZeroI128, core::num::NonZeroIsize,
+    core::num::NonZeroU8, core::num::NonZeroU16, core::num::NonZeroU32,
+    core::num::NonZeroU64, core::num::NonZeroU128, core::num::NonZeroUsize,
 }
 
-impl<T: HtmlSafe> HtmlSafe for std::num::Wrapping<T> {}
+impl<T: HtmlSafe> HtmlSafe for core::num::Wrapping<T> {}
 impl<T: fmt::Display> HtmlSafe for HtmlSafeOutput<T> {}
 
 impl<T> HtmlSafe for borrow::Cow<'_, T>
@@ -520,7 +956,7 @@ const _: () = {
         }
     }
 
-    impl<T: FastWritable + ToOwned> FastWritable for borrow::Cow<'_, T> {
+    impl<T: FastWritable + borrow::ToOwned> FastWritable for borrow::Cow<'_, T> {
         #[inline]
         fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
             T::write_into(self.as_ref(), dest)
@@ -618,6 +1054,8 @@ const _: () = {
 
 #[test]
 fn test_escape() {
+    use alloc::string::ToString;
+
     assert_eq!(escape("", Html).unwrap().to_string(), "");
     assert_eq!(escape("<&>", Html).unwrap().to_string(), "&#60;&#38;&#62;");
     assert_eq!(escape("bla&", Html).unwrap().to_string(), "bla&#38;");
@@ -631,8 +1069,100 @@ fn test_escape() {
     assert_eq!(escape("bla&h", Text).unwrap().to_string(), "bla&h");
 }
 
+#[test]
+fn test_context_escapers() {
+    use alloc::string::ToString;
+
+    assert_eq!(
+        escape("a\"b'c`d\\e", Js).unwrap().to_string(),
+        r#"a\u0022b\u0027c\u0060d\\e"#,
+    );
+    assert_eq!(
+        escape("</script>&\n\u{2028}ä", Js).unwrap().to_string(),
+        r#"\u003C/script\u003E\u0026\u000A\u2028ä"#,
+    );
+
+    assert_eq!(escape("red", Css).unwrap().to_string(), "red");
+    assert_eq!(
+        escape("a\";}\0ä", Css).unwrap().to_string(),
+        r#"a\22 \3b \7d \fffd ä"#,
+    );
+
+    assert_eq!(
+        escape("<a href=\"x\">'&'</a>\t\r\n", Xml)
+            .unwrap()
+            .to_string(),
+        "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;\t\r\n",
+    );
+    let mut output = String::new();
+    for c in ['\0', '\x0b', '\x1f', '\u{fffe}', '\u{ffff}'] {
+        assert!(write!(output, "{}", escape(c, Xml).unwrap()).is_err());
+    }
+    assert_eq!(escape('\u{7f}', Xml).unwrap().to_string(), "\u{7f}");
+
+    assert_eq!(
+        escape(r"50% of $x_1 & {y} #2 ~ ^ \par", Latex)
+            .unwrap()
+            .to_string(),
+        r"50\% of \$x\_1 \& \{y\} \#2 \textasciitilde{} \textasciicircum{} \textbackslash{}par",
+    );
+
+    assert_eq!(
+        escape(".TH x\n.B \"--help\" 'a' `b` \\fB", Roff)
+            .unwrap()
+            .to_string(),
+        "\\&.TH x\n\\&.B \\(dq\\-\\-help\\(dq \\(aqa\\(aq \\(gab\\(ga \\efB",
+    );
+    assert_eq!(escape("3.14", Roff).unwrap().to_string(), "3.14");
+
+    assert_eq!(
+        escape("it's $HOME", Shell).unwrap().to_string(),
+        r"'it'\''s $HOME'"
+    );
+    assert_eq!(escape("", Shell).unwrap().to_string(), "''");
+    assert_eq!(
+        shell_quote(format_args!("{}{}", "a'", ""))
+            .unwrap()
+            .to_string(),
+        r"'a'\'''"
+    );
+
+    assert_eq!(
+        escape("a b/c?d=ä&e~", Url).unwrap().to_string(),
+        "a%20b%2Fc%3Fd%3D%C3%A4%26e~",
+    );
+
+    assert_eq!(
+        escape("https://example.com/?a=1&b=\"<x>\"", SafeUrl)
+            .unwrap()
+            .to_string(),
+        "https://example.com/?a=1&#38;b=%22%3Cx%3E%22",
+    );
+    assert_eq!(
+        escape("/path#top", SafeUrl).unwrap().to_string(),
+        "/path#top"
+    );
+    assert_eq!(
+        escape("mailto:a@b.c", SafeUrl).unwrap().to_string(),
+        "mailto:a@b.c"
+    );
+    for url in [
+        "javascript:alert(1)",
+        " JavaScript:x",
+        "java\tscript:x",
+        "data:text/html,x",
+    ] {
+        assert_eq!(
+            escape(url, SafeUrl).unwrap().to_string(),
+            "about:invalid#rinja"
+        );
+    }
+}
+
 #[test]
 fn test_html_safe_marker() {
+    use alloc::string::ToString;
+
     struct Script1;
     struct Script2;
 
@@ -740,14 +1270,14 @@ fn test_html_safe_marker() {
     );
 
     assert_eq!(
-        (&&AutoEscaper::new(&Safe(std::pin::Pin::new(&Script1)), Html))
+        (&&AutoEscaper::new(&Safe(core::pin::Pin::new(&Script1)), Html))
             .rinja_auto_escape()
             .unwrap()
             .to_string(),
         "<script>",
     );
     assert_eq!(
-        (&&AutoEscaper::new(&Safe(std::pin::Pin::new(&Script2)), Html))
+        (&&AutoEscaper::new(&Safe(core::pin::Pin::new(&Script2)), Html))
             .rinja_auto_escape()
             .unwrap()
             .to_string(),
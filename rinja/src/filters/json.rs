@@ -1,7 +1,10 @@
-use std::convert::Infallible;
-use std::ops::Deref;
-use std::pin::Pin;
-use std::{fmt, io, str};
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::convert::Infallible;
+use core::ops::Deref;
+use core::pin::Pin;
+use core::{fmt, str};
+use std::io;
 
 use serde::Serialize;
 use serde_json::ser::{PrettyFormatter, Serializer, to_writer};
@@ -133,14 +136,14 @@ impl AsIndent for usize {
     }
 }
 
-impl AsIndent for std::num::Wrapping<usize> {
+impl AsIndent for core::num::Wrapping<usize> {
     #[inline]
     fn as_indent(&self) -> &str {
         spaces(self.0)
     }
 }
 
-impl AsIndent for std::num::NonZeroUsize {
+impl AsIndent for core::num::NonZeroUsize {
     #[inline]
     fn as_indent(&self) -> &str {
         spaces(self.get())
@@ -157,7 +160,7 @@ fn spaces(width: usize) -> &'static str {
     &SPACES[..width.min(SPACES.len())]
 }
 
-impl<T: AsIndent + ToOwned + ?Sized> AsIndent for std::borrow::Cow<'_, T> {
+impl<T: AsIndent + ToOwned + ?Sized> AsIndent for alloc::borrow::Cow<'_, T> {
     #[inline]
     fn as_indent(&self) -> &str {
         T::as_indent(self)
@@ -267,6 +270,9 @@ fn write<W: fmt::Write + ?Sized>(f: &mut W, bytes: &[u8]) -> fmt::Result {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
     use super::*;
 
     #[test]
@@ -11,10 +11,16 @@
 //! to work with all compatible types.
 
 mod builtin;
+mod collection;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
 mod escape;
 mod humansize;
 #[cfg(feature = "serde_json")]
 mod json;
+mod number;
+#[cfg(feature = "sanitize")]
+mod sanitize;
 #[cfg(feature = "urlencode")]
 mod urlencode;
 
@@ -22,12 +28,27 @@ pub use self::builtin::{
     PluralizeCount, capitalize, center, fmt, format, indent, join, linebreaks, linebreaksbr, lower,
     lowercase, paragraphbreaks, pluralize, title, trim, truncate, upper, uppercase, wordcount,
 };
+pub use self::collection::{
+    Batch, Chunk, Select, Slice, batch, first, groupby, last, length, map, max, min, reject,
+    reverse, select, slice, sort, sort_by_key, sum, unique,
+};
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub use self::datetime::{
+    DEFAULT_DATE_FORMAT, DEFAULT_DATETIME_FORMAT, DateTimeFormat, DateTimeValue, Now, RelativeTime,
+    date, datetime, timesince, timeuntil,
+};
 pub use self::escape::{
-    AutoEscape, AutoEscaper, Escaper, FastWritable, Html, HtmlSafe, HtmlSafeOutput, MaybeSafe,
-    Safe, Text, Unsafe, Writable, WriteWritable, e, escape, safe,
+    AutoEscape, AutoEscaper, Css, EscapeWriter, Escaper, FastWritable, Html, HtmlSafe,
+    HtmlSafeOutput, Js, Latex, MaybeSafe, Roff, Safe, SafeUrl, Shell, Text, Unsafe, Url, Writable,
+    WriteWritable, Xml, e, escape, safe, shell_quote,
 };
 pub use self::humansize::filesizeformat;
 #[cfg(feature = "serde_json")]
 pub use self::json::{AsIndent, json, json_pretty};
+pub use self::number::{
+    DEFAULT_LOCALE, Locale, NumberFormat, NumberValue, currency, numberformat, percent,
+};
+#[cfg(feature = "sanitize")]
+pub use self::sanitize::{Allowlist, sanitize};
 #[cfg(feature = "urlencode")]
 pub use self::urlencode::{urlencode, urlencode_strict};
@@ -0,0 +1,585 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use super::{FastWritable, HtmlSafe};
+use crate::helpers::PrimitiveType;
+
+/// The locale of [`numberformat()`], [`percent()`] and [`currency()`] if none is given
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Formats a number with the group and decimal separators of a locale
+///
+/// The value can be any [primitive](PrimitiveType) integer or floating point number.
+/// If `decimals` is given, the number is rounded to this many decimal places, otherwise
+/// integers are printed without decimal places, and floats with as many as needed.
+/// Unknown locales are an error. If the locale is a string literal, it is checked at compile
+/// time. See [`Locale::from_name()`] for a list of the known locales.
+///
+/// In a template, `decimals` and `locale` can be passed as named arguments.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ amount|numberformat }}</div>
+/// /// <div>{{ amount|numberformat(decimals=2) }}</div>
+/// /// <div>{{ amount|numberformat(decimals=2, locale="de") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     amount: f64,
+/// }
+///
+/// assert_eq!(
+///     Example { amount: 1234567.891 }.to_string(),
+///     "<div>1,234,567.891</div>\n<div>1,234,567.89</div>\n<div>1.234.567,89</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn numberformat<T>(
+    value: T,
+    decimals: Option<usize>,
+    locale: &str,
+) -> crate::Result<NumberFormat<'static, T::Value>>
+where
+    T: PrimitiveType,
+    T::Value: NumberValue,
+{
+    Ok(NumberFormat {
+        value: value.get(),
+        decimals,
+        locale: Locale::required(locale)?,
+        kind: Kind::Number,
+    })
+}
+
+/// Formats a ratio as percentage, e.g. `0.256` as `26%`
+///
+/// The value is multiplied by 100, and rounded to `decimals` decimal places, `0` by default.
+/// Otherwise, this filter works like [`numberformat()`].
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ ratio|percent }}, {{ ratio|percent(1) }}, {{ ratio|percent(1, "fr") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     ratio: f32,
+/// }
+///
+/// assert_eq!(
+///     Example { ratio: 0.2856 }.to_string(),
+///     "<div>29%, 28.6%, 28,6\u{202f}%</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn percent<T>(
+    value: T,
+    decimals: Option<usize>,
+    locale: &str,
+) -> crate::Result<NumberFormat<'static, T::Value>>
+where
+    T: PrimitiveType,
+    T::Value: NumberValue,
+{
+    Ok(NumberFormat {
+        value: value.get(),
+        decimals: Some(decimals.unwrap_or(0)),
+        locale: Locale::required(locale)?,
+        kind: Kind::Percent,
+    })
+}
+
+/// Formats an amount of money in a currency, e.g. `1234.5` as `€1,234.50`
+///
+/// The currency is given as three letter [ISO 4217](https://en.wikipedia.org/wiki/ISO_4217)
+/// code, e.g. `"EUR"`. The symbol of common currencies is used, otherwise the code is printed.
+/// Unless `decimals` is given, the amount is rounded to the minor unit of the currency, e.g.
+/// to cents for `"USD"`, and to whole yen for `"JPY"`. The locale selects the separators, and
+/// whether the symbol is placed before or after the amount.
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ price|currency("EUR") }} / {{ price|currency("EUR", locale="de") }}</div>
+/// /// <div>{{ price|currency("JPY") }} / {{ price|currency("SEK", locale="sv") }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example {
+///     price: u32,
+/// }
+///
+/// assert_eq!(
+///     Example { price: 1234 }.to_string(),
+///     "<div>€1,234.00 / 1.234,00\u{a0}€</div>\n<div>¥1,234 / 1\u{a0}234,00\u{a0}kr</div>"
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn currency<'a, T>(
+    value: T,
+    code: &'a str,
+    decimals: Option<usize>,
+    locale: &str,
+) -> crate::Result<NumberFormat<'a, T::Value>>
+where
+    T: PrimitiveType,
+    T::Value: NumberValue,
+{
+    if !is_currency_code(code) {
+        return Err(crate::Error::custom(format!(
+            "invalid currency code {code:?}, expected three ASCII uppercase letters"
+        )));
+    }
+    let (symbol, minor_unit) = match CURRENCIES.iter().find(|(c, ..)| *c == code) {
+        Some(&(_, symbol, minor_unit)) => (symbol, minor_unit),
+        None => (code, 2),
+    };
+    Ok(NumberFormat {
+        value: value.get(),
+        decimals: Some(decimals.unwrap_or(minor_unit)),
+        locale: Locale::required(locale)?,
+        kind: Kind::Currency(symbol),
+    })
+}
+
+/// The result of [`numberformat()`], [`percent()`] and [`currency()`]
+#[derive(Debug, Clone, Copy)]
+pub struct NumberFormat<'a, T> {
+    value: T,
+    decimals: Option<usize>,
+    locale: Locale,
+    kind: Kind<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind<'a> {
+    Number,
+    Percent,
+    Currency(&'a str),
+}
+
+impl<T: NumberValue> fmt::Display for NumberFormat<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(self.write_into(f)?)
+    }
+}
+
+impl<T: NumberValue> FastWritable for NumberFormat<'_, T> {
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> crate::Result<()> {
+        let Self {
+            ref value,
+            decimals,
+            ref locale,
+            kind,
+        } = *self;
+        match kind {
+            Kind::Number => value.write_number(dest, decimals, 0, locale),
+            Kind::Percent => {
+                value.write_number(dest, decimals, 2, locale)?;
+                Ok(dest.write_str(locale.percent)?)
+            }
+            Kind::Currency(symbol) => {
+                let mut dest = CurrencyWriter {
+                    dest,
+                    symbol,
+                    locale,
+                };
+                value.write_number(&mut dest, decimals, 0, locale)?;
+                if !locale.currency_first {
+                    dest.dest.write_str(locale.currency_space)?;
+                    dest.dest.write_str(symbol)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// The output only contains digits, separators, and currency symbols.
+impl<T: NumberValue> HtmlSafe for NumberFormat<'_, T> {}
+
+/// Inserts the currency symbol after the sign, if the locale puts it before the amount
+struct CurrencyWriter<'a, W: ?Sized> {
+    dest: &'a mut W,
+    symbol: &'a str,
+    locale: &'a Locale,
+}
+
+impl<W: fmt::Write + ?Sized> fmt::Write for CurrencyWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.locale.currency_first && !self.symbol.is_empty() {
+            let (sign, s) = match s.strip_prefix('-') {
+                Some(s) => ("-", s),
+                None => ("", s),
+            };
+            self.dest.write_str(sign)?;
+            if s.is_empty() {
+                return Ok(());
+            }
+            self.dest.write_str(self.symbol)?;
+            if self.symbol.ends_with(|c: char| c.is_ascii_alphabetic()) {
+                self.dest.write_str("\u{a0}")?;
+            } else {
+                self.dest.write_str(self.locale.currency_space)?;
+            }
+            self.symbol = "";
+        }
+        self.dest.write_str(s)
+    }
+}
+
+/// The separators and symbol placement of a locale, used by [`numberformat()`], [`percent()`]
+/// and [`currency()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    group: &'static str,
+    decimal: &'static str,
+    percent: &'static str,
+    currency_first: bool,
+    currency_space: &'static str,
+}
+
+impl Locale {
+    const fn new(
+        group: &'static str,
+        decimal: &'static str,
+        percent: &'static str,
+        currency_first: bool,
+        currency_space: &'static str,
+    ) -> Self {
+        Self {
+            group,
+            decimal,
+            percent,
+            currency_first,
+            currency_space,
+        }
+    }
+
+    /// Returns the locale for a language tag like `"de"` or `"de-CH"`
+    ///
+    /// The name is case-insensitive, and `_` can be used instead of `-`. If there is no entry
+    /// for the region, the entry of the language is used, e.g. `"de"` for `"de-AT"`.
+    ///
+    /// The known locales are `en`, `de`, `de-CH`, `es`, `fr`, `it`, `ja`, `ko`, `nl`, `pl`, `pt`
+    /// (Brazil), `pt-PT`, `ru`, `sv` and `zh`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let find = |name: &str| {
+            LOCALES
+                .iter()
+                .find(|(n, _)| {
+                    n.len() == name.len()
+                        && n.bytes()
+                            .zip(name.bytes())
+                            .all(|(a, b)| a == b.to_ascii_lowercase() || (a == b'-' && b == b'_'))
+                })
+                .map(|&(_, locale)| locale)
+        };
+        find(name).or_else(|| find(name.split(['-', '_']).next()?))
+    }
+
+    fn required(name: &str) -> crate::Result<Self> {
+        Self::from_name(name)
+            .ok_or_else(|| crate::Error::custom(format!("unknown locale {name:?}")))
+    }
+
+    /// The separator between groups of three digits, e.g. `","` in `1,234,567`
+    #[inline]
+    pub fn group(&self) -> &'static str {
+        self.group
+    }
+
+    /// The decimal separator, e.g. `"."` in `3.14`
+    #[inline]
+    pub fn decimal(&self) -> &'static str {
+        self.decimal
+    }
+}
+
+impl Default for Locale {
+    #[inline]
+    fn default() -> Self {
+        LOCALES[0].1
+    }
+}
+
+/// A primitive number that can be formatted by [`numberformat()`], [`percent()`] and
+/// [`currency()`]
+pub trait NumberValue {
+    /// Writes the number multiplied by `10^shift` with the separators of `locale`
+    ///
+    /// If `decimals` is `None`, integers are written without decimal places, and floats
+    /// with as many as needed.
+    fn write_number<W: fmt::Write + ?Sized>(
+        &self,
+        dest: &mut W,
+        decimals: Option<usize>,
+        shift: u32,
+        locale: &Locale,
+    ) -> crate::Result<()>;
+}
+
+macro_rules! impl_number_value_for_int {
+    ($($ty:ty => |$value:ident| $abs:expr),* $(,)?) => { $(
+        impl NumberValue for $ty {
+            #[allow(unused_comparisons)]
+            fn write_number<W: fmt::Write + ?Sized>(
+                &self,
+                dest: &mut W,
+                decimals: Option<usize>,
+                shift: u32,
+                locale: &Locale,
+            ) -> crate::Result<()> {
+                let $value = *self;
+                let abs = $abs;
+                let mut buffer = itoa::Buffer::new();
+                let shifted;
+                let digits = match shift {
+                    0 => buffer.format(abs),
+                    _ => match (abs as u128).checked_mul(10_u128.pow(shift)) {
+                        Some(abs) => buffer.format(abs),
+                        None => {
+                            shifted = format!("{abs}{:0<1$}", "", shift as usize);
+                            &shifted
+                        }
+                    },
+                };
+                let decimals = decimals.unwrap_or(0);
+                write_parts(dest, $value < 0 && abs != 0, digits, "", decimals, locale)
+            }
+        }
+    )* };
+}
+
+impl_number_value_for_int! {
+    i8 => |v| v.unsigned_abs(),
+    i16 => |v| v.unsigned_abs(),
+    i32 => |v| v.unsigned_abs(),
+    i64 => |v| v.unsigned_abs(),
+    i128 => |v| v.unsigned_abs(),
+    isize => |v| v.unsigned_abs(),
+    u8 => |v| v,
+    u16 => |v| v,
+    u32 => |v| v,
+    u64 => |v| v,
+    u128 => |v| v,
+    usize => |v| v,
+}
+
+macro_rules! impl_number_value_for_float {
+    ($($ty:ty),* $(,)?) => { $(
+        impl NumberValue for $ty {
+            fn write_number<W: fmt::Write + ?Sized>(
+                &self,
+                dest: &mut W,
+                decimals: Option<usize>,
+                shift: u32,
+                locale: &Locale,
+            ) -> crate::Result<()> {
+                let value = *self;
+                if value.is_nan() {
+                    return Ok(dest.write_str("NaN")?);
+                } else if value.is_infinite() {
+                    return Ok(dest.write_str(if value < 0.0 { "-∞" } else { "∞" })?);
+                }
+
+                // Moving the decimal point in the string representation is lossless, unlike
+                // multiplying the value.
+                let shift = shift as usize;
+                let abs = match decimals {
+                    Some(decimals) => format!("{:.*}", decimals + shift, value.abs()),
+                    None => format!("{}", value.abs()),
+                };
+                let (int, frac) = abs.split_once('.').unwrap_or((&abs, ""));
+                let (moved, frac) = frac.split_at(shift.min(frac.len()));
+                let mut digits = String::with_capacity(int.len() + shift);
+                digits.push_str(int.trim_start_matches('0'));
+                digits.push_str(moved);
+                digits.extend(core::iter::repeat('0').take(shift - moved.len()));
+                let digits = digits.trim_start_matches('0');
+                let digits = if digits.is_empty() { "0" } else { digits };
+
+                // Do not print `-0.00` if the value was rounded to zero.
+                let negative = value.is_sign_negative()
+                    && (digits != "0" || frac.bytes().any(|b| b != b'0'));
+                write_parts(dest, negative, digits, frac, 0, locale)
+            }
+        }
+    )* };
+}
+
+impl_number_value_for_float!(f32, f64);
+
+/// Writes the number `-digits.frac` followed by `zeros` additional decimal places
+fn write_parts<W: fmt::Write + ?Sized>(
+    dest: &mut W,
+    negative: bool,
+    digits: &str,
+    frac: &str,
+    zeros: usize,
+    locale: &Locale,
+) -> crate::Result<()> {
+    if negative {
+        dest.write_str("-")?;
+    }
+
+    let mut start = match digits.len() % 3 {
+        0 => 3.min(digits.len()),
+        len => len,
+    };
+    dest.write_str(&digits[..start])?;
+    while start < digits.len() {
+        dest.write_str(locale.group)?;
+        dest.write_str(&digits[start..start + 3])?;
+        start += 3;
+    }
+
+    if !frac.is_empty() || zeros > 0 {
+        dest.write_str(locale.decimal)?;
+        dest.write_str(frac)?;
+        for _ in 0..zeros {
+            dest.write_str("0")?;
+        }
+    }
+    Ok(())
+}
+
+fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+/// `(name, locale)`, the first entry is the default locale
+const LOCALES: &[(&str, Locale)] = &[
+    ("en", Locale::new(",", ".", "%", true, "")),
+    ("de", Locale::new(".", ",", "\u{a0}%", false, "\u{a0}")),
+    ("de-ch", Locale::new("’", ".", "%", true, "\u{a0}")),
+    ("es", Locale::new(".", ",", "\u{a0}%", false, "\u{a0}")),
+    (
+        "fr",
+        Locale::new("\u{202f}", ",", "\u{202f}%", false, "\u{a0}"),
+    ),
+    ("it", Locale::new(".", ",", "%", false, "\u{a0}")),
+    ("ja", Locale::new(",", ".", "%", true, "")),
+    ("ko", Locale::new(",", ".", "%", true, "")),
+    ("nl", Locale::new(".", ",", "%", true, "\u{a0}")),
+    ("pl", Locale::new("\u{a0}", ",", "%", false, "\u{a0}")),
+    ("pt", Locale::new(".", ",", "%", true, "\u{a0}")),
+    ("pt-pt", Locale::new("\u{a0}", ",", "%", false, "\u{a0}")),
+    ("ru", Locale::new("\u{a0}", ",", "\u{a0}%", false, "\u{a0}")),
+    ("sv", Locale::new("\u{a0}", ",", "\u{a0}%", false, "\u{a0}")),
+    ("zh", Locale::new(",", ".", "%", true, "")),
+];
+
+/// `(code, symbol, minor unit)` of common currencies
+const CURRENCIES: &[(&str, &str, usize)] = &[
+    ("AUD", "A$", 2),
+    ("BRL", "R$", 2),
+    ("CAD", "CA$", 2),
+    ("CHF", "CHF", 2),
+    ("CNY", "CN¥", 2),
+    ("CZK", "Kč", 2),
+    ("DKK", "kr.", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("HUF", "Ft", 2),
+    ("ILS", "₪", 2),
+    ("INR", "₹", 2),
+    ("ISK", "kr", 0),
+    ("JPY", "¥", 0),
+    ("KRW", "₩", 0),
+    ("NOK", "kr", 2),
+    ("PLN", "zł", 2),
+    ("RUB", "₽", 2),
+    ("SEK", "kr", 2),
+    ("TRY", "₺", 2),
+    ("UAH", "₴", 2),
+    ("USD", "$", 2),
+    ("VND", "₫", 0),
+];
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_numberformat() {
+        let fmt = |value: f64, decimals, locale| {
+            numberformat(value, decimals, locale).unwrap().to_string()
+        };
+        assert_eq!(fmt(1234567.891, None, "en"), "1,234,567.891");
+        assert_eq!(fmt(1234567.891, Some(2), "en"), "1,234,567.89");
+        assert_eq!(fmt(1234567.891, Some(2), "de"), "1.234.567,89");
+        assert_eq!(fmt(1234567.891, Some(0), "de_DE"), "1.234.568");
+        assert_eq!(fmt(-999.999, Some(2), "EN-us"), "-1,000.00");
+        assert_eq!(fmt(-0.001, Some(2), "en"), "0.00");
+        assert_eq!(fmt(0.5, None, "fr"), "0,5");
+        assert_eq!(fmt(123.0, None, "en"), "123");
+        assert_eq!(fmt(f64::NEG_INFINITY, None, "en"), "-∞");
+
+        assert_eq!(numberformat(0, None, "en").unwrap().to_string(), "0");
+        assert_eq!(numberformat(&999, None, "en").unwrap().to_string(), "999");
+        assert_eq!(
+            numberformat(-1000_i16, Some(1), "de-CH")
+                .unwrap()
+                .to_string(),
+            "-1’000.0"
+        );
+        assert_eq!(
+            numberformat(u128::MAX, None, "en").unwrap().to_string(),
+            "340,282,366,920,938,463,463,374,607,431,768,211,455"
+        );
+        assert_eq!(
+            numberformat(i8::MIN, None, "ru").unwrap().to_string(),
+            "-128"
+        );
+        assert!(numberformat(1, None, "xx").is_err());
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(0.2856, None, "en").unwrap().to_string(), "29%");
+        assert_eq!(percent(0.2856, Some(1), "en").unwrap().to_string(), "28.6%");
+        assert_eq!(
+            percent(0.29_f32, Some(2), "de").unwrap().to_string(),
+            "29,00\u{a0}%"
+        );
+        assert_eq!(percent(12.5, None, "en").unwrap().to_string(), "1,250%");
+        assert_eq!(percent(3, None, "en").unwrap().to_string(), "300%");
+        assert_eq!(
+            percent(u128::MAX, None, "en").unwrap().to_string(),
+            "34,028,236,692,093,846,346,337,460,743,176,821,145,500%"
+        );
+    }
+
+    #[test]
+    fn test_currency() {
+        let fmt =
+            |value: f64, code, locale| currency(value, code, None, locale).unwrap().to_string();
+        assert_eq!(fmt(1234.5, "USD", "en"), "$1,234.50");
+        assert_eq!(fmt(-1234.5, "USD", "en"), "-$1,234.50");
+        assert_eq!(fmt(1234.5, "EUR", "de"), "1.234,50\u{a0}€");
+        assert_eq!(fmt(-1234.5, "EUR", "nl"), "-€\u{a0}1.234,50");
+        assert_eq!(fmt(1234.5, "CHF", "en"), "CHF\u{a0}1,234.50");
+        assert_eq!(fmt(1234.56, "JPY", "ja"), "¥1,235");
+        assert_eq!(fmt(1234.5, "XTS", "en"), "XTS\u{a0}1,234.50");
+        assert_eq!(
+            currency(5, "EUR", Some(0), "fr").unwrap().to_string(),
+            "5\u{a0}€"
+        );
+        assert!(currency(5, "eur", None, "en").is_err());
+        assert!(currency(5, "<b>", None, "en").is_err());
+    }
+}
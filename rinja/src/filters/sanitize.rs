@@ -0,0 +1,527 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use super::escape::{HtmlSafeOutput, has_safe_scheme};
+
+/// The elements and attributes that are kept by [`sanitize()`]
+///
+/// In templates, the allow-list can be configured in the `[sanitize]` section of your
+/// `rinja.toml`. All names must be lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Allowlist<'a> {
+    /// The allowed elements, e.g. `"b"`
+    pub tags: &'a [&'a str],
+    /// The allowed attributes of allowed elements, e.g. `"href"`
+    pub attributes: &'a [&'a str],
+}
+
+impl Allowlist<'static> {
+    /// Simple text formatting, links, lists, and quotes
+    pub const DEFAULT: Self = Self {
+        tags: &[
+            "a",
+            "abbr",
+            "b",
+            "blockquote",
+            "br",
+            "code",
+            "em",
+            "i",
+            "li",
+            "ol",
+            "p",
+            "pre",
+            "s",
+            "strong",
+            "sub",
+            "sup",
+            "u",
+            "ul",
+        ],
+        attributes: &["href", "title"],
+    };
+}
+
+impl Default for Allowlist<'static> {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Removes all HTML elements and attributes that are not in the [`Allowlist`]
+///
+/// Use this filter to render rich text of your users, e.g. comments, without allowing them to
+/// inject scripts. The content of removed elements is kept, except for `<script>`, `<style>` and
+/// other elements that contain raw text. Comments are removed, too.
+///
+/// * Event handler attributes like `onclick` are never kept.
+/// * URLs in attributes like `href` and `src` must use a safe scheme, i.e. `http`, `https`,
+///   `mailto`, `tel`, or no scheme at all.
+/// * All elements that were opened are closed again.
+///
+/// Rinja inserts the allow-list that is configured in the `[sanitize]` section of your
+/// `rinja.toml`, or [`Allowlist::DEFAULT`].
+///
+/// ```
+/// # #[cfg(feature = "code-in-doc")] {
+/// # use rinja::Template;
+/// /// ```jinja
+/// /// <div>{{ comment|sanitize }}</div>
+/// /// ```
+/// #[derive(Template)]
+/// #[template(ext = "html", in_doc = true)]
+/// struct Example<'a> {
+///     comment: &'a str,
+/// }
+///
+/// assert_eq!(
+///     Example {
+///         comment: r#"<b onclick="evil()">Hi</b><script>evil()</script> <a href="/x">x"#,
+///     }
+///     .to_string(),
+///     r#"<div><b>Hi</b> <a href="/x">x</a></div>"#
+/// );
+/// # }
+/// ```
+#[inline]
+pub fn sanitize(
+    s: impl fmt::Display,
+    allowlist: &Allowlist<'_>,
+) -> Result<HtmlSafeOutput<String>, fmt::Error> {
+    let args = format_args!("{s}");
+    let mut buffer;
+    let source = match args.as_str() {
+        Some(s) => s,
+        None => {
+            buffer = String::new();
+            buffer.write_fmt(args)?;
+            &buffer
+        }
+    };
+    Ok(HtmlSafeOutput(Sanitizer::new(allowlist).run(source)))
+}
+
+struct Sanitizer<'a> {
+    allowlist: &'a Allowlist<'a>,
+    output: String,
+    open: Vec<&'a str>,
+}
+
+impl<'a> Sanitizer<'a> {
+    fn new(allowlist: &'a Allowlist<'a>) -> Self {
+        Self {
+            allowlist,
+            output: String::new(),
+            open: Vec::new(),
+        }
+    }
+
+    fn run(mut self, mut rest: &str) -> String {
+        self.output.reserve(rest.len());
+        while let Some(start) = rest.find('<') {
+            push_escaped(&mut self.output, &rest[..start]);
+            rest = self.tag(&rest[start..]);
+        }
+        push_escaped(&mut self.output, rest);
+        while let Some(name) = self.open.pop() {
+            self.close(name);
+        }
+        self.output
+    }
+
+    /// Handles the markup at the start of `src`, which starts with `<`, returns the rest
+    fn tag<'s>(&mut self, src: &'s str) -> &'s str {
+        if let Some(comment) = src.strip_prefix("<!--") {
+            return comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if src.starts_with("<!") || src.starts_with("<?") {
+            return src.find('>').map_or("", |end| &src[end + 1..]);
+        }
+
+        let (closing, name_start) = match src.as_bytes().get(1) {
+            Some(b'/') => (true, 2),
+            _ => (false, 1),
+        };
+        if !src[name_start..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            self.output.push_str("&#60;");
+            return &src[1..];
+        }
+        let name_end = src[name_start..]
+            .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+            .map_or(src.len(), |end| name_start + end);
+        let name = &src[name_start..name_end];
+
+        if closing {
+            let rest = src[name_end..]
+                .find('>')
+                .map_or("", |end| &src[name_end + end + 1..]);
+            if let Some(index) = self.open.iter().rposition(|n| n.eq_ignore_ascii_case(name)) {
+                for name in self.open.split_off(index).into_iter().rev() {
+                    self.close(name);
+                }
+            }
+            return rest;
+        }
+
+        let mut attrs = Vec::new();
+        let Some(rest) = parse_attributes(&src[name_end..], &mut attrs) else {
+            // an unterminated tag is dropped, with everything after it
+            return "";
+        };
+
+        if RAW_TEXT_ELEMENTS
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(name))
+        {
+            return skip_raw_text(rest, name);
+        }
+        let Some(&name) = self
+            .allowlist
+            .tags
+            .iter()
+            .find(|n| n.eq_ignore_ascii_case(name))
+        else {
+            return rest;
+        };
+
+        self.output.push('<');
+        self.output.push_str(name);
+        let mut written: Vec<&str> = Vec::new();
+        for (attr, value) in attrs {
+            let Some(&attr) = self
+                .allowlist
+                .attributes
+                .iter()
+                .find(|n| n.eq_ignore_ascii_case(attr))
+            else {
+                continue;
+            };
+            if attr.starts_with("on") || written.contains(&attr) {
+                continue;
+            }
+            if URL_ATTRIBUTES.contains(&attr) {
+                let Some(url) = value
+                    .and_then(decode_url)
+                    .filter(|url| has_safe_scheme(url))
+                else {
+                    continue;
+                };
+                self.output.push(' ');
+                self.output.push_str(attr);
+                self.output.push_str("=\"");
+                // writing into a `String` cannot fail
+                let _ = crate::html::write_escaped_str(&mut self.output, &url);
+                self.output.push('"');
+            } else {
+                self.output.push(' ');
+                self.output.push_str(attr);
+                if let Some(value) = value {
+                    self.output.push_str("=\"");
+                    push_escaped(&mut self.output, value);
+                    self.output.push('"');
+                }
+            }
+            written.push(attr);
+        }
+        self.output.push('>');
+        if !VOID_ELEMENTS.contains(&name) {
+            self.open.push(name);
+        }
+        rest
+    }
+
+    fn close(&mut self, name: &str) {
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>');
+    }
+}
+
+/// Elements whose content is not HTML, and which are removed together with their content
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe",
+    "noembed",
+    "noframes",
+    "noscript",
+    "plaintext",
+    "script",
+    "style",
+    "template",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+/// Elements that have no end tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Attributes that contain a URL, which must have a safe scheme
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "longdesc",
+    "poster",
+    "src",
+    "srcset",
+    "xlink:href",
+];
+
+/// Parses the attributes of a start tag up to and including its `>`, returns the rest
+///
+/// Returns `None` if the tag is not terminated.
+fn parse_attributes<'s>(
+    mut src: &'s str,
+    attrs: &mut Vec<(&'s str, Option<&'s str>)>,
+) -> Option<&'s str> {
+    loop {
+        src = src.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if let Some(rest) = src.strip_prefix('>') {
+            return Some(rest);
+        } else if src.is_empty() {
+            return None;
+        }
+
+        // the first character of a name can be `=`
+        let first = src.chars().next().map_or(1, char::len_utf8);
+        let name_end = src[first..]
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '/' | '>' | '='))
+            .map_or(src.len(), |end| end + first);
+        let name = &src[..name_end];
+        src = src[name_end..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+
+        let Some(value) = src.strip_prefix('=') else {
+            attrs.push((name, None));
+            continue;
+        };
+        let value = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+        let (value, rest) = match value.as_bytes().first() {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = value[1..].find(char::from(quote))?;
+                (&value[1..end + 1], &value[end + 2..])
+            }
+            _ => {
+                let end = value
+                    .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .unwrap_or(value.len());
+                value.split_at(end)
+            }
+        };
+        attrs.push((name, Some(value)));
+        src = rest;
+    }
+}
+
+/// Skips the content of a raw text element `name` and its end tag
+fn skip_raw_text<'s>(src: &'s str, name: &str) -> &'s str {
+    for (start, _) in src.match_indices("</") {
+        let tail = &src[start + 2..];
+        let is_end_tag = tail
+            .get(..name.len())
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            && tail[name.len()..]
+                .chars()
+                .next()
+                .map_or(true, |c| c.is_ascii_whitespace() || c == '/' || c == '>');
+        if is_end_tag {
+            return tail.find('>').map_or("", |end| &tail[end + 1..]);
+        }
+    }
+    ""
+}
+
+/// Escapes `src`, but keeps character references like `&amp;` as they are
+fn push_escaped(output: &mut String, mut src: &str) {
+    while let Some(pos) = src.find(['&', '<', '>', '"', '\'']) {
+        output.push_str(&src[..pos]);
+        let len = match src.as_bytes()[pos] {
+            b'&' => char_ref_len(&src[pos..]),
+            _ => 0,
+        };
+        match len {
+            0 => {
+                let c = src.as_bytes()[pos];
+                output.push_str(match c {
+                    b'&' => "&#38;",
+                    b'<' => "&#60;",
+                    b'>' => "&#62;",
+                    b'"' => "&#34;",
+                    _ => "&#39;",
+                });
+                src = &src[pos + 1..];
+            }
+            len => {
+                output.push_str(&src[pos..pos + len]);
+                src = &src[pos + len..];
+            }
+        }
+    }
+    output.push_str(src);
+}
+
+/// Returns the length of the character reference at the start of `src`, or `0`
+fn char_ref_len(src: &str) -> usize {
+    let bytes = src.as_bytes();
+    let count = |start: usize, pred: fn(&u8) -> bool| {
+        let len = bytes[start..].iter().take_while(|b| pred(b)).count();
+        (len > 0).then_some(start + len)
+    };
+    let end = match (bytes.get(1), bytes.get(2)) {
+        (Some(b'#'), Some(b'x' | b'X')) => count(3, u8::is_ascii_hexdigit),
+        (Some(b'#'), _) => count(2, u8::is_ascii_digit),
+        _ => count(1, u8::is_ascii_alphanumeric).filter(|_| bytes[1].is_ascii_alphabetic()),
+    };
+    match end {
+        Some(end) if bytes.get(end) == Some(&b';') => end + 1,
+        _ => 0,
+    }
+}
+
+/// Decodes the character references in the value of a URL attribute
+///
+/// Returns `None` if the value contains a reference that is not known, so the URL cannot be
+/// checked. ASCII tabs and newlines are removed, like browsers do.
+fn decode_url(value: &str) -> Option<String> {
+    const NAMED: &[(&str, char)] = &[
+        ("amp;", '&'),
+        ("apos;", '\''),
+        ("colon;", ':'),
+        ("gt;", '>'),
+        ("lt;", '<'),
+        ("NewLine;", '\n'),
+        ("quot;", '"'),
+        ("Tab;", '\t'),
+    ];
+
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('&') {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(num) = rest.strip_prefix('#') {
+            let (radix, digits) = match num.strip_prefix(['x', 'X']) {
+                Some(hex) => (16, hex),
+                None => (10, num),
+            };
+            let len = digits
+                .find(|c: char| !c.is_digit(radix))
+                .unwrap_or(digits.len());
+            if len == 0 {
+                return None;
+            }
+            let c = u32::from_str_radix(&digits[..len], radix)
+                .ok()
+                .and_then(char::from_u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            output.push(c);
+            rest = &digits[len..];
+            rest = rest.strip_prefix(';').unwrap_or(rest);
+        } else if let Some(&(name, c)) = NAMED.iter().find(|(name, _)| rest.starts_with(name)) {
+            output.push(c);
+            rest = &rest[name.len()..];
+        } else if rest.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            return None;
+        } else {
+            output.push('&');
+        }
+    }
+    output.push_str(rest);
+    output.retain(|c| !matches!(c, '\t' | '\n' | '\r'));
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    fn sanitize(src: &str) -> String {
+        super::sanitize(src, &Allowlist::DEFAULT).unwrap().0
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("a < b && c > d"), "a &#60; b &#38;&#38; c &#62; d");
+        assert_eq!(sanitize("&amp; &#x27; &eacute;"), "&amp; &#x27; &eacute;");
+        assert_eq!(
+            sanitize("<P CLASS=x>Hi <b>there<i>!</b></p>"),
+            "<p>Hi <b>there<i>!</i></b></p>"
+        );
+        assert_eq!(sanitize("<ul><li>1<li>2"), "<ul><li>1<li>2</li></li></ul>");
+        assert_eq!(sanitize("</b>a<br/>b"), "a<br>b");
+        assert_eq!(sanitize("<div><span>a</span></div>"), "a");
+        assert_eq!(sanitize("a<!-- <b> -->b<!x>c<?y>d"), "abcd");
+        assert_eq!(sanitize("a<b"), "a");
+        assert_eq!(sanitize("a<3 <"), "a&#60;3 &#60;");
+    }
+
+    #[test]
+    fn test_sanitize_raw_text() {
+        assert_eq!(
+            sanitize("a<script>alert('<b>')</script >b<STYLE>*{}</style>c"),
+            "abc"
+        );
+        assert_eq!(sanitize("a<script>document.write('</scripts>')"), "a");
+        assert_eq!(sanitize("<b><textarea></b></textarea>x"), "<b>x</b>");
+    }
+
+    #[test]
+    fn test_sanitize_attributes() {
+        assert_eq!(
+            sanitize(r#"<a href="/x?a=1&amp;b=2" title='"hi"' onclick="x()" rel=a>x</a>"#),
+            r#"<a href="/x?a=1&#38;b=2" title="&#34;hi&#34;">x</a>"#
+        );
+        assert_eq!(
+            sanitize("<abbr title=a&b title=c>x</abbr>"),
+            r#"<abbr title="a&#38;b">x</abbr>"#
+        );
+        assert_eq!(sanitize("<a href title=x>x</a>"), r#"<a title="x">x</a>"#);
+        for href in [
+            "javascript:alert(1)",
+            " JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            "&#106;avascript:alert(1)",
+            "&#x6A;avascript:alert(1)",
+            "&#106avascript:alert(1)",
+            "javascript&colon;alert(1)",
+            "java&Tab;script:alert(1)",
+            "&jscr;avascript:alert(1)",
+            "data:text/html,x",
+        ] {
+            assert_eq!(
+                sanitize(&format!("<a href='{href}'>x</a>")),
+                "<a>x</a>",
+                "{href:?}"
+            );
+        }
+        assert_eq!(
+            sanitize("<a href='mailto:a@b.c'>x</a>"),
+            r#"<a href="mailto:a@b.c">x</a>"#
+        );
+    }
+
+    #[test]
+    fn test_custom_allowlist() {
+        let allowlist = Allowlist {
+            tags: &["img", "span"],
+            attributes: &["src", "class", "onerror"],
+        };
+        assert_eq!(
+            super::sanitize(
+                r#"<b>a</b><img src="a.png" onerror="x()" class=big><span class="a b">c"#,
+                &allowlist,
+            )
+            .unwrap()
+            .0,
+            r#"a<img src="a.png" class="big"><span class="a b">c</span>"#
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use std::convert::Infallible;
-use std::fmt;
-use std::fmt::Write;
+use core::convert::Infallible;
+use core::fmt;
+use core::fmt::Write;
 
 use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
 
@@ -128,6 +128,8 @@ impl<W: fmt::Write> fmt::Write for UrlencodeWriter<W> {
 
 #[test]
 fn test_urlencoding() {
+    use alloc::string::ToString;
+
     // Unreserved (https://tools.ietf.org/html/rfc3986.html#section-2.3)
     // alpha / digit
     assert_eq!(urlencode("AZaz09").unwrap().to_string(), "AZaz09");
@@ -1,12 +1,16 @@
-use std::cell::Cell;
-use std::fmt;
-use std::iter::{Enumerate, Peekable};
-use std::ops::Deref;
-use std::pin::Pin;
+use alloc::boxed::Box;
+use core::cell::Cell;
+use core::fmt;
+use core::iter::{Enumerate, Peekable};
+use core::ops::Deref;
+use core::pin::Pin;
 
 // The re-exports are used in the generated code for macro hygiene. Even if the paths `::core` or
-// `::std` are shadowed, the generated code will still be able to access the crates.
-pub use {core, std};
+// `::alloc` are shadowed, the generated code will still be able to access the crates.
+pub extern crate alloc;
+#[cfg(feature = "std")]
+pub extern crate std;
+pub use core;
 
 use crate::filters::FastWritable;
 
@@ -15,6 +19,8 @@ where
     I: Iterator,
 {
     iter: Peekable<Enumerate<I>>,
+    /// Only loops of a `render_with_limits()` call need to count their iterations
+    budgeted: bool,
 }
 
 impl<I> TemplateLoop<I>
@@ -25,6 +31,7 @@ where
     pub fn new(iter: I) -> Self {
         TemplateLoop {
             iter: iter.enumerate().peekable(),
+            budgeted: crate::limits::loop_budget_active(),
         }
     }
 }
@@ -37,13 +44,15 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<(<I as Iterator>::Item, LoopItem)> {
-        self.iter.next().map(|(index, item)| {
-            (item, LoopItem {
-                index,
-                first: index == 0,
-                last: self.iter.peek().is_none(),
-            })
-        })
+        let (index, item) = self.iter.next()?;
+        if self.budgeted && !crate::limits::take_loop_iteration() {
+            return None;
+        }
+        Some((item, LoopItem {
+            index,
+            first: index == 0,
+            last: self.iter.peek().is_none(),
+        }))
     }
 }
 
@@ -153,7 +162,7 @@ where
 ///
 /// ```
 /// # use std::cell::Cell;
-/// # use std::num::{NonZeroI16, Saturating};
+/// # use core::num::{NonZeroI16, Saturating};
 /// # use std::rc::Rc;
 /// # use std::pin::Pin;
 /// # use rinja::Template;
@@ -177,7 +186,7 @@ impl<T: PrimitiveType + Copy> PrimitiveType for Cell<T> {
     }
 }
 
-impl<T: PrimitiveType> PrimitiveType for std::num::Wrapping<T> {
+impl<T: PrimitiveType> PrimitiveType for core::num::Wrapping<T> {
     type Value = T::Value;
 
     #[inline]
@@ -186,7 +195,7 @@ impl<T: PrimitiveType> PrimitiveType for std::num::Wrapping<T> {
     }
 }
 
-impl<T: PrimitiveType> PrimitiveType for std::num::Saturating<T> {
+impl<T: PrimitiveType> PrimitiveType for core::num::Saturating<T> {
     type Value = T::Value;
 
     #[inline]
@@ -209,18 +218,76 @@ macro_rules! primitize_nz {
 }
 
 primitize_nz! {
-    std::num::NonZeroI8 => i8,
-    std::num::NonZeroI16 => i16,
-    std::num::NonZeroI32 => i32,
-    std::num::NonZeroI64 => i64,
-    std::num::NonZeroI128 => i128,
-    std::num::NonZeroIsize => isize,
-    std::num::NonZeroU8 => u8,
-    std::num::NonZeroU16 => u16,
-    std::num::NonZeroU32 => u32,
-    std::num::NonZeroU64 => u64,
-    std::num::NonZeroU128 => u128,
-    std::num::NonZeroUsize => usize,
+    core::num::NonZeroI8 => i8,
+    core::num::NonZeroI16 => i16,
+    core::num::NonZeroI32 => i32,
+    core::num::NonZeroI64 => i64,
+    core::num::NonZeroI128 => i128,
+    core::num::NonZeroIsize => isize,
+    core::num::NonZeroU8 => u8,
+    core::num::NonZeroU16 => u16,
+    core::num::NonZeroU32 => u32,
+    core::num::NonZeroU64 => u64,
+    core::num::NonZeroU128 => u128,
+    core::num::NonZeroUsize => usize,
+}
+
+/// A primitive number that can be added up by the `sum` filter
+pub trait CheckedAdd: Copy {
+    const ZERO: Self;
+
+    /// Returns `None` if the sum overflows
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! checked_add_int {
+    ($($ty:ty),* $(,)?) => {$(
+        impl CheckedAdd for $ty {
+            const ZERO: Self = 0;
+
+            #[inline]
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$ty>::checked_add(self, other)
+            }
+        }
+    )*};
+}
+
+checked_add_int! {
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize,
+}
+
+macro_rules! checked_add_float {
+    ($($ty:ty),* $(,)?) => {$(
+        impl CheckedAdd for $ty {
+            const ZERO: Self = 0.0;
+
+            /// Floats become infinite instead of overflowing
+            #[inline]
+            fn checked_add(self, other: Self) -> Option<Self> {
+                Some(self + other)
+            }
+        }
+    )*};
+}
+
+checked_add_float!(f32, f64);
+
+/// `map(attribute="...")` without a function returns a reference to the attribute, so the items
+/// must be references, too
+#[diagnostic::on_unimplemented(
+    message = "`map(attribute=...)` cannot return a reference into the owned item `{Self}`",
+    label = "the items of the collection are not references",
+    note = "assign the collection to a variable with `{{% let %}}` first, so it is borrowed"
+)]
+pub trait BorrowedItem {}
+
+impl<T: ?Sized> BorrowedItem for &T {}
+
+#[inline]
+pub fn borrowed_item<T: BorrowedItem>(item: T) -> T {
+    item
 }
 
 /// An empty element, so nothing will be written.
@@ -265,9 +332,56 @@ impl<L: FastWritable, R: FastWritable> FastWritable for Concat<L, R> {
 }
 
 #[inline]
-pub fn map_try<T, E>(result: Result<T, E>) -> Result<T, crate::Error>
+pub fn map_try<T, E>(
+    result: Result<T, E>,
+    location: &'static crate::Location,
+) -> Result<T, crate::Error>
+where
+    E: Into<Box<dyn crate::error::StdError + Send + Sync>>,
+{
+    result.map_err(|err| crate::Error::custom(err).with_location(location))
+}
+
+#[inline]
+pub fn map_filter<T, E>(
+    result: Result<T, E>,
+    location: &'static crate::Location,
+) -> Result<T, crate::Error>
 where
-    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    E: Into<crate::Error>,
 {
-    result.map_err(crate::Error::custom)
+    result.map_err(|err| err.into().with_location(location))
+}
+
+#[cfg(feature = "with-actix-web")]
+pub use actix_web;
+#[cfg(feature = "with-axum")]
+pub use axum_core;
+
+#[cfg(feature = "with-actix-web")]
+#[inline]
+pub fn actix_respond_to<T: crate::Template + ?Sized>(tmpl: &T) -> actix_web::HttpResponse {
+    match tmpl.render() {
+        Ok(body) => actix_web::HttpResponse::Ok()
+            .content_type(T::MIME_TYPE)
+            .body(body),
+        Err(err) => actix_web::HttpResponse::from_error(err),
+    }
+}
+
+#[cfg(feature = "with-axum")]
+#[inline]
+pub fn axum_into_response<T: crate::Template + ?Sized>(tmpl: &T) -> axum_core::response::Response {
+    use axum_core::response::IntoResponse;
+    use http::StatusCode;
+    use http::header::{CONTENT_TYPE, HeaderValue};
+
+    match tmpl.render() {
+        Ok(body) => (
+            [(CONTENT_TYPE, HeaderValue::from_static(T::MIME_TYPE))],
+            body,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
 }
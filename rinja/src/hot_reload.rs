@@ -0,0 +1,239 @@
+//! Re-render templates from their source file, used by the generated code in debug builds
+//!
+//! If the feature `"hot-reload"` is enabled, the code generated for templates that were loaded
+//! from a file calls [`render()`] before running the compiled code. It checks the template file
+//! on disk, and if its content differs from the content the template was compiled from, the new
+//! content is parsed and interpreted. That way a change to a template only needs a reload in the
+//! browser and not a recompilation. As long as the file is unmodified, the compiled code is used.
+//!
+//! Only templates that are self-contained are interpreted. If the template uses a construct the
+//! interpreter does not support, e.g. `{% extends %}`, `{% include %}`, macros, or calling rust
+//! methods and functions, then the compiled code is used.
+
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::fmt;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+use std::{fs, io};
+
+pub use rinja_parser::node::Whitespace;
+use rinja_parser::{Parsed, SyntaxBuilder};
+
+use crate::Error;
+use crate::dynamic::interpreter::{Interpreter, Mode};
+pub use crate::dynamic::{DynEscaper, ToValue, Value};
+use crate::filters::{Html, HtmlSafe};
+
+/// Information about the template file, as seen by `rinja_derive`
+pub struct Source {
+    /// Absolute path to the template file
+    pub path: &'static str,
+    /// `[block_start, block_end, expr_start, expr_end, comment_start, comment_end]`
+    pub syntax: [&'static str; 6],
+    /// The default whitespace handling of the template
+    pub whitespace: Whitespace,
+    /// The [`hash()`] of the file content that the template was compiled from
+    pub hash: u64,
+}
+
+/// Hashes the content of a template file, so [`render()`] can tell if it was modified
+///
+/// This is the 64 bit FNV-1a hash, which can be computed in a `const` context.
+pub const fn hash(content: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut i = 0;
+    while i < content.len() {
+        hash ^= content[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Renders the current content of the template file `source` into `writer`
+///
+/// Returns `None` if the compiled code should be used instead, i.e. if the file is unmodified,
+/// if it cannot be read, or if the template uses a construct that cannot be interpreted.
+pub fn render<W: fmt::Write + ?Sized>(
+    writer: &mut W,
+    source: &Source,
+    escaper: &dyn DynEscaper,
+    fields: &dyn Fn(&str) -> Option<Value>,
+) -> Option<crate::Result<()>> {
+    let parsed = match load(source) {
+        Ok(parsed) => parsed?,
+        Err(err) => return Some(Err(err)),
+    };
+    let output = Interpreter::new(Mode::Mirror, escaper, fields, source.whitespace)
+        .render(parsed.nodes())
+        .ok()?;
+    Some(writer.write_str(&output).map_err(Error::from))
+}
+
+type Cache = HashMap<(&'static str, [&'static str; 6]), (SystemTime, Option<Arc<Parsed>>)>;
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+/// Reads and parses the template file, unless the cached version is still up to date
+///
+/// Returns `None` if the file is unmodified or cannot be read.
+fn load(source: &Source) -> Result<Option<Arc<Parsed>>, Error> {
+    let key = (source.path, source.syntax);
+    let path = Path::new(source.path);
+    let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+        return Ok(None);
+    };
+
+    if let Some(cache) = &*CACHE.lock().unwrap_or_else(PoisonError::into_inner) {
+        if let Some((cached_modified, parsed)) = cache.get(&key) {
+            if *cached_modified == modified {
+                return Ok(parsed.clone());
+            }
+        }
+    }
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => return Err(Error::custom(err)),
+        Err(_) => return Ok(None),
+    };
+    let parsed = match hash(content.as_bytes()) == source.hash {
+        true => None,
+        false => parse(source, content)?,
+    };
+
+    CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(HashMap::new)
+        .insert(key, (modified, parsed.clone()));
+    Ok(parsed)
+}
+
+fn parse(source: &Source, mut content: String) -> Result<Option<Arc<Parsed>>, Error> {
+    // `rinja_derive` strips one trailing newline, too
+    if content.ends_with('\n') {
+        content.pop();
+    }
+    let [
+        block_start,
+        block_end,
+        expr_start,
+        expr_end,
+        comment_start,
+        comment_end,
+    ] = source.syntax.map(Some);
+    let Ok(syntax) = (SyntaxBuilder {
+        name: "hot-reload",
+        block_start,
+        block_end,
+        expr_start,
+        expr_end,
+        comment_start,
+        comment_end,
+    })
+    .to_syntax() else {
+        return Ok(None);
+    };
+    let path = Arc::from(Path::new(source.path));
+    let parsed = Parsed::new(content.into(), Some(path), &syntax).map_err(Error::custom)?;
+    Ok(Some(Arc::new(parsed)))
+}
+
+/// Used by the generated code to make a template field accessible to the interpreter
+///
+/// The method [`ReflectValue::rinja_reflect()`] uses autoref-based specialization to select
+/// how the field is converted:
+///
+/// * types that implement [`ToValue`] are converted losslessly,
+/// * [`HtmlSafe`] types are rendered and not escaped again if the escaper is [`Html`],
+/// * other types that implement [`Display`][fmt::Display] are rendered into a string, and
+/// * any other type is inaccessible.
+pub struct Reflect<'a, T: ?Sized, E> {
+    value: &'a T,
+    _escaper: E,
+}
+
+impl<'a, T: ?Sized, E> Reflect<'a, T, E> {
+    /// Wraps a template field
+    #[inline]
+    pub fn new(value: &'a T, escaper: E) -> Self {
+        Self {
+            value,
+            _escaper: escaper,
+        }
+    }
+}
+
+/// Used by the generated code to make a template field accessible to the interpreter
+pub trait ReflectValue {
+    /// Converts the field into a [`Value`], if possible
+    fn rinja_reflect(&self) -> Option<Value>;
+}
+
+impl<T: ToValue + ?Sized, E> ReflectValue for &&&Reflect<'_, T, E> {
+    #[inline]
+    fn rinja_reflect(&self) -> Option<Value> {
+        Some(self.value.to_value())
+    }
+}
+
+impl<T: HtmlSafe + ?Sized> ReflectValue for &&Reflect<'_, T, Html> {
+    #[inline]
+    fn rinja_reflect(&self) -> Option<Value> {
+        Some(Value::Safe(self.value.to_string().into()))
+    }
+}
+
+impl<T: fmt::Display + ?Sized, E> ReflectValue for &Reflect<'_, T, E> {
+    #[inline]
+    fn rinja_reflect(&self) -> Option<Value> {
+        Some(Value::Str(self.value.to_string().into()))
+    }
+}
+
+impl<T: ?Sized, E> ReflectValue for Reflect<'_, T, E> {
+    #[inline]
+    fn rinja_reflect(&self) -> Option<Value> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::filters::{HtmlSafeOutput, Text};
+
+    #[test]
+    #[allow(clippy::needless_borrow)] // autoref-based specialization
+    fn test_reflect() {
+        struct Opaque;
+
+        assert_eq!(
+            (&&&&Reflect::new(&vec![1_u8, 2], Html)).rinja_reflect(),
+            Some(Value::List([Value::Int(1), Value::Int(2)].into())),
+        );
+        assert_eq!(
+            (&&&&Reflect::new(&HtmlSafeOutput("<b>"), Html)).rinja_reflect(),
+            Some(Value::Safe("<b>".into())),
+        );
+        assert_eq!(
+            (&&&&Reflect::new(&HtmlSafeOutput("<b>"), Text)).rinja_reflect(),
+            Some(Value::Str("<b>".into())),
+        );
+        assert_eq!(
+            (&&&&Reflect::new(&1.5_f32, Html)).rinja_reflect(),
+            Some(Value::Safe("1.5".into())),
+        );
+        assert_eq!(
+            (&&&&Reflect::new(&'<', Html)).rinja_reflect(),
+            Some(Value::Str("<".into())),
+        );
+        assert_eq!((&&&&Reflect::new(&Opaque, Html)).rinja_reflect(), None);
+    }
+}
@@ -1,25 +1,78 @@
-use std::{fmt, str};
+use core::{fmt, str};
 
 #[allow(unused)]
 pub(crate) fn write_escaped_str(mut dest: impl fmt::Write, src: &str) -> fmt::Result {
-    // This implementation reads one byte after another.
-    // It's not very fast, but should work well enough until portable SIMD gets stabilized.
+    // This implementation looks at a whole chunk of bytes at once, so long runs of safe bytes
+    // are skipped quickly, and finds the bytes that need escaping in a chunk with bit operations.
+    // The last bytes that don't fill a whole chunk are read one byte after another.
 
     let mut escaped_buf = ESCAPED_BUF_INIT;
     let mut last = 0;
+    let mut write_escaped = |index: usize, escaped: [u8; 2]| -> fmt::Result {
+        [escaped_buf[2], escaped_buf[3]] = escaped;
+        write_str_if_nonempty(&mut dest, &src[last..index])?;
+        // SAFETY: the content of `escaped_buf` is pure ASCII
+        dest.write_str(unsafe { str::from_utf8_unchecked(&escaped_buf[..ESCAPED_BUF_LEN]) })?;
+        last = index + 1;
+        Ok(())
+    };
 
-    for (index, byte) in src.bytes().enumerate() {
+    let bytes = src.as_bytes();
+    let mut start = 0;
+    while let Some(chunk) = bytes.get(start..start + CHUNK_LEN) {
+        let mut masks = [0; CHUNK_LEN / WORD_LEN];
+        for (mask, word) in masks.iter_mut().zip(chunk.chunks_exact(WORD_LEN)) {
+            // RATIONALE: llvm turns this into a single unaligned load
+            *mask = escape_mask(usize::from_le_bytes(word.try_into().unwrap()));
+        }
+        if masks.iter().any(|&mask| mask != 0) {
+            for (word_index, mut mask) in masks.into_iter().enumerate() {
+                while mask != 0 {
+                    let index = start + word_index * WORD_LEN + mask.trailing_zeros() as usize / 8;
+                    if let Some(escaped) = get_escaped(bytes[index]) {
+                        write_escaped(index, escaped)?;
+                    }
+                    mask &= mask - 1;
+                }
+            }
+        }
+        start += CHUNK_LEN;
+    }
+    for (offset, &byte) in bytes[start..].iter().enumerate() {
         if let Some(escaped) = get_escaped(byte) {
-            [escaped_buf[2], escaped_buf[3]] = escaped;
-            write_str_if_nonempty(&mut dest, &src[last..index])?;
-            // SAFETY: the content of `escaped_buf` is pure ASCII
-            dest.write_str(unsafe { str::from_utf8_unchecked(&escaped_buf[..ESCAPED_BUF_LEN]) })?;
-            last = index + 1;
+            write_escaped(start + offset, escaped)?;
         }
     }
     write_str_if_nonempty(&mut dest, &src[last..])
 }
 
+/// Returns a word with the high bit set in every byte of the little-endian `word`
+/// that needs HTML escaping.
+#[inline(always)]
+const fn escape_mask(word: usize) -> usize {
+    // `&` and `'`, and `<` and `>` only differ in a single bit, so they are tested together
+    zero_bytes(word ^ splat(b'"'))
+        | zero_bytes((word | splat(0x01)) ^ splat(b'\''))
+        | zero_bytes((word | splat(0x02)) ^ splat(b'>'))
+}
+
+/// Returns a word with the high bit set in every byte of `word` that is zero.
+#[inline(always)]
+const fn zero_bytes(word: usize) -> usize {
+    !(((word & splat(0x7f)) + splat(0x7f)) | word | splat(0x7f))
+}
+
+/// Returns a word where every byte is `byte`.
+#[inline(always)]
+const fn splat(byte: u8) -> usize {
+    usize::MAX / 0xff * byte as usize
+}
+
+const WORD_LEN: usize = core::mem::size_of::<usize>();
+
+/// Number of bytes that are tested at once: 16 bytes on 64 bit targets.
+const CHUNK_LEN: usize = 2 * WORD_LEN;
+
 #[allow(unused)]
 pub(crate) fn write_escaped_char(mut dest: impl fmt::Write, c: char) -> fmt::Result {
     if !c.is_ascii() {
@@ -117,6 +170,8 @@ const ESCAPED_BUF_LEN: usize = b"&#__;".len();
 
 #[test]
 fn test_simple_html_string_escaping() {
+    use alloc::string::String;
+
     let mut buf = String::new();
     write_escaped_str(&mut buf, "<script>").unwrap();
     assert_eq!(buf, "&#60;script&#62;");
@@ -129,3 +184,43 @@ fn test_simple_html_string_escaping() {
     write_escaped_str(&mut buf, "s<cripcripcripcripcripcripcripcripcripcrip>t").unwrap();
     assert_eq!(buf, "s&#60;cripcripcripcripcripcripcripcripcripcrip&#62;t");
 }
+
+#[test]
+fn test_html_string_escaping_in_chunks() {
+    use alloc::string::String;
+
+    // compare with escaping one byte after another at all positions around chunk boundaries
+    let mut expected = String::new();
+    let mut buf = String::new();
+    for len in 0..3 * CHUNK_LEN {
+        for pos in 0..len {
+            for &c in CHARS.iter().chain(b"a\0\x7f!#;=?") {
+                let mut src = alloc::vec![b'x'; len];
+                src[pos] = c;
+                if pos + 1 < len {
+                    src[len - 1] = b'>';
+                }
+                let src = str::from_utf8(&src).unwrap();
+
+                expected.clear();
+                for c in src.chars() {
+                    write_escaped_char(&mut expected, c).unwrap();
+                }
+                buf.clear();
+                write_escaped_str(&mut buf, src).unwrap();
+                assert_eq!(buf, expected, "{src:?}");
+            }
+        }
+    }
+
+    buf.clear();
+    write_escaped_str(
+        &mut buf,
+        "äöü <b>ß</b> 🦀 & 'quoted' \"text\" in a longer string",
+    )
+    .unwrap();
+    assert_eq!(
+        buf,
+        "äöü &#60;b&#62;ß&#60;/b&#62; 🦀 &#38; &#39;quoted&#39; &#34;text&#34; in a longer string",
+    );
+}
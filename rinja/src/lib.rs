@@ -19,7 +19,7 @@
 //!
 //! The main feature of Rinja is the [`Template`] derive macro
 //! which reads your template code, so your `struct` can implement
-//! the [`Template`] trait and [`Display`][std::fmt::Display], type-safe and fast:
+//! the [`Template`] trait and [`Display`][core::fmt::Display], type-safe and fast:
 //!
 //! ```rust
 //! # use rinja::Template;
@@ -55,24 +55,42 @@
 //! [Tera](https://github.com/Keats/tera).
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![no_std]
 #![deny(elided_lifetimes_in_paths)]
 #![deny(unreachable_pub)]
 #![deny(missing_docs)]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
 mod error;
 pub mod filters;
 #[doc(hidden)]
 pub mod helpers;
+#[cfg(feature = "hot-reload")]
+#[doc(hidden)]
+pub mod hot_reload;
 mod html;
+mod limits;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 
-use std::{fmt, io};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
 pub use rinja_derive::Template;
 
 #[doc(hidden)]
 pub use crate as shared;
-pub use crate::error::{Error, Result};
+pub use crate::error::{Error, Located, Location, Result};
 pub use crate::helpers::PrimitiveType;
+pub use crate::limits::{Limit, Limits};
 
 /// Main `Template` trait; implementations are generally derived
 ///
@@ -83,12 +101,15 @@ pub use crate::helpers::PrimitiveType;
 /// When rendering a rinja template, you should prefer the methods
 ///
 /// * [`.render()`][Template::render] (to render the content into a new string),
+/// * [`.render_bytes()`][Template::render_bytes] (to render the content into a new byte vector),
 /// * [`.render_into()`][Template::render_into] (to render the content into an [`fmt::Write`]
 ///   object, e.g. [`String`]) or
 /// * [`.write_into()`][Template::write_into] (to render the content into an [`io::Write`] object,
-///   e.g. [`Vec<u8>`])
+///   e.g. [`Vec<u8>`]) or
+/// * [`.render_chunks()`][Template::render_chunks] (to process the content in chunks while it is
+///   being rendered, e.g. to stream it into a socket)
 ///
-/// over [`.to_string()`][std::string::ToString::to_string] or [`format!()`].
+/// over [`.to_string()`][alloc::string::ToString::to_string] or [`format!()`][alloc::format].
 /// While `.to_string()` and `format!()` give you the same result, they generally perform much worse
 /// than rinja's own methods, because [`fmt::Write`] uses [dynamic methods calls] instead of
 /// monomorphised code. On average, expect `.to_string()` to be 100% to 200% slower than
@@ -107,43 +128,131 @@ pub trait Template: fmt::Display + filters::FastWritable {
     /// Renders the template to the given `writer` fmt buffer
     fn render_into<W: fmt::Write + ?Sized>(&self, writer: &mut W) -> Result<()>;
 
-    /// Renders the template to the given `writer` io buffer
-    fn write_into<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
-        struct Wrapped<W: io::Write> {
-            writer: W,
-            err: Option<io::Error>,
+    /// Like [`render()`][Template::render], but aborts with [`Error::LimitExceeded`] if the
+    /// rendering exceeds one of the [`Limits`]
+    ///
+    /// Use it to render templates with untrusted data, e.g. if a loop iterates over a range that
+    /// was supplied by a user. The output is discarded if a limit was exceeded.
+    ///
+    /// Without the feature `"std"`, only [`Limits::max_bytes`] is enforced.
+    fn render_with_limits(&self, limits: Limits) -> Result<String> {
+        let mut buf = limits::LimitedWriter::new(String::new(), limits.max_bytes);
+        let _ = buf
+            .writer
+            .try_reserve(Self::SIZE_HINT.min(limits.max_bytes));
+        let result =
+            limits::with_loop_budget(limits.max_loop_iterations, || self.render_into(&mut buf));
+        match result {
+            None => Err(Error::LimitExceeded(Limit::LoopIterations)),
+            Some(_) if buf.exceeded => Err(Error::LimitExceeded(Limit::Bytes)),
+            Some(result) => result.map(|()| buf.writer),
         }
+    }
 
-        impl<W: io::Write> fmt::Write for Wrapped<W> {
-            fn write_str(&mut self, s: &str) -> fmt::Result {
-                if let Err(err) = self.writer.write_all(s.as_bytes()) {
-                    self.err = Some(err);
-                    Err(fmt::Error)
-                } else {
-                    Ok(())
-                }
-            }
+    /// Helper method which allocates a new `Vec<u8>` and renders into it
+    ///
+    /// Use it if you need the rendered output as bytes, e.g. to write it into a file or to send
+    /// it over the network. The output is valid UTF-8.
+    ///
+    /// There is no separate byte-level code path: the template is rendered with
+    /// [`render_into()`][Template::render_into] like for [`render()`][Template::render], but the
+    /// output is appended to the `Vec<u8>` directly, without an intermediate `String`.
+    fn render_bytes(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        let _ = buf.try_reserve(Self::SIZE_HINT);
+        self.render_into(&mut ByteWriter(&mut buf))?;
+        Ok(buf)
+    }
+
+    /// Renders the template to the given `writer` io buffer
+    ///
+    /// The output is collected as bytes and handed to `writer` in chunks of at least
+    /// [`CHUNK_SIZE`] bytes (except for the last one), so you don't need to wrap unbuffered
+    /// writers like [`File`][std::fs::File] in a [`BufWriter`][std::io::BufWriter]. Like
+    /// [`render_bytes()`][Template::render_bytes], it uses the [`fmt::Write`] code path of the
+    /// template.
+    #[cfg(feature = "std")]
+    fn write_into<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buffered = IoBuffer {
+            buf: Vec::new(),
+            writer,
+            err: None,
+        };
+        let _ = buffered
+            .buf
+            .try_reserve(Self::SIZE_HINT.min(2 * CHUNK_SIZE));
+        let result = self
+            .render_into(&mut buffered)
+            .and_then(|()| Ok(buffered.flush()?));
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) => Err(buffered.err.take().unwrap_or_else(|| err.into_io_error())),
         }
+    }
 
-        let mut wrapped = Wrapped { writer, err: None };
-        if self.render_into(&mut wrapped).is_ok() {
-            Ok(())
-        } else {
-            let err = wrapped.err.take();
-            Err(err.unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, fmt::Error)))
+    /// Renders the template in chunks, calling `f` for each chunk
+    ///
+    /// The output is collected until it amounts to at least [`CHUNK_SIZE`] bytes, or until the
+    /// rendering is done. The chunks do not align with the constructs of the template, e.g. the
+    /// output of a single expression may be spread over two chunks, but a chunk never ends in the
+    /// middle of a UTF-8 character.
+    /// If `f` returns an error, rendering is aborted and the error is returned.
+    ///
+    /// ```
+    /// # use rinja::Template;
+    /// #[derive(Template)]
+    /// #[template(ext = "txt", source = "{% for i in 0..3 %}{{ i }} {% endfor %}")]
+    /// struct Counter;
+    ///
+    /// let mut chunks = vec![];
+    /// Counter
+    ///     .render_chunks(|chunk| {
+    ///         chunks.push(chunk.to_owned());
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(chunks.concat(), "0 1 2 ");
+    /// ```
+    fn render_chunks<F>(&self, f: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<()>,
+    {
+        let mut chunked = Chunked {
+            buf: String::new(),
+            f,
+            err: None,
+        };
+        let result = self
+            .render_into(&mut chunked)
+            .and_then(|()| Ok(chunked.flush()?));
+        match chunked.err {
+            Some(err) => Err(err),
+            None => result,
         }
     }
 
     /// Provides a rough estimate of the expanded length of the rendered template. Larger
     /// values result in higher memory usage but fewer reallocations. Smaller values result in the
-    /// opposite. This value only affects [`render`]. It does not take effect when calling
-    /// [`render_into`], [`write_into`], the [`fmt::Display`] implementation, or the blanket
-    /// [`ToString::to_string`] implementation.
+    /// opposite. This value only affects [`render`] and [`render_bytes`]. It does not take effect
+    /// when calling [`render_into`], [`write_into`], the [`fmt::Display`] implementation, or the
+    /// blanket [`ToString::to_string`] implementation.
     ///
     /// [`render`]: Template::render
+    /// [`render_bytes`]: Template::render_bytes
     /// [`render_into`]: Template::render_into
     /// [`write_into`]: Template::write_into
     const SIZE_HINT: usize;
+
+    /// The extension of the template's output format, e.g. `Some("html")` for a template
+    /// `page.html` or `page.html.j2`, or the value of the `escape` argument if it was given
+    /// (other than `"none"`). `None` if the template's path has no extension.
+    const EXTENSION: Option<&'static str> = None;
+
+    /// The MIME type of the rendered output, guessed from the template's [extension], e.g.
+    /// `"text/html; charset=utf-8"`.
+    ///
+    /// [extension]: Template::EXTENSION
+    const MIME_TYPE: &'static str = "application/octet-stream";
 }
 
 impl<T: Template + ?Sized> Template for &T {
@@ -158,11 +267,32 @@ impl<T: Template + ?Sized> Template for &T {
     }
 
     #[inline]
+    fn render_with_limits(&self, limits: Limits) -> Result<String> {
+        <T as Template>::render_with_limits(self, limits)
+    }
+
+    #[inline]
+    fn render_bytes(&self) -> Result<Vec<u8>> {
+        <T as Template>::render_bytes(self)
+    }
+
+    #[inline]
+    #[cfg(feature = "std")]
     fn write_into<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         <T as Template>::write_into(self, writer)
     }
 
+    #[inline]
+    fn render_chunks<F>(&self, f: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<()>,
+    {
+        <T as Template>::render_chunks(self, f)
+    }
+
     const SIZE_HINT: usize = T::SIZE_HINT;
+    const EXTENSION: Option<&'static str> = T::EXTENSION;
+    const MIME_TYPE: &'static str = T::MIME_TYPE;
 }
 
 /// Object-safe wrapper trait around [`Template`] implementers
@@ -175,11 +305,27 @@ pub trait DynTemplate {
     /// Renders the template to the given `writer` fmt buffer
     fn dyn_render_into(&self, writer: &mut dyn fmt::Write) -> Result<()>;
 
+    /// Helper method which allocates a new `Vec<u8>` and renders into it
+    fn dyn_render_bytes(&self) -> Result<Vec<u8>>;
+
+    /// Renders the template unless it exceeds the `limits`, see [`Template::render_with_limits`]
+    fn dyn_render_with_limits(&self, limits: Limits) -> Result<String>;
+
     /// Renders the template to the given `writer` io buffer
+    #[cfg(feature = "std")]
     fn dyn_write_into(&self, writer: &mut dyn io::Write) -> io::Result<()>;
 
+    /// Renders the template in chunks, calling `f` for each chunk, see [`Template::render_chunks`]
+    fn dyn_render_chunks(&self, f: &mut dyn FnMut(&str) -> Result<()>) -> Result<()>;
+
     /// Provides a conservative estimate of the expanded length of the rendered template
     fn size_hint(&self) -> usize;
+
+    /// The extension of the template's output format, see [`Template::EXTENSION`]
+    fn extension(&self) -> Option<&'static str>;
+
+    /// The MIME type of the rendered output, see [`Template::MIME_TYPE`]
+    fn mime_type(&self) -> &'static str;
 }
 
 impl<T: Template> DynTemplate for T {
@@ -191,14 +337,149 @@ impl<T: Template> DynTemplate for T {
         <Self as Template>::render_into(self, writer)
     }
 
+    fn dyn_render_bytes(&self) -> Result<Vec<u8>> {
+        <Self as Template>::render_bytes(self)
+    }
+
+    #[inline]
+    fn dyn_render_with_limits(&self, limits: Limits) -> Result<String> {
+        <Self as Template>::render_with_limits(self, limits)
+    }
+
     #[inline]
+    #[cfg(feature = "std")]
     fn dyn_write_into(&self, writer: &mut dyn io::Write) -> io::Result<()> {
         <Self as Template>::write_into(self, writer)
     }
 
+    #[inline]
+    fn dyn_render_chunks(&self, f: &mut dyn FnMut(&str) -> Result<()>) -> Result<()> {
+        <Self as Template>::render_chunks(self, f)
+    }
+
     fn size_hint(&self) -> usize {
         Self::SIZE_HINT
     }
+
+    #[inline]
+    fn extension(&self) -> Option<&'static str> {
+        Self::EXTENSION
+    }
+
+    #[inline]
+    fn mime_type(&self) -> &'static str {
+        Self::MIME_TYPE
+    }
+}
+
+/// The minimum size of a chunk emitted by [`Template::render_chunks()`], except for the last one
+pub const CHUNK_SIZE: usize = 8 * 1024;
+
+/// An [`fmt::Write`] buffer that hands its content to `f` whenever it reaches [`CHUNK_SIZE`]
+struct Chunked<F> {
+    buf: String,
+    f: F,
+    err: Option<Error>,
+}
+
+impl<F: FnMut(&str) -> Result<()>> Chunked<F> {
+    fn flush(&mut self) -> fmt::Result {
+        if !self.buf.is_empty() {
+            let result = (self.f)(&self.buf);
+            self.buf.clear();
+            self.emitted(result)?;
+        }
+        Ok(())
+    }
+
+    fn emitted(&mut self, result: Result<()>) -> fmt::Result {
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                self.err = Some(err);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+impl<F: FnMut(&str) -> Result<()>> fmt::Write for Chunked<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.buf.is_empty() && s.len() >= CHUNK_SIZE {
+            // no need to copy the data if it is big enough to be a chunk on its own
+            let result = (self.f)(s);
+            return self.emitted(result);
+        }
+        self.buf.push_str(s);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// An [`fmt::Write`] that appends the output to a [`Vec<u8>`]
+struct ByteWriter<'a>(&'a mut Vec<u8>);
+
+impl fmt::Write for ByteWriter<'_> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        match c.is_ascii() {
+            true => self.0.push(c as u8),
+            false => self
+                .0
+                .extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+        Ok(())
+    }
+}
+
+/// An [`fmt::Write`] buffer that hands its content to an [`io::Write`] whenever it reaches
+/// [`CHUNK_SIZE`]
+#[cfg(feature = "std")]
+struct IoBuffer<W> {
+    buf: Vec<u8>,
+    writer: W,
+    err: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> IoBuffer<W> {
+    fn flush(&mut self) -> fmt::Result {
+        let result = self.writer.write_all(&self.buf);
+        self.buf.clear();
+        result.map_err(|err| {
+            self.err = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> fmt::Write for IoBuffer<W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        ByteWriter(&mut self.buf).write_str(s)?;
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        ByteWriter(&mut self.buf).write_char(c)?;
+        if self.buf.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for dyn DynTemplate {
@@ -207,6 +488,28 @@ impl fmt::Display for dyn DynTemplate {
     }
 }
 
+impl filters::FastWritable for dyn DynTemplate {
+    #[inline]
+    fn write_into<W: fmt::Write + ?Sized>(&self, dest: &mut W) -> Result<()> {
+        self.dyn_render_into(&mut DynWriter(dest))
+    }
+}
+
+/// Turns an unsized [`fmt::Write`] into a sized one, so it can be used as `dyn fmt::Write`
+struct DynWriter<'a, W: ?Sized>(&'a mut W);
+
+impl<W: fmt::Write + ?Sized> fmt::Write for DynWriter<'_, W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.0.write_char(c)
+    }
+}
+
 /// Implement the trait `$Trait` for a list of reference (wrapper) types to `$T: $Trait + ?Sized`
 macro_rules! impl_for_ref {
     (impl $Trait:ident for $T:ident $body:tt) => {
@@ -214,11 +517,16 @@ macro_rules! impl_for_ref {
             impl<$T> $Trait for [
                 &T
                 &mut T
-                Box<T>
-                std::cell::Ref<'_, T>
-                std::cell::RefMut<'_, T>
-                std::rc::Rc<T>
-                std::sync::Arc<T>
+                alloc::boxed::Box<T>
+                core::cell::Ref<'_, T>
+                core::cell::RefMut<'_, T>
+                alloc::rc::Rc<T>
+                alloc::sync::Arc<T>
+            ] $body
+        }
+        #[cfg(feature = "std")]
+        crate::impl_for_ref! {
+            impl<$T> $Trait for [
                 std::sync::MutexGuard<'_, T>
                 std::sync::RwLockReadGuard<'_, T>
                 std::sync::RwLockWriteGuard<'_, T>
@@ -234,7 +542,10 @@ pub(crate) use impl_for_ref;
 
 #[cfg(test)]
 mod tests {
-    use std::fmt;
+    use alloc::borrow::ToOwned;
+    use alloc::string::ToString;
+    use alloc::{format, vec};
+    use core::fmt;
 
     use super::*;
     use crate::{DynTemplate, Template};
@@ -277,8 +588,22 @@ mod tests {
 
         assert_eq!(format!("{test}"), "test");
 
-        let mut vec = Vec::new();
-        test.dyn_write_into(&mut vec).unwrap();
-        assert_eq!(vec, vec![b't', b'e', b's', b't']);
+        #[cfg(feature = "std")]
+        {
+            let mut vec = std::vec::Vec::new();
+            test.dyn_write_into(&mut vec).unwrap();
+            assert_eq!(vec, vec![b't', b'e', b's', b't']);
+        }
+
+        let mut chunks = vec![];
+        test.dyn_render_chunks(&mut |chunk| {
+            chunks.push(chunk.to_owned());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(chunks, ["test"]);
+
+        assert_eq!(test.extension(), None);
+        assert_eq!(test.mime_type(), "application/octet-stream");
     }
 }
@@ -0,0 +1,185 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::cell::Cell;
+
+/// Budgets for [`Template::render_with_limits()`][crate::Template::render_with_limits]
+///
+/// The [`Default`] is unlimited.
+///
+/// ```
+/// # use rinja::{Error, Limit, Limits, Template};
+/// #[derive(Template)]
+/// #[template(ext = "txt", source = "{% for i in 0..n %}{{ i }}{% endfor %}")]
+/// struct Counter {
+///     n: usize,
+/// }
+///
+/// let limits = Limits {
+///     max_bytes: 1024,
+///     max_loop_iterations: 100,
+/// };
+/// assert_eq!(Counter { n: 10 }.render_with_limits(limits).unwrap(), "0123456789");
+/// assert!(matches!(
+///     Counter { n: 1000 }.render_with_limits(limits),
+///     Err(Error::LimitExceeded(Limit::LoopIterations)),
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum length of the rendered output in bytes
+    pub max_bytes: usize,
+    /// The maximum number of `{% for %}` iterations, summed up over all loops of the template,
+    /// including the loops of nested templates
+    ///
+    /// The budget is stored in a thread-local, so it is only enforced with the feature `"std"`.
+    pub max_loop_iterations: usize,
+}
+
+impl Default for Limits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_bytes: usize::MAX,
+            max_loop_iterations: usize::MAX,
+        }
+    }
+}
+
+/// The budget of [`Limits`] that was exceeded, stored in [`Error::LimitExceeded`][crate::Error]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// [`Limits::max_bytes`]
+    Bytes,
+    /// [`Limits::max_loop_iterations`]
+    LoopIterations,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Bytes => "the rendered output exceeds the byte limit",
+            Limit::LoopIterations => "the template exceeds the loop iteration limit",
+        })
+    }
+}
+
+impl crate::error::StdError for Limit {}
+
+/// A [`fmt::Write`] that fails instead of writing more than `max_bytes`
+pub(crate) struct LimitedWriter<W> {
+    pub(crate) writer: W,
+    remaining: usize,
+    pub(crate) exceeded: bool,
+}
+
+impl<W: fmt::Write> LimitedWriter<W> {
+    pub(crate) fn new(writer: W, max_bytes: usize) -> Self {
+        Self {
+            writer,
+            remaining: max_bytes,
+            exceeded: false,
+        }
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for LimitedWriter<W> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.remaining.checked_sub(s.len()) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                self.writer.write_str(s)
+            }
+            None => {
+                self.exceeded = true;
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopBudget {
+    Unlimited,
+    Remaining(usize),
+    Exceeded,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The loop budget of the innermost running `render_with_limits()` call on this thread
+    static LOOP_BUDGET: Cell<LoopBudget> = const { Cell::new(LoopBudget::Unlimited) };
+}
+
+/// The number of running `render_with_limits()` calls on all threads
+///
+/// As long as it is zero, loops don't need to look at their thread's [`LOOP_BUDGET`].
+#[cfg(feature = "std")]
+static ACTIVE_BUDGETS: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs `f` with a budget of `max_loop_iterations`, returns `None` if the budget was exceeded
+#[cfg(feature = "std")]
+pub(crate) fn with_loop_budget<T>(max_loop_iterations: usize, f: impl FnOnce() -> T) -> Option<T> {
+    struct Restore(LoopBudget);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LOOP_BUDGET.set(self.0);
+            ACTIVE_BUDGETS.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    ACTIVE_BUDGETS.fetch_add(1, Ordering::Relaxed);
+    let _restore = Restore(LOOP_BUDGET.replace(LoopBudget::Remaining(max_loop_iterations)));
+    let result = f();
+    match LOOP_BUDGET.get() {
+        LoopBudget::Exceeded => None,
+        _ => Some(result),
+    }
+}
+
+/// Returns `true` if loops that start now must call [`take_loop_iteration()`]
+///
+/// A thread only sees its own increments of [`ACTIVE_BUDGETS`] in order, but that is all that
+/// matters, because the budget itself is thread-local.
+#[inline]
+pub(crate) fn loop_budget_active() -> bool {
+    #[cfg(feature = "std")]
+    {
+        ACTIVE_BUDGETS.load(Ordering::Relaxed) != 0 && LOOP_BUDGET.get() != LoopBudget::Unlimited
+    }
+    #[cfg(not(feature = "std"))]
+    false
+}
+
+/// Without the feature `"std"` there is no thread-local to store the budget in
+#[cfg(not(feature = "std"))]
+pub(crate) fn with_loop_budget<T>(_max_loop_iterations: usize, f: impl FnOnce() -> T) -> Option<T> {
+    Some(f())
+}
+
+/// Called for every loop iteration if [`loop_budget_active()`], returns `false` if the loop
+/// must stop
+#[inline]
+pub(crate) fn take_loop_iteration() -> bool {
+    #[cfg(feature = "std")]
+    {
+        LOOP_BUDGET.with(|budget| match budget.get() {
+            LoopBudget::Unlimited => true,
+            LoopBudget::Remaining(0) | LoopBudget::Exceeded => {
+                budget.set(LoopBudget::Exceeded);
+                false
+            }
+            LoopBudget::Remaining(remaining) => {
+                budget.set(LoopBudget::Remaining(remaining - 1));
+                true
+            }
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    true
+}
@@ -0,0 +1,98 @@
+//! Stream rendered templates into a [`tokio`](::tokio) [`AsyncWrite`] sink
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use std::io;
+use std::panic::resume_unwind;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
+
+use crate::{Error, Template};
+
+/// How many rendered chunks may wait to be written before the rendering is paused
+const QUEUED_CHUNKS: usize = 4;
+
+/// Renders `template` into the asynchronous writer `dest`
+///
+/// The template is rendered on the current task, and then the output is written into `dest`,
+/// which is flushed afterwards. The task is blocked while the template is rendered, and the whole
+/// output is held in memory. This is the right choice for most templates, because rendering is
+/// usually a lot faster than the I/O. For big templates have a look at [`stream_into()`].
+///
+/// ```
+/// # use rinja::Template;
+/// #[derive(Template)]
+/// #[template(ext = "txt", source = "Hello, {{ name }}!")]
+/// struct Hello<'a> {
+///     name: &'a str,
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut dest = Vec::new();
+/// rinja::tokio::write_into(&Hello { name: "world" }, &mut dest).await.unwrap();
+/// assert_eq!(dest, b"Hello, world!");
+/// # });
+/// ```
+pub async fn write_into<T, W>(template: &T, dest: &mut W) -> io::Result<()>
+where
+    T: Template + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let output = template.render().map_err(Error::into_io_error)?;
+    dest.write_all(output.as_bytes()).await?;
+    dest.flush().await
+}
+
+/// Renders `template` into the asynchronous writer `dest`, while it is being rendered
+///
+/// The template is rendered with [`Template::render_chunks()`] in a blocking task, while the
+/// chunks are written into `dest` as soon as they are available. So the first bytes of a big
+/// template can be sent before the rendering is done, without ever holding the whole output
+/// in memory.
+///
+/// This has a cost: the template is moved into a thread of tokio's blocking thread pool
+/// with [`spawn_blocking()`], which is why it must be `Send + 'static`, and every chunk is
+/// copied and sent through a channel. For small templates [`write_into()`] is faster.
+///
+/// If writing into `dest` fails, the rendering is aborted. `dest` is flushed after the last chunk.
+///
+/// ```
+/// # use rinja::Template;
+/// #[derive(Template)]
+/// #[template(ext = "txt", source = "Hello, {{ name }}!")]
+/// struct Hello {
+///     name: String,
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut dest = Vec::new();
+/// let hello = Hello { name: "world".into() };
+/// rinja::tokio::stream_into(hello, &mut dest).await.unwrap();
+/// assert_eq!(dest, b"Hello, world!");
+/// # });
+/// ```
+pub async fn stream_into<T, W>(template: T, dest: &mut W) -> io::Result<()>
+where
+    T: Template + Send + 'static,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let (tx, mut rx) = mpsc::channel::<String>(QUEUED_CHUNKS);
+    let render = spawn_blocking(move || {
+        template.render_chunks(|chunk| {
+            // the receiver is only dropped if writing into `dest` failed
+            tx.blocking_send(chunk.to_owned()).map_err(|_| Error::Fmt)
+        })
+    });
+
+    while let Some(chunk) = rx.recv().await {
+        dest.write_all(chunk.as_bytes()).await?;
+    }
+    match render.await {
+        Ok(result) => result.map_err(Error::into_io_error)?,
+        Err(err) if err.is_panic() => resume_unwind(err.into_panic()),
+        Err(err) => return Err(io::Error::other(err)),
+    }
+    dest.flush().await
+}
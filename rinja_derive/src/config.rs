@@ -20,7 +20,11 @@ pub(crate) struct Config {
     pub(crate) syntaxes: BTreeMap<String, SyntaxAndCache<'static>>,
     pub(crate) default_syntax: &'static str,
     pub(crate) escapers: Vec<(Vec<Cow<'static, str>>, Cow<'static, str>)>,
+    pub(crate) dir_escapers: Vec<(Vec<PathBuf>, Cow<'static, str>)>,
     pub(crate) whitespace: Whitespace,
+    pub(crate) context_aware_escaping: bool,
+    pub(crate) sanitize_tags: Option<Vec<Cow<'static, str>>>,
+    pub(crate) sanitize_attributes: Option<Vec<Cow<'static, str>>>,
     // `Config` is self referential and `_key` owns it data, so it must come last
     _key: OwnedConfigKey,
 }
@@ -110,19 +114,26 @@ impl Config {
             RawConfig::from_toml_str(s)?
         };
 
-        let (dirs, default_syntax, whitespace) = match raw.general {
+        let (dirs, default_syntax, whitespace, context_aware_escaping) = match raw.general {
             Some(General {
                 dirs,
                 default_syntax,
                 whitespace,
+                context_aware_escaping,
             }) => (
                 dirs.map_or(default_dirs, |v| {
                     v.into_iter().map(|dir| root.join(dir)).collect()
                 }),
                 default_syntax.unwrap_or(DEFAULT_SYNTAX_NAME),
                 whitespace,
+                context_aware_escaping,
+            ),
+            None => (
+                default_dirs,
+                DEFAULT_SYNTAX_NAME,
+                Whitespace::default(),
+                false,
             ),
-            None => (default_dirs, DEFAULT_SYNTAX_NAME, Whitespace::default()),
         };
         let file_info = config_path.map(|path| FileInfo::new(Path::new(path), None, None));
         let whitespace = key.0.template_whitespace.unwrap_or(whitespace);
@@ -154,9 +165,25 @@ impl Config {
         }
 
         let mut escapers = Vec::new();
+        let mut dir_escapers = Vec::new();
         if let Some(configured) = raw.escaper {
             for escaper in configured {
-                escapers.push((str_set(&escaper.extensions), escaper.path.into()));
+                if escaper.extensions.is_empty() && escaper.dirs.is_empty() {
+                    return Err(CompileError::new(
+                        format_args!(
+                            "escaper {:?} needs a list of `extensions` or `dirs`",
+                            escaper.path,
+                        ),
+                        file_info,
+                    ));
+                }
+                if !escaper.extensions.is_empty() {
+                    escapers.push((str_set(&escaper.extensions), escaper.path.into()));
+                }
+                if !escaper.dirs.is_empty() {
+                    let dirs = escaper.dirs.iter().map(PathBuf::from).collect();
+                    dir_escapers.push((dirs, escaper.path.into()));
+                }
             }
         }
         for (extensions, name) in DEFAULT_ESCAPERS {
@@ -166,16 +193,39 @@ impl Config {
             ));
         }
 
+        let (sanitize_tags, sanitize_attributes) = match raw.sanitize {
+            Some(RawSanitize { tags, attributes }) => (
+                tags.as_deref().map(lowercase_set),
+                attributes.as_deref().map(lowercase_set),
+            ),
+            None => (None, None),
+        };
+
         Ok(Config {
             dirs,
             syntaxes,
             default_syntax,
             escapers,
+            dir_escapers,
             whitespace,
+            context_aware_escaping,
+            sanitize_tags,
+            sanitize_attributes,
             _key: key,
         })
     }
 
+    /// Returns the escaper that is configured for a directory containing the template `path`
+    pub(crate) fn dir_escaper(&self, path: &Path) -> Option<&str> {
+        self.dir_escapers.iter().find_map(|(dirs, escaper)| {
+            self.dirs
+                .iter()
+                .filter_map(|root| path.strip_prefix(root).ok())
+                .any(|path| dirs.iter().any(|dir| path.starts_with(dir)))
+                .then_some(escaper.as_ref())
+        })
+    }
+
     pub(crate) fn find_template(
         &self,
         path: &str,
@@ -288,6 +338,7 @@ struct RawConfig<'a> {
     general: Option<General<'a>>,
     syntax: Option<Vec<SyntaxBuilder<'a>>>,
     escaper: Option<Vec<RawEscaper<'a>>>,
+    sanitize: Option<RawSanitize<'a>>,
 }
 
 impl RawConfig<'_> {
@@ -314,12 +365,24 @@ struct General<'a> {
     default_syntax: Option<&'a str>,
     #[cfg_attr(feature = "config", serde(default))]
     whitespace: Whitespace,
+    #[cfg_attr(feature = "config", serde(default))]
+    context_aware_escaping: bool,
 }
 
 #[cfg_attr(feature = "config", derive(Deserialize))]
 struct RawEscaper<'a> {
     path: &'a str,
+    #[cfg_attr(feature = "config", serde(default))]
     extensions: Vec<&'a str>,
+    #[cfg_attr(feature = "config", serde(default))]
+    dirs: Vec<&'a str>,
+}
+
+#[cfg_attr(feature = "config", derive(Deserialize))]
+struct RawSanitize<'a> {
+    #[cfg_attr(feature = "config", serde(borrow))]
+    tags: Option<Vec<&'a str>>,
+    attributes: Option<Vec<&'a str>>,
 }
 
 pub(crate) fn read_config_file(
@@ -349,7 +412,7 @@ pub(crate) fn read_config_file(
     }
 }
 
-fn manifest_root() -> PathBuf {
+pub(crate) fn manifest_root() -> PathBuf {
     env::var_os("CARGO_MANIFEST_DIR").map_or_else(|| PathBuf::from("."), PathBuf::from)
 }
 
@@ -357,16 +420,29 @@ fn str_set(vals: &[&'static str]) -> Vec<Cow<'static, str>> {
     vals.iter().map(|s| Cow::Borrowed(*s)).collect()
 }
 
+fn lowercase_set(vals: &[&'static str]) -> Vec<Cow<'static, str>> {
+    vals.iter()
+        .map(|s| match s.bytes().any(|b| b.is_ascii_uppercase()) {
+            true => Cow::Owned(s.to_ascii_lowercase()),
+            false => Cow::Borrowed(*s),
+        })
+        .collect()
+}
+
 static CONFIG_FILE_NAME: &str = "rinja.toml";
 static DEFAULT_SYNTAX_NAME: &str = "default";
 static DEFAULT_ESCAPERS: &[(&[&str], &str)] = &[
+    (&["html", "htm", "j2", "jinja", "jinja2", "rinja"], "Html"),
+    (&["css"], "Css"),
+    (&["js", "mjs"], "Js"),
+    (&["md", "none", "txt", "yml", ""], "Text"),
+    (&["tex"], "Latex"),
+    (&["bash", "sh"], "Shell"),
+    (&["atom", "rss", "svg", "xhtml", "xml"], "Xml"),
     (
-        &[
-            "html", "htm", "j2", "jinja", "jinja2", "rinja", "svg", "xml",
-        ],
-        "Html",
+        &["1", "2", "3", "4", "5", "6", "7", "8", "9", "man", "roff"],
+        "Roff",
     ),
-    (&["md", "none", "txt", "yml", ""], "Text"),
 ];
 
 #[cfg(test)]
@@ -634,18 +710,138 @@ mod tests {
         assert_eq!(config.escapers, vec![
             (str_set(&["js"]), "::my_filters::Js".into()),
             (
-                str_set(&[
-                    "html", "htm", "j2", "jinja", "jinja2", "rinja", "svg", "xml"
-                ]),
+                str_set(&["html", "htm", "j2", "jinja", "jinja2", "rinja"]),
                 "rinja::filters::Html".into()
             ),
+            (str_set(&["css"]), "rinja::filters::Css".into()),
+            (str_set(&["js", "mjs"]), "rinja::filters::Js".into()),
             (
                 str_set(&["md", "none", "txt", "yml", ""]),
                 "rinja::filters::Text".into()
             ),
+            (str_set(&["tex"]), "rinja::filters::Latex".into()),
+            (str_set(&["bash", "sh"]), "rinja::filters::Shell".into()),
+            (
+                str_set(&["atom", "rss", "svg", "xhtml", "xml"]),
+                "rinja::filters::Xml".into()
+            ),
+            (
+                str_set(&["1", "2", "3", "4", "5", "6", "7", "8", "9", "man", "roff"]),
+                "rinja::filters::Roff".into()
+            ),
         ]);
     }
 
+    #[cfg(feature = "config")]
+    #[test]
+    fn escape_dirs() {
+        let config = Config::new(
+            r#"
+            [[escaper]]
+            path = "rinja::filters::Html"
+            dirs = ["email/", "export/html"]
+
+            [[escaper]]
+            path = "::my_filters::Csv"
+            extensions = ["csv"]
+            dirs = ["export"]
+        "#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.escapers[0], (
+            str_set(&["csv"]),
+            "::my_filters::Csv".into()
+        ));
+        assert_eq!(config.dir_escapers, vec![
+            (
+                vec![PathBuf::from("email"), PathBuf::from("export/html")],
+                "rinja::filters::Html".into()
+            ),
+            (vec![PathBuf::from("export")], "::my_filters::Csv".into()),
+        ]);
+
+        let root = config.dirs[0].clone();
+        let escaper = |path: &str| config.dir_escaper(&root.join(path));
+        assert_eq!(escaper("email/welcome.txt"), Some("rinja::filters::Html"));
+        assert_eq!(escaper("export/html/a.txt"), Some("rinja::filters::Html"));
+        assert_eq!(escaper("export/a.txt"), Some("::my_filters::Csv"));
+        assert_eq!(escaper("emails/a.txt"), None);
+        assert_eq!(escaper("a.txt"), None);
+        assert_eq!(config.dir_escaper(Path::new("email/a.txt")), None);
+
+        let err = Config::new(
+            "[[escaper]]\npath = \"::my_filters::Csv\"",
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.msg,
+            r#"escaper "::my_filters::Csv" needs a list of `extensions` or `dirs`"#,
+        );
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_context_aware_escaping_parsing() {
+        let config = Config::new("", None, None, None).unwrap();
+        assert!(!config.context_aware_escaping);
+
+        let config = Config::new(
+            r#"
+            [general]
+            context_aware_escaping = true
+            "#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(config.context_aware_escaping);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_sanitize_parsing() {
+        let config = Config::new("", None, None, None).unwrap();
+        assert_eq!(config.sanitize_tags, None);
+        assert_eq!(config.sanitize_attributes, None);
+
+        let config = Config::new(
+            r#"
+            [sanitize]
+            tags = ["b", "IMG"]
+            "#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.sanitize_tags, Some(vec!["b".into(), "img".into()]));
+        assert_eq!(config.sanitize_attributes, None);
+
+        let config = Config::new(
+            r#"
+            [sanitize]
+            tags = []
+            attributes = ["alt", "src"]
+            "#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.sanitize_tags, Some(vec![]));
+        assert_eq!(
+            config.sanitize_attributes,
+            Some(vec!["alt".into(), "src".into()])
+        );
+    }
+
     #[cfg(feature = "config")]
     #[test]
     fn test_whitespace_parsing() {
@@ -11,15 +11,19 @@ use parser::node::{
 };
 use parser::{
     CharLit, CharPrefix, Expr, Filter, FloatKind, IntKind, Node, Num, Span, StrLit, StrPrefix,
-    Target, WithSpan,
+    Target, WithSpan, strip_common,
 };
 use rustc_hash::FxBuildHasher;
 
+use crate::config::manifest_root;
 use crate::heritage::{Context, Heritage};
 use crate::html::write_escaped_str;
+use crate::html_context::{Branches, Escaping, HtmlContext};
 use crate::input::{Source, TemplateInput};
 use crate::integration::{Buffer, impl_everything, write_header};
-use crate::{BUILT_IN_FILTERS, CompileError, FileInfo, MsgValidEscapers};
+use crate::{
+    BUILT_IN_FILTERS, CompileError, FileInfo, MsgValidEscapers, generate_source_snippet,
+};
 
 pub(crate) fn template_to_string(
     buf: &mut Buffer,
@@ -89,6 +93,9 @@ impl<'a, 'h> Generator<'a, 'h> {
             super_block: None,
             buf_writable: WritableBuffer {
                 discard: buf_writable_discard,
+                html: (input.config.context_aware_escaping
+                    && input.escaper == "rinja::filters::Html")
+                    .then_some(HtmlContext::Text),
                 ..Default::default()
             },
             is_in_filter_block,
@@ -195,6 +202,10 @@ impl<'a, 'h> Generator<'a, 'h> {
             }
         }
 
+        if cfg!(feature = "hot-reload") {
+            self.write_hot_reload(buf);
+        }
+
         let size_hint = self.impl_template_inner(ctx, buf)?;
 
         buf.write(format_args!(
@@ -203,11 +214,91 @@ impl<'a, 'h> Generator<'a, 'h> {
             }}\
             const SIZE_HINT: rinja::helpers::core::primitive::usize = {size_hint}usize;",
         ));
+        match self.input.extension() {
+            Some(ext) => buf.write(format_args!(
+                "const EXTENSION: rinja::helpers::core::option::Option<&'static rinja::helpers::core::primitive::str> = \
+                    rinja::helpers::core::option::Option::Some({ext:?});",
+            )),
+            None => buf.write(
+                "const EXTENSION: rinja::helpers::core::option::Option<&'static rinja::helpers::core::primitive::str> = \
+                    rinja::helpers::core::option::Option::None;",
+            ),
+        }
+        buf.write(format_args!(
+            "const MIME_TYPE: &'static rinja::helpers::core::primitive::str = {:?};",
+            self.input.mime_type(),
+        ));
 
         buf.write('}');
         Ok(size_hint)
     }
 
+    // In debug builds, let `rinja::hot_reload` interpret the current content of the template file.
+    // If the template cannot be interpreted, the compiled code below is used.
+    fn write_hot_reload(&self, buf: &mut Buffer) {
+        let Source::Path(_) = self.input.source else {
+            return;
+        };
+        if self.input.block.is_some() || !matches!(self.input.ast.data, syn::Data::Struct(_)) {
+            return;
+        }
+        // The interpreter only knows a single escaper for the whole template.
+        if self.input.config.context_aware_escaping {
+            return;
+        }
+
+        let path = self.input.path.canonicalize();
+        let path = path.as_deref().unwrap_or(&self.input.path);
+        let Some(path) = path.to_str() else {
+            return;
+        };
+        let syntax = &**self.input.syntax;
+        let escaper = self.input.escaper;
+
+        buf.write(format_args!(
+            "\
+            #[cfg(debug_assertions)] {{\
+                use rinja::hot_reload::ReflectValue as _;\
+                if let rinja::helpers::core::option::Option::Some(result) = \
+                    rinja::hot_reload::render(\
+                        writer,\
+                        &rinja::hot_reload::Source {{\
+                            path: {path:?},\
+                            syntax: [{:?}, {:?}, {:?}, {:?}, {:?}, {:?}],\
+                            whitespace: rinja::hot_reload::Whitespace::{:?},\
+                            hash: const {{\
+                                rinja::hot_reload::hash(rinja::helpers::core::include_bytes!({path:?}))\
+                            }},\
+                        }},\
+                        &{escaper},\
+                        &|rinja_field: &rinja::helpers::core::primitive::str| match rinja_field {{",
+            syntax.block_start,
+            syntax.block_end,
+            syntax.expr_start,
+            syntax.expr_end,
+            syntax.comment_start,
+            syntax.comment_end,
+            self.input.config.whitespace,
+        ));
+        for field in &self.input.fields {
+            buf.write(format_args!(
+                "{:?} => (&&&&rinja::hot_reload::Reflect::new(&self.{field}, {escaper}))\
+                    .rinja_reflect(),",
+                field.strip_prefix("r#").unwrap_or(field),
+            ));
+        }
+        buf.write(
+            "\
+                            _ => rinja::helpers::core::option::Option::None,\
+                        },\
+                    )\
+                {\
+                    return result;\
+                }\
+            }",
+        );
+    }
+
     fn impl_template_inner(
         &mut self,
         ctx: &Context<'a>,
@@ -500,6 +591,7 @@ impl<'a, 'h> Generator<'a, 'h> {
             self.handle_ws(ws_before);
         }
 
+        let mut branches = None;
         let mut iter = conds.conds.iter().enumerate().peekable();
         while let Some((pos, cond_info)) = iter.next() {
             let cond = cond_info.cond;
@@ -508,6 +600,8 @@ impl<'a, 'h> Generator<'a, 'h> {
                 self.handle_ws(cond.ws);
                 flushed += self.write_buf_writable(ctx, buf)?;
             }
+            let branches = branches.get_or_insert_with(|| Branches::new(&self.buf_writable.html));
+            branches.enter(&mut self.buf_writable.html);
 
             self.push_locals(|this| {
                 let mut arm_size = 0;
@@ -573,6 +667,7 @@ impl<'a, 'h> Generator<'a, 'h> {
                     this.handle_ws(if_.ws);
                     flushed += this.write_buf_writable(ctx, buf)?;
                 }
+                branches.leave(&this.buf_writable.html);
                 Ok(0)
             })?;
         }
@@ -580,6 +675,13 @@ impl<'a, 'h> Generator<'a, 'h> {
         if conds.nb_conds > 0 {
             buf.write('}');
         }
+        if let Some(mut branches) = branches {
+            match conds.conds.last() {
+                Some(last) if last.cond.cond.is_none() || !last.generate_condition => {}
+                _ => branches.leave_empty(),
+            }
+            branches.finish(&mut self.buf_writable.html);
+        }
 
         if !has_else && !conds.conds.is_empty() {
             arm_sizes.push(0);
@@ -609,11 +711,14 @@ impl<'a, 'h> Generator<'a, 'h> {
         buf.write(format_args!("match &{expr_code} {{"));
 
         let mut arm_size = 0;
+        let mut branches = None;
         let mut iter = arms.iter().enumerate().peekable();
         while let Some((i, arm)) = iter.next() {
             if i == 0 {
                 self.handle_ws(arm.ws);
             }
+            let branches = branches.get_or_insert_with(|| Branches::new(&self.buf_writable.html));
+            branches.enter(&mut self.buf_writable.html);
 
             self.push_locals(|this| {
                 for (index, target) in arm.target.iter().enumerate() {
@@ -636,9 +741,13 @@ impl<'a, 'h> Generator<'a, 'h> {
                     arm_sizes.push(arm_size + this.write_buf_writable(ctx, buf)?);
                     buf.write('}');
                 }
+                branches.leave(&this.buf_writable.html);
                 Ok(0)
             })?;
         }
+        if let Some(branches) = branches {
+            branches.finish(&mut self.buf_writable.html);
+        }
 
         buf.write('}');
 
@@ -696,6 +805,10 @@ impl<'a, 'h> Generator<'a, 'h> {
                 })?;
             }
 
+            // The body may be rendered any number of times, so it must end in its start context.
+            let mut branches = Branches::new(&this.buf_writable.html);
+            branches.leave_empty();
+
             let size_hint1 = this.push_locals(|this| {
                 buf.write("for (");
                 this.visit_target(buf, true, true, &loop_block.var);
@@ -707,6 +820,7 @@ impl<'a, 'h> Generator<'a, 'h> {
                 let mut size_hint1 = this.handle(ctx, &loop_block.body, buf, AstLevel::Nested)?;
                 this.handle_ws(loop_block.ws2);
                 size_hint1 += this.write_buf_writable(ctx, buf)?;
+                branches.leave(&this.buf_writable.html);
                 Ok(size_hint1)
             })?;
             buf.write('}');
@@ -714,6 +828,7 @@ impl<'a, 'h> Generator<'a, 'h> {
             let size_hint2;
             if has_else_nodes {
                 buf.write("if !_did_loop {");
+                branches.enter(&mut this.buf_writable.html);
                 size_hint2 = this.push_locals(|this| {
                     let mut size_hint =
                         this.handle(ctx, &loop_block.else_nodes, buf, AstLevel::Nested)?;
@@ -722,7 +837,10 @@ impl<'a, 'h> Generator<'a, 'h> {
                     Ok(size_hint)
                 })?;
                 buf.write('}');
+                branches.leave(&this.buf_writable.html);
+                branches.finish(&mut this.buf_writable.html);
             } else {
+                branches.finish(&mut this.buf_writable.html);
                 this.handle_ws(loop_block.ws3);
                 size_hint2 = this.write_buf_writable(ctx, buf)?;
             }
@@ -1221,8 +1339,26 @@ impl<'a, 'h> Generator<'a, 'h> {
             std::slice::from_ref(s)
         };
         for s in items {
+            let escaping = match self.buf_writable.escaping() {
+                // JSON is a JavaScript expression, and `|json` escapes `<`, `>`, `&` and `'`.
+                Escaping::JsCode(escaper) => match **s {
+                    Expr::Filter(Filter {
+                        name: "json" | "tojson",
+                        ..
+                    }) => Escaping::With(escaper),
+                    _ => Escaping::Unsafe("in JavaScript code, outside of a string literal"),
+                },
+                escaping => escaping,
+            };
+            let writable = match escaping {
+                Escaping::Default => compile_time_escape(s, self.input.escaper),
+                _ => None,
+            };
             self.buf_writable
-                .push(compile_time_escape(s, self.input.escaper).unwrap_or(Writable::Expr(s)));
+                .push(writable.unwrap_or(Writable::Expr(s, escaping)));
+            if let Some(html) = &mut self.buf_writable.html {
+                html.skip_expr();
+            }
         }
     }
 
@@ -1268,16 +1404,34 @@ impl<'a, 'h> Generator<'a, 'h> {
                         break;
                     }
                 }
-                Writable::Expr(s) => {
+                Writable::Expr(s, escaping) => {
                     size_hint += 3;
 
                     let mut expr_buf = Buffer::new();
-                    let expr = match self.visit_expr(ctx, &mut expr_buf, s)? {
-                        DisplayWrap::Wrapped => expr_buf.into_string(),
-                        DisplayWrap::Unwrapped => format!(
-                            "(&&rinja::filters::AutoEscaper::new(&({expr_buf}), {})).\
+                    let wrap = self.visit_expr(ctx, &mut expr_buf, s)?;
+                    let escaper = match (wrap, escaping) {
+                        (DisplayWrap::Wrapped, _) => None,
+                        (DisplayWrap::Unwrapped, Escaping::Default) => Some(self.input.escaper),
+                        (DisplayWrap::Unwrapped, Escaping::With(escaper)) => Some(*escaper),
+                        (DisplayWrap::Unwrapped, Escaping::JsCode(_)) => {
+                            unreachable!("`write_expr()` resolves `JsCode`")
+                        }
+                        (DisplayWrap::Unwrapped, Escaping::Unsafe(position)) => {
+                            return Err(ctx.generate_error(
+                                format_args!(
+                                    "the expression is written {position}, where it cannot be \
+                                    escaped safely. Use the `|safe` filter if its output is safe \
+                                    in this position"
+                                ),
+                                s.span(),
+                            ));
+                        }
+                    };
+                    let expr = match escaper {
+                        None => expr_buf.into_string(),
+                        Some(escaper) => format!(
+                            "(&&rinja::filters::AutoEscaper::new(&({expr_buf}), {escaper})).\
                                 rinja_auto_escape()?",
-                            self.input.escaper,
                         ),
                     };
                     let idx = if is_cacheable(s) {
@@ -1327,22 +1481,20 @@ impl<'a, 'h> Generator<'a, 'h> {
                     self.next_ws = Some(lws);
                 }
                 Whitespace::Preserve => {
-                    self.buf_writable.push(Writable::Lit(Cow::Borrowed(lws)));
+                    self.buf_writable.push_lit(lws);
                 }
                 Whitespace::Minimize => {
-                    self.buf_writable.push(Writable::Lit(Cow::Borrowed(
-                        match lws.contains('\n') {
-                            true => "\n",
-                            false => " ",
-                        },
-                    )));
+                    self.buf_writable.push_lit(match lws.contains('\n') {
+                        true => "\n",
+                        false => " ",
+                    });
                 }
             }
         }
 
         if !val.is_empty() {
             self.skip_ws = Whitespace::Preserve;
-            self.buf_writable.push(Writable::Lit(Cow::Borrowed(val)));
+            self.buf_writable.push_lit(val);
         }
 
         if !rws.is_empty() {
@@ -1394,7 +1546,7 @@ impl<'a, 'h> Generator<'a, 'h> {
             Expr::Group(ref inner) => self.visit_group(ctx, buf, inner)?,
             Expr::Call(ref obj, ref args) => self.visit_call(ctx, buf, obj, args)?,
             Expr::RustMacro(ref path, args) => self.visit_rust_macro(buf, path, args),
-            Expr::Try(ref expr) => self.visit_try(ctx, buf, expr)?,
+            Expr::Try(ref inner) => self.visit_try(ctx, buf, inner)?,
             Expr::Tuple(ref exprs) => self.visit_tuple(ctx, buf, exprs)?,
             Expr::NamedArgument(_, ref expr) => self.visit_named_argument(ctx, buf, expr)?,
             Expr::FilterSource => self.visit_filter_source(buf),
@@ -1476,7 +1628,7 @@ impl<'a, 'h> Generator<'a, 'h> {
             [] => unreachable!(),
             [expr] => self.visit_expr(ctx, buf, expr),
             exprs => {
-                let (l, r) = exprs.split_at((exprs.len() + 1) / 2);
+                let (l, r) = exprs.split_at(exprs.len().div_ceil(2));
                 buf.write("rinja::helpers::Concat(&(");
                 self.visit_concat(ctx, buf, l)?;
                 buf.write("), &(");
@@ -1495,10 +1647,26 @@ impl<'a, 'h> Generator<'a, 'h> {
     ) -> Result<DisplayWrap, CompileError> {
         buf.write("rinja::helpers::map_try(");
         self.visit_expr(ctx, buf, expr)?;
+        buf.write(", ");
+        self.visit_location(ctx, buf, expr_start(expr));
         buf.write(")?");
         Ok(DisplayWrap::Unwrapped)
     }
 
+    /// Writes a `&'static rinja::Location` of `node`, which is attached to runtime errors.
+    fn visit_location(&self, ctx: &Context<'_>, buf: &mut Buffer, node: Span<'_>) {
+        let source = ctx.parsed.source();
+        let node_source = node.as_suffix_of(source).unwrap_or_default();
+        let path = strip_common(&manifest_root(), ctx.path.unwrap_or(Path::new("")));
+        let before = &source[..source.len() - node_source.len()];
+        let row = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..].chars().count() + 1;
+        let snippet = generate_source_snippet(source, node_source, self.input.syntax);
+        buf.write(format_args!(
+            "const {{ &rinja::Location::new({path:?}, {row}, {column}, {snippet:?}) }}",
+        ));
+    }
+
     fn visit_rust_macro(&mut self, buf: &mut Buffer, path: &[&str], args: &str) -> DisplayWrap {
         self.visit_path(buf, path);
         buf.write("!(");
@@ -1516,18 +1684,43 @@ impl<'a, 'h> Generator<'a, 'h> {
         args: &[WithSpan<'_, Expr<'_>>],
         node: Span<'_>,
     ) -> Result<DisplayWrap, CompileError> {
+        if !FILTERS_WITH_NAMED_ARGUMENTS.contains(&name) {
+            if let Some(arg) = args
+                .iter()
+                .find(|arg| matches!(***arg, Expr::NamedArgument(..)))
+            {
+                return Err(ctx.generate_error(
+                    format_args!("the `{name}` filter does not accept named arguments"),
+                    arg.span(),
+                ));
+            }
+        }
+
         let filter = match name {
+            "batch" | "slice" => Self::_visit_batch_filter,
+            "currency" | "numberformat" | "percent" => Self::_visit_number_filter,
+            "date" | "datetime" => Self::_visit_date_filter,
             "deref" => Self::_visit_deref_filter,
             "escape" | "e" => Self::_visit_escape_filter,
+            "escapejs" => Self::_visit_escapejs_filter,
             "filesizeformat" => Self::_visit_humansize,
+            "first" | "last" | "length" | "max" | "min" | "reverse" | "sum" | "unique" => {
+                Self::_visit_collection_filter
+            }
             "fmt" => Self::_visit_fmt_filter,
             "format" => Self::_visit_format_filter,
+            "groupby" => Self::_visit_groupby_filter,
             "join" => Self::_visit_join_filter,
             "json" | "tojson" => Self::_visit_json_filter,
             "linebreaks" | "linebreaksbr" | "paragraphbreaks" => Self::_visit_linebreaks_filter,
+            "map" | "reject" | "select" => Self::_visit_map_filter,
             "pluralize" => Self::_visit_pluralize_filter,
             "ref" => Self::_visit_ref_filter,
             "safe" => Self::_visit_safe_filter,
+            "sanitize" => Self::_visit_sanitize_filter,
+            "shell_quote" => Self::_visit_shell_quote_filter,
+            "sort" => Self::_visit_sort_filter,
+            "timesince" | "timeuntil" => Self::_visit_timesince_filter,
             "uppercase" | "urlencode_strict" => Self::_visit_urlencode,
             name if BUILT_IN_FILTERS.contains(&name) => Self::_visit_builtin_filter,
             _ => Self::_visit_custom_filter,
@@ -1541,10 +1734,12 @@ impl<'a, 'h> Generator<'a, 'h> {
         buf: &mut Buffer,
         name: &str,
         args: &[WithSpan<'_, Expr<'_>>],
-        _node: Span<'_>,
+        node: Span<'_>,
     ) -> Result<DisplayWrap, CompileError> {
-        buf.write(format_args!("filters::{name}("));
+        buf.write(format_args!("rinja::helpers::map_filter(filters::{name}("));
         self._visit_args(ctx, buf, args)?;
+        buf.write("), ");
+        self.visit_location(ctx, buf, node);
         buf.write(")?");
         Ok(DisplayWrap::Unwrapped)
     }
@@ -1734,6 +1929,40 @@ impl<'a, 'h> Generator<'a, 'h> {
         Ok(DisplayWrap::Unwrapped)
     }
 
+    fn _visit_sanitize_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        _name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        if cfg!(not(feature = "sanitize")) {
+            return Err(ctx.generate_error(
+                "the `sanitize` filter requires the `sanitize` feature to be enabled",
+                node,
+            ));
+        }
+        if args.len() != 1 {
+            return Err(ctx.generate_error("unexpected argument(s) in `sanitize` filter", node));
+        }
+
+        buf.write("rinja::filters::sanitize(");
+        self._visit_args(ctx, buf, args)?;
+        buf.write(", &rinja::filters::Allowlist { tags: ");
+        match &self.input.config.sanitize_tags {
+            Some(tags) => buf.write(format_args!("&{tags:?}")),
+            None => buf.write("rinja::filters::Allowlist::DEFAULT.tags"),
+        }
+        buf.write(", attributes: ");
+        match &self.input.config.sanitize_attributes {
+            Some(attributes) => buf.write(format_args!("&{attributes:?}")),
+            None => buf.write("rinja::filters::Allowlist::DEFAULT.attributes"),
+        }
+        buf.write(" })?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
     fn _visit_safe_filter(
         &mut self,
         ctx: &Context<'_>,
@@ -1751,6 +1980,27 @@ impl<'a, 'h> Generator<'a, 'h> {
         Ok(DisplayWrap::Wrapped)
     }
 
+    fn _visit_shell_quote_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        _name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        if args.len() != 1 {
+            return Err(ctx.generate_error("unexpected argument(s) in `shell_quote` filter", node));
+        }
+        buf.write("rinja::filters::shell_quote(");
+        self._visit_args(ctx, buf, args)?;
+        buf.write(")?");
+        // The output is escaped by the template's escaper, unless it is quoted already.
+        Ok(match self.input.escaper {
+            "rinja::filters::Shell" => DisplayWrap::Wrapped,
+            _ => DisplayWrap::Unwrapped,
+        })
+    }
+
     fn _visit_escape_filter(
         &mut self,
         ctx: &Context<'_>,
@@ -1812,6 +2062,23 @@ impl<'a, 'h> Generator<'a, 'h> {
         Ok(DisplayWrap::Wrapped)
     }
 
+    fn _visit_escapejs_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        _name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        if args.len() != 1 {
+            return Err(ctx.generate_error("unexpected argument(s) in `escapejs` filter", node));
+        }
+        buf.write("rinja::filters::escape(");
+        self._visit_args(ctx, buf, args)?;
+        buf.write(", rinja::filters::Js)?");
+        Ok(DisplayWrap::Wrapped)
+    }
+
     fn _visit_format_filter(
         &mut self,
         ctx: &Context<'_>,
@@ -1822,7 +2089,7 @@ impl<'a, 'h> Generator<'a, 'h> {
     ) -> Result<DisplayWrap, CompileError> {
         if !args.is_empty() {
             if let Expr::StrLit(ref fmt) = *args[0] {
-                buf.write("rinja::helpers::std::format!(");
+                buf.write("rinja::helpers::alloc::format!(");
                 self.visit_str_lit(buf, fmt);
                 if args.len() > 1 {
                     buf.write(',');
@@ -1845,7 +2112,7 @@ impl<'a, 'h> Generator<'a, 'h> {
     ) -> Result<DisplayWrap, CompileError> {
         if let [_, arg2] = args {
             if let Expr::StrLit(ref fmt) = **arg2 {
-                buf.write("rinja::helpers::std::format!(");
+                buf.write("rinja::helpers::alloc::format!(");
                 self.visit_str_lit(buf, fmt);
                 buf.write(',');
                 self._visit_args(ctx, buf, &args[..1])?;
@@ -1856,6 +2123,185 @@ impl<'a, 'h> Generator<'a, 'h> {
         Err(ctx.generate_error(r#"use filter fmt like `value|fmt("{:?}")`"#, node))
     }
 
+    fn _visit_date_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        if cfg!(not(any(feature = "chrono", feature = "time"))) {
+            return Err(ctx.generate_error(
+                format_args!(
+                    "the `{name}` filter requires the `chrono` or `time` feature to be enabled"
+                ),
+                node,
+            ));
+        }
+        let (value, format) = match args {
+            [value] => (value, None),
+            [value, format] => (value, Some(format)),
+            _ => {
+                return Err(ctx.generate_error(
+                    format_args!("unexpected argument(s) in `{name}` filter"),
+                    node,
+                ));
+            }
+        };
+        // A literal format string is checked at compile time, so it cannot fail when rendering
+        // because of an unknown format specifier.
+        if let Some(format) = format {
+            if let Expr::StrLit(StrLit {
+                prefix: None,
+                content,
+            }) = **format
+            {
+                check_strftime_format(content).map_err(|err| {
+                    ctx.generate_error(
+                        format_args!("invalid format string in `{name}` filter: {err}"),
+                        format.span(),
+                    )
+                })?;
+            }
+        }
+
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_arg(ctx, buf, value)?;
+        buf.write(',');
+        match format {
+            Some(format) => self._visit_arg(ctx, buf, format)?,
+            None if name == "date" => buf.write("rinja::filters::DEFAULT_DATE_FORMAT"),
+            None => buf.write("rinja::filters::DEFAULT_DATETIME_FORMAT"),
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_number_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let (value, code, [decimals, locale]) = match name {
+            "currency" => {
+                let (value, [code, decimals, locale]) =
+                    filter_arguments(ctx, name, args, ["code", "decimals", "locale"], node)?;
+                let Some(code) = code else {
+                    return Err(ctx.generate_error(
+                        r#"use filter currency like `value|currency("EUR")`"#,
+                        node,
+                    ));
+                };
+                (value, Some(code), [decimals, locale])
+            }
+            _ => {
+                let (value, params) =
+                    filter_arguments(ctx, name, args, ["decimals", "locale"], node)?;
+                (value, None, params)
+            }
+        };
+        // Literal currency codes and locales are checked at compile time.
+        if let Some(code) = code {
+            if let Expr::StrLit(StrLit {
+                prefix: None,
+                content,
+            }) = **code
+            {
+                if content.len() != 3 || !content.bytes().all(|b| b.is_ascii_uppercase()) {
+                    return Err(ctx.generate_error(
+                        format_args!(
+                            "invalid currency code `{content}` in `currency` filter, \
+                             expected three ASCII uppercase letters"
+                        ),
+                        code.span(),
+                    ));
+                }
+            }
+        }
+        if let Some(locale) = locale {
+            if let Expr::StrLit(StrLit {
+                prefix: None,
+                content,
+            }) = **locale
+            {
+                if !is_known_locale(content) {
+                    return Err(ctx.generate_error(
+                        format_args!("unknown locale `{content}` in `{name}` filter"),
+                        locale.span(),
+                    ));
+                }
+            }
+        }
+
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_arg(ctx, buf, value)?;
+        if let Some(code) = code {
+            buf.write(',');
+            self._visit_arg(ctx, buf, code)?;
+        }
+        buf.write(',');
+        match decimals {
+            Some(decimals) => {
+                buf.write(
+                    "rinja::helpers::core::option::Option::Some(\
+                         rinja::helpers::get_primitive_value(&(",
+                );
+                self._visit_arg(ctx, buf, decimals)?;
+                buf.write(")) as rinja::helpers::core::primitive::usize)");
+            }
+            None => buf.write("rinja::helpers::core::option::Option::None"),
+        }
+        buf.write(',');
+        match locale {
+            Some(locale) => self._visit_arg(ctx, buf, locale)?,
+            None => buf.write("rinja::filters::DEFAULT_LOCALE"),
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_timesince_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        if cfg!(not(any(feature = "chrono", feature = "time"))) {
+            return Err(ctx.generate_error(
+                format_args!(
+                    "the `{name}` filter requires the `chrono` or `time` feature to be enabled"
+                ),
+                node,
+            ));
+        }
+        let (value, now) = match args {
+            [value] => (value, None),
+            [value, now] => (value, Some(now)),
+            _ => {
+                return Err(ctx.generate_error(
+                    format_args!("unexpected argument(s) in `{name}` filter"),
+                    node,
+                ));
+            }
+        };
+
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_arg(ctx, buf, value)?;
+        buf.write(',');
+        match now {
+            Some(now) => self._visit_arg(ctx, buf, now)?,
+            None => buf.write("rinja::filters::Now"),
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
     // Force type coercion on first argument to `join` filter (see #39).
     fn _visit_join_filter(
         &mut self,
@@ -1879,6 +2325,217 @@ impl<'a, 'h> Generator<'a, 'h> {
         Ok(DisplayWrap::Unwrapped)
     }
 
+    fn _visit_collection_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let [iter] = args else {
+            return Err(ctx.generate_error(
+                format_args!("unexpected argument(s) in `{name}` filter"),
+                node,
+            ));
+        };
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_iter_arg(ctx, buf, iter)?;
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_sort_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let (iter, [reverse, attribute]) =
+            filter_arguments(ctx, name, args, ["reverse", "attribute"], node)?;
+        match attribute {
+            Some(attribute) => {
+                let path = attribute_path(ctx, name, attribute)?;
+                buf.write("rinja::filters::sort_by_key(");
+                self._visit_iter_arg(ctx, buf, iter)?;
+                buf.write(format_args!(", |__rinja_item| &__rinja_item{path}"));
+            }
+            None => {
+                buf.write("rinja::filters::sort(");
+                self._visit_iter_arg(ctx, buf, iter)?;
+            }
+        }
+        buf.write(',');
+        match reverse {
+            Some(reverse) => {
+                buf.write("rinja::helpers::as_bool(&(");
+                self._visit_arg(ctx, buf, reverse)?;
+                buf.write("))");
+            }
+            None => buf.write("false"),
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_map_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let function_param = match name {
+            "map" => "function",
+            _ => "test",
+        };
+        let (iter, [function, attribute]) =
+            filter_arguments(ctx, name, args, [function_param, "attribute"], node)?;
+        // The closure is applied to the attribute of the item if one was given, otherwise to the
+        // item itself.
+        let path = match attribute {
+            Some(attribute) => Some(attribute_path(ctx, name, attribute)?),
+            None => None,
+        };
+        let value = match &path {
+            Some(path) => format!("&__rinja_item{path}"),
+            None => "__rinja_item".to_owned(),
+        };
+
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_iter_arg(ctx, buf, iter)?;
+        buf.write(",|__rinja_item| ");
+        match (name, function) {
+            // The attribute cannot be moved out of a borrowed item, and a reference cannot be
+            // returned for an owned item, so owned items are rejected with a clear error.
+            ("map", None) => match &path {
+                Some(path) => buf.write(format_args!(
+                    "&rinja::helpers::borrowed_item(__rinja_item){path}"
+                )),
+                None => {
+                    return Err(ctx.generate_error(
+                        "the `map` filter needs a function or an `attribute` to map the items to",
+                        node,
+                    ));
+                }
+            },
+            ("map", Some(function)) if matches!(**function, Expr::StrLit(_)) => {
+                return Err(ctx.generate_error(
+                    "the `map` filter expects a function, not a string literal",
+                    function.span(),
+                ));
+            }
+            (_, None) => buf.write(format_args!("rinja::helpers::as_bool({value})")),
+            (_, Some(test)) if matches!(**test, Expr::StrLit(_)) => {
+                let Expr::StrLit(StrLit {
+                    prefix: None,
+                    content,
+                }) = **test
+                else {
+                    return Err(ctx.generate_error(
+                        format_args!("the test in `{name}` filter must not have a prefix"),
+                        test.span(),
+                    ));
+                };
+                buf.write(test_expr(ctx, name, content, &value, test.span())?);
+            }
+            (_, Some(function)) => {
+                buf.write('(');
+                self.visit_expr(ctx, buf, function)?;
+                buf.write(format_args!(")({value})"));
+            }
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_groupby_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let (iter, [attribute]) = filter_arguments(ctx, name, args, ["attribute"], node)?;
+        let Some(attribute) = attribute else {
+            return Err(ctx.generate_error(
+                "the `groupby` filter needs the `attribute` to group the items by",
+                node,
+            ));
+        };
+        let path = attribute_path(ctx, name, attribute)?;
+        buf.write("rinja::filters::groupby(");
+        self._visit_iter_arg(ctx, buf, iter)?;
+        buf.write(format_args!(",|__rinja_item| &__rinja_item{path})?"));
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    fn _visit_batch_filter(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        name: &str,
+        args: &[WithSpan<'_, Expr<'_>>],
+        node: Span<'_>,
+    ) -> Result<DisplayWrap, CompileError> {
+        let (iter, size, fill_with) = match name {
+            "batch" => {
+                let (iter, [size, fill_with]) =
+                    filter_arguments(ctx, name, args, ["size", "fill_with"], node)?;
+                (iter, size, Some(fill_with))
+            }
+            _ => {
+                let (iter, [count]) = filter_arguments(ctx, name, args, ["count"], node)?;
+                (iter, count, None)
+            }
+        };
+        let Some(size) = size else {
+            return Err(ctx.generate_error(
+                format_args!("missing size argument in `{name}` filter"),
+                node,
+            ));
+        };
+
+        buf.write(format_args!("rinja::filters::{name}("));
+        self._visit_iter_arg(ctx, buf, iter)?;
+        buf.write(",rinja::helpers::get_primitive_value(&(");
+        self._visit_arg(ctx, buf, size)?;
+        buf.write(")) as rinja::helpers::core::primitive::usize");
+        match fill_with {
+            Some(Some(fill_with)) => {
+                // The items are borrowed from the collection, so `fill_with` is borrowed, too.
+                buf.write(",rinja::helpers::core::option::Option::Some(");
+                self._visit_arg_inner(ctx, buf, fill_with, true)?;
+                buf.write(')');
+            }
+            Some(None) => buf.write(",rinja::helpers::core::option::Option::None"),
+            None => {}
+        }
+        buf.write(")?");
+        Ok(DisplayWrap::Unwrapped)
+    }
+
+    /// Writes an argument that is iterated over, borrowing it unless it is a temporary value
+    fn _visit_iter_arg(
+        &mut self,
+        ctx: &Context<'_>,
+        buf: &mut Buffer,
+        arg: &WithSpan<'_, Expr<'_>>,
+    ) -> Result<(), CompileError> {
+        match **arg {
+            // The result of a filter or a call, e.g. `items|reverse`, can be consumed.
+            Expr::Filter(_) | Expr::Call(..) => buf.write('('),
+            _ => buf.write("(&"),
+        }
+        self.visit_expr(ctx, buf, arg)?;
+        buf.write(").into_iter()");
+        Ok(())
+    }
+
     fn _visit_args(
         &mut self,
         ctx: &Context<'_>,
@@ -2360,18 +3017,16 @@ impl<'a, 'h> Generator<'a, 'h> {
             Whitespace::Preserve => {
                 let val = self.next_ws.unwrap();
                 if !val.is_empty() {
-                    self.buf_writable.push(Writable::Lit(Cow::Borrowed(val)));
+                    self.buf_writable.push_lit(val);
                 }
             }
             Whitespace::Minimize => {
                 let val = self.next_ws.unwrap();
                 if !val.is_empty() {
-                    self.buf_writable.push(Writable::Lit(Cow::Borrowed(
-                        match val.contains('\n') {
-                            true => "\n",
-                            false => " ",
-                        },
-                    )));
+                    self.buf_writable.push_lit(match val.contains('\n') {
+                        true => "\n",
+                        false => " ",
+                    });
                 }
             }
             Whitespace::Suppress => {}
@@ -2424,6 +3079,154 @@ fn macro_call_ensure_arg_count(
     ))
 }
 
+/// Built-in filters that accept named arguments, e.g. `value|numberformat(decimals=2)`
+const FILTERS_WITH_NAMED_ARGUMENTS: &[&str] = &[
+    "batch",
+    "currency",
+    "groupby",
+    "map",
+    "numberformat",
+    "percent",
+    "reject",
+    "select",
+    "sort",
+];
+
+/// The input of a filter, and the arguments assigned to its parameters
+type FilterArguments<'a, 'b, const N: usize> = (
+    &'b WithSpan<'a, Expr<'a>>,
+    [Option<&'b WithSpan<'a, Expr<'a>>>; N],
+);
+
+/// Assigns the arguments of the filter `name` to its parameters `params`
+///
+/// The first argument is the input of the filter, it is not listed in `params`.
+/// The other arguments are assigned in order, or by name if they are named arguments.
+fn filter_arguments<'a, 'b, const N: usize>(
+    ctx: &Context<'_>,
+    name: &str,
+    args: &'b [WithSpan<'a, Expr<'a>>],
+    params: [&str; N],
+    node: Span<'_>,
+) -> Result<FilterArguments<'a, 'b, N>, CompileError> {
+    let Some((input, args)) = args.split_first() else {
+        return Err(ctx.generate_error(format_args!("missing input of `{name}` filter"), node));
+    };
+    let mut values = [None; N];
+    for (index, arg) in args.iter().enumerate() {
+        let (index, value) = match **arg {
+            Expr::NamedArgument(arg_name, ref value) => {
+                match params.iter().position(|param| *param == arg_name) {
+                    Some(index) => (index, &**value),
+                    None => {
+                        return Err(ctx.generate_error(
+                            format_args!("unknown named argument `{arg_name}` in `{name}` filter"),
+                            arg.span(),
+                        ));
+                    }
+                }
+            }
+            _ if index < N => (index, arg),
+            _ => {
+                return Err(ctx.generate_error(
+                    format_args!("unexpected argument(s) in `{name}` filter"),
+                    node,
+                ));
+            }
+        };
+        if values[index].replace(value).is_some() {
+            return Err(ctx.generate_error(
+                format_args!(
+                    "argument `{}` was passed more than once in `{name}` filter",
+                    params[index],
+                ),
+                arg.span(),
+            ));
+        }
+    }
+    Ok((input, values))
+}
+
+/// Converts the `attribute` argument of the filter `name`, e.g. `"user.name"`, into a field
+/// access path, e.g. `.user.name`
+fn attribute_path(
+    ctx: &Context<'_>,
+    name: &str,
+    attribute: &WithSpan<'_, Expr<'_>>,
+) -> Result<String, CompileError> {
+    let Expr::StrLit(StrLit {
+        prefix: None,
+        content,
+    }) = **attribute
+    else {
+        return Err(ctx.generate_error(
+            format_args!(
+                "the attribute in `{name}` filter must be a string literal, like `\"user.name\"`"
+            ),
+            attribute.span(),
+        ));
+    };
+
+    let mut path = String::with_capacity(content.len() + 1);
+    for field in content.split('.') {
+        let is_index = !field.is_empty() && field.bytes().all(|b| b.is_ascii_digit());
+        let is_identifier = field
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && field.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_index && !is_identifier {
+            return Err(ctx.generate_error(
+                format_args!("invalid attribute `{content}` in `{name}` filter"),
+                attribute.span(),
+            ));
+        }
+        path.push('.');
+        path.push_str(normalize_identifier(field));
+    }
+    Ok(path)
+}
+
+/// Converts the name of a test in the filter `name`, e.g. `select("odd")`, into an expression
+/// that tests `value`
+fn test_expr(
+    ctx: &Context<'_>,
+    name: &str,
+    test: &str,
+    value: &str,
+    span: Span<'_>,
+) -> Result<String, CompileError> {
+    Ok(match test {
+        "even" => format!("rinja::helpers::get_primitive_value({value}) % 2 == 0"),
+        "odd" => format!("rinja::helpers::get_primitive_value({value}) % 2 != 0"),
+        "none" => format!("({value}).is_none()"),
+        "some" => format!("({value}).is_some()"),
+        "true" => format!("rinja::helpers::as_bool({value})"),
+        "false" => format!("!rinja::helpers::as_bool({value})"),
+        _ => {
+            return Err(ctx.generate_error(
+                format_args!(
+                    "unknown test `{test}` in `{name}` filter, expected one of \
+                     `even`, `odd`, `none`, `some`, `true` or `false`"
+                ),
+                span,
+            ));
+        }
+    })
+}
+
+/// Returns whether `rinja::filters::Locale::from_name()` knows the locale `name`
+fn is_known_locale(name: &str) -> bool {
+    const LOCALES: &[&str] = &[
+        "de", "de-ch", "en", "es", "fr", "it", "ja", "ko", "nl", "pl", "pt", "pt-pt", "ru", "sv",
+        "zh",
+    ];
+
+    let name = name.to_ascii_lowercase().replace('_', "-");
+    let language = name.split('-').next().unwrap_or_default();
+    LOCALES.contains(&name.as_str()) || LOCALES.contains(&language)
+}
+
 #[cfg(target_pointer_width = "16")]
 type TargetIsize = i16;
 #[cfg(target_pointer_width = "32")]
@@ -2506,6 +3309,41 @@ fn expr_is_int_lit_plus_minus_one(expr: &WithSpan<'_, Expr<'_>>) -> Option<bool>
     }
 }
 
+/// Checks that all specifiers in a `strftime`-like format string are understood by the date
+/// libraries that are enabled.
+fn check_strftime_format(format: &str) -> Result<(), String> {
+    // Specifiers that are understood by both `chrono` and `time`.
+    const COMMON: &[char] = &[
+        '%', 'a', 'A', 'b', 'B', 'c', 'C', 'd', 'D', 'e', 'F', 'g', 'G', 'h', 'H', 'I', 'j', 'k',
+        'l', 'm', 'M', 'n', 'p', 'P', 'r', 'R', 's', 'S', 't', 'T', 'u', 'U', 'V', 'w', 'W', 'x',
+        'X', 'y', 'Y', 'z',
+    ];
+    // Specifiers that are only understood by `chrono`.
+    const CHRONO: &[&str] = &[
+        "+", ".3f", ".6f", ".9f", ".f", "3f", "6f", "9f", "::z", ":::z", ":z", "#z", "f", "v", "Z",
+    ];
+
+    let mut rest = format;
+    while let Some(pos) = rest.find('%') {
+        rest = &rest[pos + 1..];
+        let spec = rest.strip_prefix(['-', '_', '0']).unwrap_or(rest);
+        let Some(c) = spec.chars().next() else {
+            return Err("the format string must not end with a single `%`".into());
+        };
+        if COMMON.contains(&c) {
+            rest = &spec[c.len_utf8()..];
+        } else if let Some(chrono) = CHRONO
+            .iter()
+            .find(|chrono| cfg!(feature = "chrono") && spec.starts_with(*chrono))
+        {
+            rest = &spec[chrono.len()..];
+        } else {
+            return Err(format!("unknown format specifier `%{c}`"));
+        }
+    }
+    Ok(())
+}
+
 /// In here, we inspect in the expression if it is a literal, and if it is, whether it
 /// can be escaped at compile time.
 fn compile_time_escape<'a>(expr: &Expr<'a>, escaper: &str) -> Option<Writable<'a>> {
@@ -2864,6 +3702,19 @@ fn is_attr_self(mut expr: &Expr<'_>) -> bool {
     }
 }
 
+/// Returns the position where `expr` starts. The span of a suffix expression like `a.b`, `a[b]`,
+/// `a()` or `a?` points at the suffix, so we need to look at the receiver.
+fn expr_start<'a>(mut expr: &WithSpan<'a, Expr<'a>>) -> Span<'a> {
+    loop {
+        match &**expr {
+            Expr::Attr(obj, _) | Expr::Index(obj, _) | Expr::Call(obj, _) | Expr::Try(obj) => {
+                expr = obj;
+            }
+            _ => return expr.span(),
+        }
+    }
+}
+
 /// Returns `true` if the outcome of this expression may be used multiple times in the same
 /// `write!()` call, without evaluating the expression again, i.e. the expression should be
 /// side-effect free.
@@ -2934,6 +3785,8 @@ enum DisplayWrap {
 struct WritableBuffer<'a> {
     buf: Vec<Writable<'a>>,
     discard: bool,
+    // The HTML context after the literals, if context-aware escaping is enabled
+    html: Option<HtmlContext>,
 }
 
 impl<'a> WritableBuffer<'a> {
@@ -2942,6 +3795,21 @@ impl<'a> WritableBuffer<'a> {
             self.buf.push(writable);
         }
     }
+
+    fn push_lit(&mut self, lit: &'a str) {
+        // Discarded content still determines the context of the content that is written.
+        if let Some(html) = &mut self.html {
+            html.feed(lit);
+        }
+        self.push(Writable::Lit(Cow::Borrowed(lit)));
+    }
+
+    fn escaping(&self) -> Escaping {
+        match &self.html {
+            Some(html) => html.escaping(),
+            None => Escaping::Default,
+        }
+    }
 }
 
 impl<'a> Deref for WritableBuffer<'a> {
@@ -2955,7 +3823,7 @@ impl<'a> Deref for WritableBuffer<'a> {
 #[derive(Debug)]
 enum Writable<'a> {
     Lit(Cow<'a, str>),
-    Expr(&'a WithSpan<'a, Expr<'a>>),
+    Expr(&'a WithSpan<'a, Expr<'a>>, Escaping),
 }
 
 /// Identifiers to be replaced with raw identifiers, so as to avoid
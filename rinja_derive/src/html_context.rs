@@ -0,0 +1,634 @@
+//! Tracks the HTML context of the template's literal content, so that every expression can be
+//! escaped for the position it is written to, see `context_aware_escaping` in `rinja.toml`.
+
+/// How an expression must be escaped at the current position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Escaping {
+    /// The template's escaper, i.e. `Html`, is fine
+    Default,
+    /// The expression must be escaped with this escaper instead
+    With(&'static str),
+    /// In JavaScript code, only the output of `|json` is safe, if it is escaped with this escaper
+    JsCode(&'static str),
+    /// There is no escaper that is safe at this position; the string describes the position
+    Unsafe(&'static str),
+}
+
+/// The position in an HTML document after the literal content that was fed into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum HtmlContext {
+    /// Between tags
+    Text,
+    /// After `<`
+    TagOpen,
+    /// In the name of a tag
+    TagName { name: String, closing: bool },
+    /// In `<!doctype ...>` or `<?...>`
+    Declaration,
+    /// In `<!-- ... -->`, counting the trailing dashes
+    Comment { dashes: u8 },
+    /// In a tag, between its attributes
+    Tag { element: Element },
+    /// In the name of an attribute
+    AttrName { element: Element, name: String },
+    /// After the name of an attribute, before a possible `=`
+    AfterAttrName { element: Element, attr: Attr },
+    /// After the `=` of an attribute, before its value
+    BeforeValue { element: Element, attr: Attr },
+    /// In an attribute value that is not quoted
+    UnquotedValue { element: Element },
+    /// In an attribute value that is delimited by `quote`
+    Value {
+        element: Element,
+        attr: Attr,
+        quote: char,
+    },
+    /// In the content of a `<script>` element
+    Script(Js),
+    /// In the content of a `<style>` element
+    Style(Css),
+    /// Branches of the template ended in different contexts
+    Unknown,
+}
+
+/// Elements whose content is not HTML
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Element {
+    Other,
+    Script,
+    Style,
+}
+
+/// Attributes whose value is not plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Attr {
+    Normal,
+    Js(Js),
+    Css(Css),
+    Url(Url),
+}
+
+/// The lexical state of JavaScript code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Js {
+    Code,
+    Slash,
+    /// After a `/` that does not start a comment, so it may start a regular expression literal,
+    /// which is not lexed. Regular expressions cannot span lines, so this ends at a line break.
+    MaybeRegex,
+    Str(char),
+    StrEscape(char),
+    Template,
+    LineComment,
+    BlockComment,
+    BlockCommentStar,
+}
+
+/// The lexical state of a CSS stylesheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Css {
+    Code,
+    Slash,
+    Str(char),
+    StrEscape(char),
+    Comment,
+    CommentStar,
+}
+
+/// The part of a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Url {
+    /// Nothing but whitespace was written yet, so an expression may contain the scheme
+    Start,
+    /// An expression is a component of the URL, e.g. a path segment or a query parameter
+    Component,
+}
+
+impl HtmlContext {
+    /// Advances the context over the literal `text`
+    pub(crate) fn feed(&mut self, text: &str) {
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            if let Some(skipped) = self.feed_char(c, rest) {
+                rest = &rest[skipped..];
+            }
+        }
+    }
+
+    /// Advances the context over `c`; `rest` is the text that follows `c`, if it is known.
+    /// Returns the number of bytes in `rest` that were consumed as well.
+    fn feed_char(&mut self, c: char, rest: &str) -> Option<usize> {
+        *self = match take(self) {
+            Self::Text => match c {
+                '<' => Self::TagOpen,
+                _ => Self::Text,
+            },
+            Self::TagOpen => match c {
+                '/' => Self::TagName {
+                    name: String::new(),
+                    closing: true,
+                },
+                '!' if rest.starts_with("--") => {
+                    *self = Self::Comment { dashes: 0 };
+                    return Some(2);
+                }
+                '!' | '?' => Self::Declaration,
+                c if c.is_ascii_alphabetic() => Self::TagName {
+                    name: c.to_ascii_lowercase().into(),
+                    closing: false,
+                },
+                '<' => Self::TagOpen,
+                _ => Self::Text,
+            },
+            Self::TagName { mut name, closing } => match c {
+                c if c.is_ascii_whitespace() || c == '/' || c == '>' => {
+                    let element = match (closing, name.as_str()) {
+                        (false, "script") => Element::Script,
+                        (false, "style") => Element::Style,
+                        _ => Element::Other,
+                    };
+                    match c {
+                        '>' => Self::Tag { element }.close_tag(),
+                        _ => Self::Tag { element },
+                    }
+                }
+                c => {
+                    name.push(c.to_ascii_lowercase());
+                    Self::TagName { name, closing }
+                }
+            },
+            Self::Declaration => match c {
+                '>' => Self::Text,
+                _ => Self::Declaration,
+            },
+            Self::Comment { dashes } => match c {
+                '>' if dashes >= 2 => Self::Text,
+                '-' => Self::Comment {
+                    dashes: (dashes + 1).min(2),
+                },
+                _ => Self::Comment { dashes: 0 },
+            },
+            Self::Tag { element } => match c {
+                '>' => Self::Tag { element }.close_tag(),
+                c if c.is_ascii_whitespace() || c == '/' => Self::Tag { element },
+                c => Self::AttrName {
+                    element,
+                    name: c.to_ascii_lowercase().into(),
+                },
+            },
+            Self::AttrName { element, mut name } => match c {
+                '>' => Self::Tag { element }.close_tag(),
+                '=' => Self::BeforeValue {
+                    element,
+                    attr: Attr::new(&name),
+                },
+                c if c.is_ascii_whitespace() || c == '/' => Self::AfterAttrName {
+                    element,
+                    attr: Attr::new(&name),
+                },
+                c => {
+                    name.push(c.to_ascii_lowercase());
+                    Self::AttrName { element, name }
+                }
+            },
+            Self::AfterAttrName { element, attr } => match c {
+                '>' => Self::Tag { element }.close_tag(),
+                '=' => Self::BeforeValue { element, attr },
+                c if c.is_ascii_whitespace() || c == '/' => Self::AfterAttrName { element, attr },
+                c => Self::AttrName {
+                    element,
+                    name: c.to_ascii_lowercase().into(),
+                },
+            },
+            Self::BeforeValue { element, attr } => match c {
+                '>' => Self::Tag { element }.close_tag(),
+                '"' | '\'' => Self::Value {
+                    element,
+                    attr,
+                    quote: c,
+                },
+                c if c.is_ascii_whitespace() => Self::BeforeValue { element, attr },
+                _ => Self::UnquotedValue { element },
+            },
+            Self::UnquotedValue { element } => match c {
+                '>' => Self::Tag { element }.close_tag(),
+                c if c.is_ascii_whitespace() => Self::Tag { element },
+                _ => Self::UnquotedValue { element },
+            },
+            Self::Value { element, quote, .. } if c == quote => Self::Tag { element },
+            Self::Value {
+                element,
+                mut attr,
+                quote,
+            } => {
+                // The value is decoded before it is interpreted as code.
+                let (c, skipped) = match c {
+                    '&' => decode_char_ref(rest),
+                    c => (c, 0),
+                };
+                match &mut attr {
+                    Attr::Normal => {}
+                    Attr::Js(js) => js.feed(c),
+                    Attr::Css(css) => css.feed(c),
+                    Attr::Url(url) => url.feed(c),
+                }
+                *self = Self::Value {
+                    element,
+                    attr,
+                    quote,
+                };
+                return Some(skipped);
+            }
+            Self::Script(mut js) => match c {
+                '<' if starts_with_end_tag(rest, "script") => Self::TagOpen,
+                c => {
+                    js.feed(c);
+                    Self::Script(js)
+                }
+            },
+            Self::Style(mut css) => match c {
+                '<' if starts_with_end_tag(rest, "style") => Self::TagOpen,
+                c => {
+                    css.feed(c);
+                    Self::Style(css)
+                }
+            },
+            Self::Unknown => Self::Unknown,
+        };
+        None
+    }
+
+    /// The context after the `>` of a tag
+    fn close_tag(self) -> Self {
+        match self {
+            Self::Tag {
+                element: Element::Script,
+            } => Self::Script(Js::Code),
+            Self::Tag {
+                element: Element::Style,
+            } => Self::Style(Css::Code),
+            _ => Self::Text,
+        }
+    }
+
+    /// How an expression must be escaped if it is written at the current position
+    pub(crate) fn escaping(&self) -> Escaping {
+        match self {
+            Self::Text
+            | Self::Comment { .. }
+            | Self::Value {
+                attr: Attr::Normal, ..
+            } => Escaping::Default,
+            Self::TagOpen | Self::TagName { .. } | Self::Declaration => {
+                Escaping::Unsafe("in the name of a tag")
+            }
+            Self::Tag { .. } | Self::AttrName { .. } | Self::AfterAttrName { .. } => {
+                Escaping::Unsafe("in a tag, outside of an attribute value")
+            }
+            Self::BeforeValue { .. } | Self::UnquotedValue { .. } => {
+                Escaping::Unsafe("in an unquoted attribute value")
+            }
+            Self::Value {
+                attr: Attr::Js(js), ..
+            } => js.escaping("rinja::filters::Html"),
+            Self::Script(js) => js.escaping("rinja::filters::Text"),
+            Self::Value {
+                attr: Attr::Css(css),
+                ..
+            }
+            | Self::Style(css) => css.escaping(),
+            Self::Value {
+                attr: Attr::Url(url),
+                ..
+            } => match url {
+                Url::Start => Escaping::With("rinja::filters::SafeUrl"),
+                Url::Component => Escaping::With("rinja::filters::Url"),
+            },
+            Self::Unknown => {
+                Escaping::Unsafe("after branches of the template that end in different contexts")
+            }
+        }
+    }
+
+    /// Advances the context over the output of an expression, which is escaped and cannot change
+    /// the context, but which ends the start of a URL
+    pub(crate) fn skip_expr(&mut self) {
+        if let Self::Value {
+            attr: Attr::Url(url @ Url::Start),
+            ..
+        } = self
+        {
+            *url = Url::Component;
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if self != other {
+            *self = Self::Unknown;
+        }
+    }
+}
+
+/// Merges the contexts at the end of the branches of an `{% if %}`, `{% match %}` or `{% for %}`
+/// block, which all start in the same context
+///
+/// The `Option`s are `None` if context-aware escaping is not enabled.
+pub(crate) struct Branches {
+    start: Option<HtmlContext>,
+    end: Option<HtmlContext>,
+}
+
+impl Branches {
+    pub(crate) fn new(start: &Option<HtmlContext>) -> Self {
+        Self {
+            start: start.clone(),
+            end: None,
+        }
+    }
+
+    /// Resets `current` to the start of the branches
+    pub(crate) fn enter(&self, current: &mut Option<HtmlContext>) {
+        current.clone_from(&self.start);
+    }
+
+    /// Records the `current` context at the end of a branch
+    pub(crate) fn leave(&mut self, current: &Option<HtmlContext>) {
+        if let Some(current) = current {
+            match &mut self.end {
+                Some(end) => end.merge(current),
+                None => self.end = Some(current.clone()),
+            }
+        }
+    }
+
+    /// Records a branch without content, e.g. the missing `{% else %}` of an `{% if %}` block
+    pub(crate) fn leave_empty(&mut self) {
+        let start = self.start.take();
+        self.leave(&start);
+        self.start = start;
+    }
+
+    /// Sets `current` to the merged context at the end of the branches
+    pub(crate) fn finish(self, current: &mut Option<HtmlContext>) {
+        if self.end.is_some() {
+            *current = self.end;
+        }
+    }
+}
+
+impl Attr {
+    fn new(name: &str) -> Self {
+        match name {
+            "style" => Self::Css(Css::Code),
+            "action" | "background" | "cite" | "classid" | "codebase" | "data" | "formaction"
+            | "href" | "icon" | "longdesc" | "manifest" | "poster" | "profile" | "src"
+            | "srcset" | "usemap" | "xlink:href" => Self::Url(Url::Start),
+            name if name.starts_with("on") => Self::Js(Js::Code),
+            _ => Self::Normal,
+        }
+    }
+}
+
+impl Js {
+    fn feed(&mut self, c: char) {
+        *self = match (*self, c) {
+            (Self::Code, '"' | '\'') => Self::Str(c),
+            (Self::Code, '`') => Self::Template,
+            (Self::Code, '/') => Self::Slash,
+            (Self::Code, _) => Self::Code,
+            (Self::Slash, '/') => Self::LineComment,
+            (Self::Slash, '*') => Self::BlockComment,
+            (Self::Slash | Self::MaybeRegex, '\n' | '\r' | '\u{2028}' | '\u{2029}') => Self::Code,
+            (Self::Slash | Self::MaybeRegex, _) => Self::MaybeRegex,
+            (Self::Str(quote), '\\') => Self::StrEscape(quote),
+            (Self::Str(quote), c) if c == quote => Self::Code,
+            (Self::Str(quote), _) | (Self::StrEscape(quote), _) => Self::Str(quote),
+            (Self::Template, '`') => Self::Code,
+            (Self::Template, _) => Self::Template,
+            (Self::LineComment, '\n') => Self::Code,
+            (Self::LineComment, _) => Self::LineComment,
+            (Self::BlockComment | Self::BlockCommentStar, '*') => Self::BlockCommentStar,
+            (Self::BlockCommentStar, '/') => Self::Code,
+            (Self::BlockComment | Self::BlockCommentStar, _) => Self::BlockComment,
+        };
+    }
+
+    /// `json_escaper` makes the output of `|json` safe for the element or the attribute
+    fn escaping(self, json_escaper: &'static str) -> Escaping {
+        match self {
+            Self::Str(_) | Self::StrEscape(_) => Escaping::With("rinja::filters::Js"),
+            Self::Code => Escaping::JsCode(json_escaper),
+            Self::Slash | Self::MaybeRegex => Escaping::Unsafe(
+                "after a `/` in JavaScript, which could start a regular expression",
+            ),
+            Self::Template => Escaping::Unsafe("in a JavaScript template literal"),
+            Self::LineComment | Self::BlockComment | Self::BlockCommentStar => {
+                Escaping::Unsafe("in a JavaScript comment")
+            }
+        }
+    }
+}
+
+impl Css {
+    fn feed(&mut self, c: char) {
+        *self = match (*self, c) {
+            (Self::Code, '"' | '\'') => Self::Str(c),
+            (Self::Code, '/') => Self::Slash,
+            (Self::Code, _) => Self::Code,
+            (Self::Slash, '*') => Self::Comment,
+            (Self::Slash, c) => {
+                *self = Self::Code;
+                return self.feed(c);
+            }
+            (Self::Str(quote), '\\') => Self::StrEscape(quote),
+            (Self::Str(quote), c) if c == quote => Self::Code,
+            (Self::Str(quote), _) | (Self::StrEscape(quote), _) => Self::Str(quote),
+            (Self::Comment | Self::CommentStar, '*') => Self::CommentStar,
+            (Self::CommentStar, '/') => Self::Code,
+            (Self::Comment | Self::CommentStar, _) => Self::Comment,
+        };
+    }
+
+    fn escaping(self) -> Escaping {
+        match self {
+            Self::Comment | Self::CommentStar => Escaping::Unsafe("in a CSS comment"),
+            _ => Escaping::With("rinja::filters::Css"),
+        }
+    }
+}
+
+impl Url {
+    fn feed(&mut self, c: char) {
+        if !c.is_ascii_whitespace() {
+            *self = Self::Component;
+        }
+    }
+}
+
+fn take(context: &mut HtmlContext) -> HtmlContext {
+    std::mem::replace(context, HtmlContext::Unknown)
+}
+
+/// Returns `true` if `rest` starts with `/{name}` that is followed by the end of the tag name
+fn starts_with_end_tag(rest: &str, name: &str) -> bool {
+    let Some(rest) = rest.strip_prefix('/') else {
+        return false;
+    };
+    match rest.get(..name.len()) {
+        Some(tag) if tag.eq_ignore_ascii_case(name) => matches!(
+            rest.as_bytes().get(name.len()),
+            None | Some(b'>' | b'/' | b' ' | b'\t' | b'\n' | b'\x0c' | b'\r'),
+        ),
+        _ => false,
+    }
+}
+
+/// Decodes the character reference after a `&`, returns the character and the length of the
+/// reference in `rest`
+///
+/// Unknown named references are returned as a neutral character, so they don't open or close a
+/// string or a comment.
+fn decode_char_ref(rest: &str) -> (char, usize) {
+    let Some(end) = rest.find(';').filter(|&end| {
+        end > 0
+            && rest[..end]
+                .bytes()
+                .enumerate()
+                .all(|(i, b)| b.is_ascii_alphanumeric() || (i == 0 && b == b'#'))
+    }) else {
+        return ('&', 0);
+    };
+    let name = &rest[..end];
+    let c = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+    } else if let Some(dec) = name.strip_prefix('#') {
+        dec.parse().ok().and_then(char::from_u32)
+    } else {
+        match name {
+            "amp" => Some('&'),
+            "apos" => Some('\''),
+            "gt" => Some('>'),
+            "lt" => Some('<'),
+            "quot" => Some('"'),
+            "sol" => Some('/'),
+            "ast" => Some('*'),
+            "grave" => Some('`'),
+            "bsol" => Some('\\'),
+            "quest" => Some('?'),
+            "num" => Some('#'),
+            "newline" => Some('\n'),
+            _ => None,
+        }
+    };
+    (c.unwrap_or('_'), end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escaping(literals: &[&str]) -> Escaping {
+        let mut context = HtmlContext::Text;
+        for literal in literals {
+            context.feed(literal);
+        }
+        context.escaping()
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escaping(&["<p>"]), Escaping::Default);
+        assert_eq!(escaping(&["<p ", "title='"]), Escaping::Default);
+        assert_eq!(escaping(&["<!-- <script> "]), Escaping::Default);
+        assert_eq!(escaping(&["<!DOCTYPE html><p>"]), Escaping::Default);
+        assert_eq!(
+            escaping(&["<", "p"]),
+            Escaping::Unsafe("in the name of a tag")
+        );
+        assert_eq!(
+            escaping(&["<p title"]),
+            Escaping::Unsafe("in a tag, outside of an attribute value")
+        );
+        assert_eq!(
+            escaping(&["<p title = "]),
+            Escaping::Unsafe("in an unquoted attribute value")
+        );
+
+        assert_eq!(
+            escaping(&["<SCRIPT>"]),
+            Escaping::JsCode("rinja::filters::Text")
+        );
+        assert_eq!(
+            escaping(&["<script type=module>x = '"]),
+            Escaping::With("rinja::filters::Js")
+        );
+        assert_eq!(escaping(&["<script>'\\''</script >"]), Escaping::Default);
+        assert_eq!(escaping(&["<script>'</style>"]), escaping(&["<script>'"]));
+        assert_eq!(
+            escaping(&["<script>/*", "*/ /", "/"]),
+            Escaping::Unsafe("in a JavaScript comment")
+        );
+        assert_eq!(
+            escaping(&["<script>x = /'/.test(y); z = "]),
+            Escaping::Unsafe("after a `/` in JavaScript, which could start a regular expression")
+        );
+        assert_eq!(
+            escaping(&["<script>x = /'/.test(y);\nz = "]),
+            Escaping::JsCode("rinja::filters::Text")
+        );
+        assert_eq!(
+            escaping(&["<a onclick=\"f(&quot;"]),
+            Escaping::With("rinja::filters::Js")
+        );
+        assert_eq!(
+            escaping(&["<a onclick=\"f(&quot;&quot;"]),
+            Escaping::JsCode("rinja::filters::Html")
+        );
+        assert_eq!(escaping(&["<a onclick='f(\"' title=\""]), Escaping::Default);
+
+        assert_eq!(
+            escaping(&["<style>p{}"]),
+            Escaping::With("rinja::filters::Css")
+        );
+        assert_eq!(
+            escaping(&["<p style='/* x"]),
+            Escaping::Unsafe("in a CSS comment")
+        );
+
+        assert_eq!(
+            escaping(&["<a href=\" "]),
+            Escaping::With("rinja::filters::SafeUrl")
+        );
+        assert_eq!(
+            escaping(&["<form action='/?q="]),
+            Escaping::With("rinja::filters::Url")
+        );
+    }
+
+    #[test]
+    fn test_branches() {
+        let mut current = Some(HtmlContext::Text);
+        let mut branches = Branches::new(&current);
+        current.as_mut().unwrap().feed("<b>");
+        branches.leave(&current);
+        branches.enter(&mut current);
+        current.as_mut().unwrap().feed("<i>");
+        branches.leave(&current);
+        branches.finish(&mut current);
+        assert_eq!(current, Some(HtmlContext::Text));
+
+        let mut branches = Branches::new(&current);
+        current.as_mut().unwrap().feed("<script>");
+        branches.leave(&current);
+        branches.leave_empty();
+        branches.finish(&mut current);
+        assert_eq!(current, Some(HtmlContext::Unknown));
+
+        let mut current = None;
+        let mut branches = Branches::new(&current);
+        branches.leave_empty();
+        branches.finish(&mut current);
+        assert_eq!(current, None);
+    }
+}
@@ -1,7 +1,6 @@
 use std::borrow::Cow;
 use std::collections::hash_map::{Entry, HashMap};
 use std::fs::read_to_string;
-use std::iter::FusedIterator;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, OnceLock};
@@ -26,6 +25,7 @@ pub(crate) struct TemplateInput<'a> {
     pub(crate) block: Option<&'a str>,
     pub(crate) print: Print,
     pub(crate) escaper: &'a str,
+    pub(crate) extension: Option<String>,
     pub(crate) path: Arc<Path>,
     pub(crate) fields: Vec<String>,
 }
@@ -79,20 +79,22 @@ impl TemplateInput<'_> {
             },
         )?;
 
-        // Match extension against defined output formats
+        // Match the directory and the extension against defined output formats
 
-        let escaping = escaping
-            .as_deref()
-            .or_else(|| path.extension().and_then(|s| s.to_str()))
-            .unwrap_or_default();
-
-        let escaper = config
-            .escapers
-            .iter()
-            .find_map(|(extensions, path)| {
-                extensions
-                    .contains(&Cow::Borrowed(escaping))
-                    .then_some(path.as_ref())
+        let dir_escaper = match (source, escaping) {
+            (Source::Path(_), None) => config.dir_escaper(&path),
+            _ => None,
+        };
+        let (extension, escaping) = output_format(&path, escaping.as_deref());
+        let extension = extension.map(str::to_owned);
+
+        let escaper = dir_escaper
+            .or_else(|| {
+                config.escapers.iter().find_map(|(extensions, path)| {
+                    extensions
+                        .contains(&Cow::Borrowed(escaping))
+                        .then_some(path.as_ref())
+                })
             })
             .ok_or_else(|| {
                 CompileError::no_file_info(
@@ -133,11 +135,25 @@ impl TemplateInput<'_> {
             block: block.as_deref(),
             print: *print,
             escaper,
+            extension,
             path,
             fields,
         })
     }
 
+    /// The extension of the output format, e.g. `html` for `page.html` and `page.html.j2`.
+    ///
+    /// This is the same value that was used to select the [escaper][Self::escaper], unless
+    /// escaping was disabled with `escape = "none"`.
+    pub(crate) fn extension(&self) -> Option<&str> {
+        self.extension.as_deref()
+    }
+
+    /// The MIME type of the rendered output, guessed from the extension of the template's path.
+    pub(crate) fn mime_type(&self) -> &'static str {
+        extension_to_mime_type(self.extension().unwrap_or("txt"))
+    }
+
     pub(crate) fn find_used_templates(
         &self,
         map: &mut HashMap<Arc<Path>, Arc<Parsed>, FxBuildHasher>,
@@ -483,13 +499,12 @@ fn collect_rinja_code_blocks(
     let mut had_rinja_code = false;
     for e in Parser::new(&source) {
         match (in_rinja_code, e) {
-            (false, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(s)))) => {
+            (false, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(s))))
                 if s.split(",")
-                    .any(|s| JINJA_EXTENSIONS.contains(&s.trim_ascii()))
-                {
-                    in_rinja_code = true;
-                    had_rinja_code = true;
-                }
+                    .any(|s| JINJA_EXTENSIONS.contains(&s.trim_ascii())) =>
+            {
+                in_rinja_code = true;
+                had_rinja_code = true;
             }
             (true, Event::End(TagEnd::CodeBlock)) => in_rinja_code = false,
             (true, Event::Text(text)) => tmpl_source.push_str(&text),
@@ -506,50 +521,21 @@ fn collect_rinja_code_blocks(
     Ok(Source::Source(tmpl_source.into()))
 }
 
-struct ResultIter<I, E>(Result<I, Option<E>>);
-
-impl<I: IntoIterator, E> From<Result<I, E>> for ResultIter<I::IntoIter, E> {
-    fn from(value: Result<I, E>) -> Self {
-        Self(match value {
-            Ok(i) => Ok(i.into_iter()),
-            Err(e) => Err(Some(e)),
-        })
-    }
-}
-
-impl<I: Iterator, E> Iterator for ResultIter<I, E> {
-    type Item = Result<I::Item, E>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match &mut self.0 {
-            Ok(iter) => Some(Ok(iter.next()?)),
-            Err(err) => Some(Err(err.take()?)),
-        }
-    }
-}
-
-impl<I: FusedIterator, E> FusedIterator for ResultIter<I, E> {}
-
 #[derive(Debug, Clone, Hash, PartialEq)]
 pub(crate) enum Source {
     Path(Arc<str>),
     Source(Arc<str>),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash)]
 pub(crate) enum Print {
     All,
     Ast,
     Code,
+    #[default]
     None,
 }
 
-impl Default for Print {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl FromStr for Print {
     type Err = String;
 
@@ -864,9 +850,54 @@ const _: () = {
     }
 };
 
-#[cfg(feature = "code-in-doc")]
 const JINJA_EXTENSIONS: &[&str] = &["j2", "jinja", "jinja2", "rinja"];
 
+/// Returns the extension of the output format, and the name to look up the escaper
+///
+/// An explicit `escape` argument takes precedence over the extension of the template's path.
+/// `escape = "none"` only disables escaping, it does not change the output format.
+///
+/// The output format of a template like `page.html.j2` is `html`, but its escaper is still looked
+/// up by its last extension, `j2`.
+fn output_format<'a>(path: &'a Path, escaping: Option<&'a str>) -> (Option<&'a str>, &'a str) {
+    let extension = match escaping {
+        Some("none") | None => path_extension(path),
+        Some(escaping) => Some(escaping),
+    };
+    let escaping = escaping.or_else(|| path.extension()?.to_str());
+    (extension, escaping.unwrap_or_default())
+}
+
+fn path_extension(path: &Path) -> Option<&str> {
+    let ext = path.extension()?.to_str()?;
+    if JINJA_EXTENSIONS.contains(&ext) {
+        // an extension like `.html.j2`: the inner extension describes the output format
+        if let Some(inner) = Path::new(path.file_stem()?).extension() {
+            return inner.to_str();
+        }
+    }
+    Some(ext)
+}
+
+fn extension_to_mime_type(ext: &str) -> &'static str {
+    match ext {
+        "html" | "htm" | "j2" | "jinja" | "jinja2" | "rinja" => "text/html; charset=utf-8",
+        "xhtml" => "application/xhtml+xml; charset=utf-8",
+        "txt" | "none" | "" => "text/plain; charset=utf-8",
+        "atom" => "application/atom+xml",
+        "css" => "text/css; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "md" => "text/markdown; charset=utf-8",
+        "rss" => "application/rss+xml",
+        "svg" => "image/svg+xml",
+        "xml" => "text/xml; charset=utf-8",
+        "yml" | "yaml" => "application/yaml",
+        _ => "application/octet-stream",
+    }
+}
+
 #[test]
 fn get_source() {
     let path = Config::new("", None, None, None)
@@ -874,3 +905,33 @@ fn get_source() {
         .unwrap();
     assert_eq!(get_template_source(&path, None).unwrap(), "bar".into());
 }
+
+#[test]
+fn test_output_format() {
+    assert_eq!(
+        output_format(Path::new("foo.html"), None),
+        (Some("html"), "html")
+    );
+    assert_eq!(
+        output_format(Path::new("foo.txt.j2"), None),
+        (Some("txt"), "j2")
+    );
+    assert_eq!(output_format(Path::new("foo.j2"), None), (Some("j2"), "j2"));
+    assert_eq!(output_format(Path::new("foo"), None), (None, ""));
+    assert_eq!(
+        output_format(Path::new("foo.html"), Some("txt")),
+        (Some("txt"), "txt"),
+    );
+    assert_eq!(
+        output_format(Path::new("foo.html"), Some("none")),
+        (Some("html"), "none"),
+    );
+    assert_eq!(
+        extension_to_mime_type(output_format(Path::new("foo.css.jinja"), None).0.unwrap()),
+        "text/css; charset=utf-8",
+    );
+    assert_eq!(
+        extension_to_mime_type("unknown"),
+        "application/octet-stream"
+    );
+}
@@ -7,6 +7,10 @@ use syn::DeriveInput;
 pub(crate) fn impl_everything(ast: &DeriveInput, buf: &mut Buffer) {
     impl_display(ast, buf);
     impl_fast_writable(ast, buf);
+    #[cfg(feature = "with-axum")]
+    impl_axum_into_response(ast, buf);
+    #[cfg(feature = "with-actix-web")]
+    impl_actix_web_responder(ast, buf);
 }
 
 /// Writes header for the `impl` for `TraitFromPathName` or `Template` for the given item
@@ -27,7 +31,7 @@ fn impl_display(ast: &DeriveInput, buf: &mut Buffer) {
     let ident = &ast.ident;
     buf.write(format_args!(
         "\
-        /// Implement the [`format!()`][rinja::helpers::std::format] trait for [`{}`]\n\
+        /// Implement the [`format!()`][rinja::helpers::alloc::format] trait for [`{}`]\n\
         ///\n\
         /// Please be aware of the rendering performance notice in the \
             [`Template`][rinja::Template] trait.\n\
@@ -65,6 +69,42 @@ fn impl_fast_writable(ast: &DeriveInput, buf: &mut Buffer) {
     );
 }
 
+/// Implement axum's `IntoResponse` for the given item.
+#[cfg(feature = "with-axum")]
+fn impl_axum_into_response(ast: &DeriveInput, buf: &mut Buffer) {
+    write_header(
+        ast,
+        buf,
+        "rinja::helpers::axum_core::response::IntoResponse",
+    );
+    buf.write(
+        "\
+            #[inline]\
+            fn into_response(self) -> rinja::helpers::axum_core::response::Response {\
+                rinja::helpers::axum_into_response(&self)\
+            }\
+        }",
+    );
+}
+
+/// Implement actix-web's `Responder` for the given item.
+#[cfg(feature = "with-actix-web")]
+fn impl_actix_web_responder(ast: &DeriveInput, buf: &mut Buffer) {
+    write_header(ast, buf, "rinja::helpers::actix_web::Responder");
+    buf.write(
+        "\
+            type Body = rinja::helpers::actix_web::body::BoxBody;\
+            #[inline]\
+            fn respond_to(\
+                self,\
+                _req: &rinja::helpers::actix_web::HttpRequest,\
+            ) -> rinja::helpers::actix_web::HttpResponse<Self::Body> {\
+                rinja::helpers::actix_respond_to(&self)\
+            }\
+        }",
+    );
+}
+
 #[derive(Debug)]
 pub(crate) struct Buffer {
     // The buffer to generate the code into
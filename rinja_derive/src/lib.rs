@@ -2,10 +2,15 @@
 #![deny(elided_lifetimes_in_paths)]
 #![deny(unreachable_pub)]
 
+// `html.rs` is shared with `rinja`, which is a `no_std` crate.
+#[cfg(test)]
+extern crate alloc;
+
 mod config;
 mod generator;
 mod heritage;
 mod html;
+mod html_context;
 mod input;
 mod integration;
 #[cfg(test)]
@@ -23,7 +28,7 @@ use generator::template_to_string;
 use heritage::{Context, Heritage};
 use input::{Print, TemplateArgs, TemplateInput};
 use integration::Buffer;
-use parser::{Parsed, strip_common};
+use parser::{Parsed, Syntax, strip_common};
 #[cfg(not(feature = "__standalone"))]
 use proc_macro::TokenStream as TokenStream12;
 #[cfg(feature = "__standalone")]
@@ -358,6 +363,35 @@ fn generate_error_info(src: &str, input: &str, file_path: &Path) -> (ErrorInfo,
     (error_info, file_path)
 }
 
+/// Return the template tag that contains `input`, e.g. `{{ total()? }}`, to be used in the
+/// location of runtime errors.
+///
+/// If the tag does not start and end in the same line, the rest of the line is returned instead.
+fn generate_source_snippet<'a>(src: &'a str, input: &'a str, syntax: &Syntax<'_>) -> Cow<'a, str> {
+    let offset = src.len() - input.len();
+    let line_start = src[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input.find('\n').map_or(src.len(), |i| offset + i);
+    let (before, after) = (&src[line_start..offset], &src[offset..line_end]);
+
+    let start = [syntax.expr_start, syntax.block_start]
+        .into_iter()
+        .filter_map(|delim| before.rfind(delim))
+        .max();
+    let end = [syntax.expr_end, syntax.block_end]
+        .into_iter()
+        .filter_map(|delim| Some(after.find(delim)? + delim.len()))
+        .min();
+    if let (Some(start), Some(end)) = (start, end) {
+        return Cow::Borrowed(&src[line_start + start..offset + end]);
+    }
+
+    let after = after.trim_end();
+    match after.char_indices().nth(40) {
+        Some((i, _)) => Cow::Owned(format!("{}...", &after[..i])),
+        None => Cow::Borrowed(after),
+    }
+}
+
 struct MsgValidEscapers<'a>(&'a [(Vec<Cow<'a, str>>, Cow<'a, str>)]);
 
 impl fmt::Display for MsgValidEscapers<'_> {
@@ -22,6 +22,30 @@ fn compare(jinja: &str, expected: &str, fields: &[(&str, &str)], size_hint: usiz
     let generated = jinja_to_rust(jinja, fields).unwrap();
 
     let expected: proc_macro2::TokenStream = expected.parse().unwrap();
+    #[allow(unused_mut)]
+    let mut integrations = proc_macro2::TokenStream::new();
+    #[cfg(feature = "with-axum")]
+    integrations.extend(quote::quote! {
+        impl rinja::helpers::axum_core::response::IntoResponse for Foo {
+            #[inline]
+            fn into_response(self) -> rinja::helpers::axum_core::response::Response {
+                rinja::helpers::axum_into_response(&self)
+            }
+        }
+    });
+    #[cfg(feature = "with-actix-web")]
+    integrations.extend(quote::quote! {
+        impl rinja::helpers::actix_web::Responder for Foo {
+            type Body = rinja::helpers::actix_web::body::BoxBody;
+            #[inline]
+            fn respond_to(
+                self,
+                _req: &rinja::helpers::actix_web::HttpRequest,
+            ) -> rinja::helpers::actix_web::HttpResponse<Self::Body> {
+                rinja::helpers::actix_respond_to(&self)
+            }
+        }
+    });
     let expected: syn::File = syn::parse_quote! {
         const _: () = {
             extern crate rinja as rinja;
@@ -37,9 +61,12 @@ fn compare(jinja: &str, expected: &str, fields: &[(&str, &str)], size_hint: usiz
                     rinja::Result::Ok(())
                 }
                 const SIZE_HINT: rinja::helpers::core::primitive::usize = #size_hint;
+                const EXTENSION: rinja::helpers::core::option::Option<&'static rinja::helpers::core::primitive::str> =
+                    rinja::helpers::core::option::Option::Some("txt");
+                const MIME_TYPE: &'static rinja::helpers::core::primitive::str = "text/plain; charset=utf-8";
             }
 
-            /// Implement the [`format!()`][rinja::helpers::std::format] trait for [`Foo`]
+            /// Implement the [`format!()`][rinja::helpers::alloc::format] trait for [`Foo`]
             ///
             /// Please be aware of the rendering performance notice in the [`Template`][rinja::Template] trait.
             impl rinja::helpers::core::fmt::Display for Foo {
@@ -58,6 +85,8 @@ fn compare(jinja: &str, expected: &str, fields: &[(&str, &str)], size_hint: usiz
                     rinja::Template::render_into(self, dest)
                 }
             }
+
+            #integrations
         };
     };
 
@@ -1003,6 +1032,49 @@ fn test_concat() {
     );
 }
 
+#[test]
+fn test_error_location() {
+    compare(
+        "{{ value()? }}",
+        r#"
+            match (
+                &((&&rinja::filters::AutoEscaper::new(
+                    &(rinja::helpers::map_try(
+                        self.value(),
+                        const { &rinja::Location::new("Foo.txt", 1, 4, "{{ value()? }}") },
+                    )?),
+                    rinja::filters::Text,
+                ))
+                    .rinja_auto_escape()?),
+            ) {
+                (expr0,) => {
+                    (&&rinja::filters::Writable(expr0)).rinja_write(writer)?;
+                }
+            }
+        "#,
+        &[],
+        3,
+    );
+
+    compare(
+        "{% if x|check(1) %}b{% endif %}",
+        r#"
+            if rinja::helpers::as_bool(
+                &(rinja::helpers::map_filter(
+                    filters::check(&(self.x), 1),
+                    const {
+                        &rinja::Location::new("Foo.txt", 1, 7, "{% if x|check(1) %}")
+                    },
+                )?),
+            ) {
+                writer.write_str("b")?;
+            }
+        "#,
+        &[("x", "u32")],
+        1,
+    );
+}
+
 #[test]
 fn extends_with_whitespace_control() {
     const CONTROL: &[&str] = &["", "\t", "-", "+", "~"];
@@ -136,7 +136,7 @@ impl<'a> Expr<'a> {
     pub(super) fn arguments(
         i: &'a str,
         level: Level,
-        is_template_macro: bool,
+        allow_named_arguments: bool,
     ) -> ParseResult<'a, Vec<WithSpan<'a, Self>>> {
         let (_, level) = level.nest(i)?;
         let mut named_arguments = HashSet::new();
@@ -159,7 +159,7 @@ impl<'a> Expr<'a> {
                                     level,
                                     named_arguments,
                                     start,
-                                    is_template_macro,
+                                    allow_named_arguments,
                                 )
                             },
                             move |i| Self::parse(i, level, false),
@@ -187,11 +187,11 @@ impl<'a> Expr<'a> {
         level: Level,
         named_arguments: &mut HashSet<&'a str>,
         start: &'a str,
-        is_template_macro: bool,
+        allow_named_arguments: bool,
     ) -> ParseResult<'a, WithSpan<'a, Self>> {
-        if !is_template_macro {
-            // If this is not a template macro, we don't want to parse named arguments so
-            // we instead return an error which will allow to continue the parsing.
+        if !allow_named_arguments {
+            // Named arguments are only allowed in calls of template macros and filters, otherwise
+            // we return an error which will allow to continue the parsing.
             return fail.parse_next(i);
         }
 
@@ -176,7 +176,7 @@ impl<'a> Span<'a> {
 impl<'a> From<&'a str> for Span<'a> {
     #[inline]
     fn from(value: &'a str) -> Self {
-        Self(value[..0].as_bytes().try_into().unwrap())
+        Self(value.as_bytes()[..0].try_into().unwrap())
     }
 }
 
@@ -965,7 +965,7 @@ fn filter<'a>(
     let (i, _) = ws(('|', not('|'))).parse_next(i)?;
 
     *level = level.nest(start)?.1;
-    cut_err((ws(identifier), opt(|i| Expr::arguments(i, *level, false)))).parse_next(i)
+    cut_err((ws(identifier), opt(|i| Expr::arguments(i, *level, true)))).parse_next(i)
 }
 
 /// Returns the common parts of two paths.
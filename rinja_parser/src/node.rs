@@ -648,12 +648,10 @@ impl<'a> Macro<'a> {
                 .parse_next(i)?;
                 match args {
                     Some((args, Some(_))) => Ok((i, args)),
-                    Some((_, None)) => {
-                        return Err(winnow::error::ErrMode::Cut(ErrorContext::new(
-                            "expected `)` to close macro argument list",
-                            i,
-                        )));
-                    }
+                    Some((_, None)) => Err(winnow::error::ErrMode::Cut(ErrorContext::new(
+                        "expected `)` to close macro argument list",
+                        i,
+                    ))),
                     None => Ok((i, None)),
                 }
             };
@@ -761,7 +759,7 @@ impl<'a> FilterBlock<'a> {
                 Some("filter"),
                 (
                     ws(identifier),
-                    opt(|i| Expr::arguments(i, s.level.get(), false)),
+                    opt(|i| Expr::arguments(i, s.level.get(), true)),
                     repeat(0.., |i| {
                         filter(i, &mut level).map(|(j, (name, params))| (j, (name, params, i)))
                     })
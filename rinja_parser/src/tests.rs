@@ -95,6 +95,30 @@ fn test_parse_filter() {
             })),
         )],
     );
+    assert_eq!(
+        Ast::from_str("{{ x|round(2, method = \"floor\") }}", None, &syntax)
+            .unwrap()
+            .nodes,
+        vec![Node::Expr(
+            Ws(None, None),
+            WithSpan::no_span(Expr::Filter(Filter {
+                name: "round",
+                arguments: vec![
+                    WithSpan::no_span(Expr::Var("x")),
+                    WithSpan::no_span(int_lit("2")),
+                    WithSpan::no_span(Expr::NamedArgument(
+                        "method",
+                        WithSpan::no_span(Expr::StrLit(StrLit {
+                            prefix: None,
+                            content: "floor"
+                        }))
+                        .into()
+                    )),
+                ],
+            })),
+        )],
+    );
+    assert!(Ast::from_str("{{ x|round(method = \"floor\", 2) }}", None, &syntax).is_err());
 }
 
 #[test]
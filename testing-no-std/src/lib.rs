@@ -0,0 +1,99 @@
+//! Make sure that the generated code works in a `#![no_std]` crate, and that `rinja` can be
+//! built without its default feature `std`, e.g. for a `thumbv7em-none-eabihf` target.
+
+#![no_std]
+
+extern crate alloc;
+
+use rinja::Template;
+
+#[derive(Template)]
+#[template(
+    ext = "html",
+    source = "\
+        <h1>{{ title|upper }}</h1>\n\
+        <ul>\n\
+        {%- for (name, value) in sensors %}\n\
+        <li{% if loop.first %} class=\"first\"{% endif %}>{{ name }}: {{ \"{:.1}\"|format(value) }}</li>\n\
+        {%- endfor %}\n\
+        </ul>\n\
+        <a href=\"/reset?device={{ title|urlencode }}\">reset</a>"
+)]
+pub struct Status<'a> {
+    pub title: &'a str,
+    pub sensors: &'a [(&'a str, f32)],
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::{self, Write};
+
+    use super::*;
+
+    #[test]
+    fn render() {
+        let status = Status {
+            title: "Boiler <1>",
+            sensors: &[("temperature", 71.25), ("pressure", 1.5)],
+        };
+        assert_eq!(
+            status.render().unwrap(),
+            "<h1>BOILER &#60;1&#62;</h1>\n\
+            <ul>\n\
+            <li class=\"first\">temperature: 71.2</li>\n\
+            <li>pressure: 1.5</li>\n\
+            </ul>\n\
+            <a href=\"/reset?device=Boiler%20%3C1%3E\">reset</a>",
+        );
+    }
+
+    #[test]
+    fn render_into_fixed_buffer() {
+        struct Buffer {
+            data: [u8; 16],
+            len: usize,
+        }
+
+        impl Write for Buffer {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let dest = self
+                    .data
+                    .get_mut(self.len..self.len + s.len())
+                    .ok_or(fmt::Error)?;
+                dest.copy_from_slice(s.as_bytes());
+                self.len += s.len();
+                Ok(())
+            }
+        }
+
+        let status = Status {
+            title: "Boiler",
+            sensors: &[],
+        };
+        let mut buf = Buffer {
+            data: [0; 16],
+            len: 0,
+        };
+        assert!(matches!(
+            status.render_into(&mut buf),
+            Err(rinja::Error::Fmt)
+        ));
+        assert_eq!(&buf.data[..buf.len], b"<h1>BOILER");
+    }
+
+    #[test]
+    fn render_with_limits() {
+        let status = Status {
+            title: "Boiler",
+            sensors: &[("temperature", 71.25)],
+        };
+        let limits = rinja::Limits {
+            max_bytes: 32,
+            ..rinja::Limits::default()
+        };
+        assert!(matches!(
+            status.render_with_limits(limits),
+            Err(rinja::Error::LimitExceeded(rinja::Limit::Bytes))
+        ));
+    }
+}
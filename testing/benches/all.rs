@@ -1,4 +1,5 @@
 use std::hint::black_box;
+use std::io;
 use std::iter::repeat;
 
 use criterion::{Criterion, criterion_group, criterion_main};
@@ -11,10 +12,14 @@ fn functions(c: &mut Criterion) {
     c.bench_function("Big table", big_table);
     c.bench_function("Big table (fmt)", big_table_fmt);
     c.bench_function("Big table (io)", big_table_io);
+    c.bench_function("Big table (bytes)", big_table_bytes);
+    c.bench_function("Big table (io, unbuffered)", big_table_io_unbuffered);
 
     c.bench_function("Teams", teams);
     c.bench_function("Teams (fmt)", teams_fmt);
     c.bench_function("Teams (io)", teams_io);
+    c.bench_function("Teams (bytes)", teams_bytes);
+    c.bench_function("Teams (io, unbuffered)", teams_io_unbuffered);
 }
 
 fn big_table(b: &mut criterion::Bencher) {
@@ -36,6 +41,20 @@ fn big_table_io(b: &mut criterion::Bencher) {
     });
 }
 
+fn big_table_bytes(b: &mut criterion::Bencher) {
+    let ctx = BigTable::default();
+    b.iter(|| black_box(&ctx).render_bytes().unwrap());
+}
+
+fn big_table_io_unbuffered(b: &mut criterion::Bencher) {
+    let ctx = BigTable::default();
+    b.iter(|| {
+        let mut writer = Unbuffered::default();
+        black_box(&ctx).write_into(&mut writer).unwrap();
+        writer
+    });
+}
+
 #[derive(Template)]
 #[template(path = "big-table.html")]
 struct BigTable {
@@ -71,6 +90,20 @@ fn teams_io(b: &mut criterion::Bencher) {
     });
 }
 
+fn teams_bytes(b: &mut criterion::Bencher) {
+    let teams = Teams::default();
+    b.iter(|| black_box(&teams).render_bytes().unwrap());
+}
+
+fn teams_io_unbuffered(b: &mut criterion::Bencher) {
+    let teams = Teams::default();
+    b.iter(|| {
+        let mut writer = Unbuffered::default();
+        black_box(&teams).write_into(&mut writer).unwrap();
+        writer
+    });
+}
+
 #[derive(Template)]
 #[template(path = "teams.html")]
 struct Teams {
@@ -108,3 +141,20 @@ struct Team {
     name: String,
     score: u8,
 }
+
+/// An [`io::Write`] without a buffer of its own, like an unbuffered `File`
+#[derive(Default)]
+struct Unbuffered {
+    data: Vec<u8>,
+}
+
+impl io::Write for Unbuffered {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use rinja::Template;
+
+macro_rules! test_template {
+    ($source:literal, $value:expr, $expected:expr $(,)?) => {{
+        #[derive(Template)]
+        #[template(source = $source, ext = "html", config = "context-aware-escaping.toml")]
+        struct Tmpl<'a> {
+            value: &'a str,
+        }
+
+        assert_eq!(Tmpl { value: $value }.render().unwrap(), $expected);
+    }};
+}
+
+#[test]
+fn test_text_and_attributes() {
+    test_template!(
+        "<p title=\"{{ value }}\">{{ value }}</p>",
+        "<\"'&>",
+        "<p title=\"&#60;&#34;&#39;&#38;&#62;\">&#60;&#34;&#39;&#38;&#62;</p>",
+    );
+    test_template!(
+        "<!-- {{ value }} --><p class='a {{ value }}'>",
+        "-->",
+        "<!-- --&#62; --><p class='a --&#62;'>",
+    );
+}
+
+#[test]
+fn test_script() {
+    test_template!(
+        "<script>let x = \"{{ value }}\"; let y = '{{ value }}';</script>{{ value }}",
+        "</script><b>\"",
+        "<script>let x = \"\\u003C/script\\u003E\\u003Cb\\u003E\\u0022\"; \
+        let y = '\\u003C/script\\u003E\\u003Cb\\u003E\\u0022';</script>\
+        &#60;/script&#62;&#60;b&#62;&#34;",
+    );
+    test_template!(
+        "<script>// \"\n/* ' */ let x = /'/, y = 1 / 2;\nlet z = \"{{ value }}\";</script>",
+        "'\n",
+        "<script>// \"\n/* ' */ let x = /'/, y = 1 / 2;\nlet z = \"\\u0027\\u000A\";</script>",
+    );
+    test_template!(
+        "<button onclick=\"alert(&quot;{{ value }}&quot;)\">",
+        "\");evil(\"",
+        "<button onclick=\"alert(&quot;\\u0022);evil(\\u0022&quot;)\">",
+    );
+    // JSON is a JavaScript expression
+    #[cfg(feature = "serde_json")]
+    test_template!(
+        "<script>let x = {{ value|json }};</script>",
+        "</script>",
+        "<script>let x = \"\\u003c/script\\u003e\";</script>",
+    );
+    // `|safe` output is never escaped
+    test_template!(
+        "<script>let x = {{ value|safe }};</script>",
+        "[1, 2]",
+        "<script>let x = [1, 2];</script>",
+    );
+}
+
+#[test]
+fn test_style() {
+    test_template!(
+        "<style>p { color: {{ value }}; content: \"{{ value }}\"; }</style>",
+        "red}</style>",
+        "<style>p { color: red\\7d \\3c \\2f style\\3e ; content: \"red\\7d \\3c \\2f style\\3e \"; }</style>",
+    );
+    test_template!(
+        "<p style=\"color: {{ value }}\">",
+        "red;background:url(x)",
+        "<p style=\"color: red\\3b background\\3a url\\28 x\\29 \">",
+    );
+}
+
+#[test]
+fn test_urls() {
+    test_template!(
+        "<a href=\"{{ value }}\">",
+        "https://example.com/?a=1&b=2",
+        "<a href=\"https://example.com/?a=1&#38;b=2\">",
+    );
+    test_template!(
+        "<a href=\"{{ value }}\">",
+        "javascript:alert(1)",
+        "<a href=\"about:invalid#rinja\">",
+    );
+    test_template!(
+        "<a href=\"/search?q={{ value }}\">",
+        "a&b c/d",
+        "<a href=\"/search?q=a%26b%20c%2Fd\">",
+    );
+    test_template!(
+        "<img src='{{ value }}{{ value }}'>",
+        "javascript:x",
+        "<img src='about:invalid#rinjajavascript%3Ax'>",
+    );
+}
+
+#[test]
+fn test_control_flow() {
+    #[derive(Template)]
+    #[template(
+        source = "{% for item in items %}<a href=\"/item/{{ item }}\" \
+            {%- if loop.first %} class=\"first\"{% endif %}>{{ item }}</a>{% endfor %}\
+            <script>let items = [{% for item in items %}\"{{ item }}\",{% endfor %}];</script>",
+        ext = "html",
+        config = "context-aware-escaping.toml"
+    )]
+    struct Items<'a> {
+        items: &'a [&'a str],
+    }
+
+    assert_eq!(
+        Items {
+            items: &["a b", "<c>"]
+        }
+        .render()
+        .unwrap(),
+        "<a href=\"/item/a%20b\" class=\"first\">a b</a><a href=\"/item/%3Cc%3E\">&#60;c&#62;</a>\
+        <script>let items = [\"a b\",\"\\u003Cc\\u003E\",];</script>",
+    );
+
+    // contexts are only tracked for the built-in `Html` escaper
+    #[derive(Template)]
+    #[template(
+        source = "<script>let x = {{ value }};</script>",
+        ext = "txt",
+        config = "context-aware-escaping.toml"
+    )]
+    struct Plain<'a> {
+        value: &'a str,
+    }
+
+    assert_eq!(
+        Plain { value: "1" }.render().unwrap(),
+        "<script>let x = 1;</script>"
+    );
+}
@@ -0,0 +1,110 @@
+#![cfg(any(feature = "chrono", feature = "time"))]
+
+use rinja::Template;
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ day|date }} {{ day|date("%-d %B %Y") }} {{ moment|datetime }} {{ moment|datetime("%H:%M %p") }}"#,
+        ext = "txt"
+    )]
+    struct Dates {
+        day: NaiveDate,
+        moment: NaiveDateTime,
+    }
+
+    let day = NaiveDate::from_ymd_opt(2024, 7, 4).unwrap();
+    let moment = day.and_hms_opt(18, 30, 15).unwrap();
+    assert_eq!(
+        Dates { day, moment }.render().unwrap(),
+        "2024-07-04 4 July 2024 2024-07-04 18:30:15 18:30 PM"
+    );
+
+    #[derive(Template)]
+    #[template(
+        source = "{{ created|timesince(now) }}, {{ deadline|timeuntil(now) }}",
+        ext = "txt"
+    )]
+    struct Relative {
+        created: NaiveDateTime,
+        deadline: NaiveDate,
+        now: NaiveDateTime,
+    }
+
+    let now = moment;
+    assert_eq!(
+        Relative {
+            created: now - TimeDelta::hours(3),
+            deadline: day + TimeDelta::days(10),
+            now,
+        }
+        .render()
+        .unwrap(),
+        "3 hours ago, in 1 week"
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time() {
+    use time::{Date, Duration, Month, OffsetDateTime, UtcOffset};
+
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ day|date }} {{ moment|datetime("%a, %d %b %Y %H:%M:%S %z") }}"#,
+        ext = "html"
+    )]
+    struct Dates {
+        day: Date,
+        moment: OffsetDateTime,
+    }
+
+    let day = Date::from_calendar_date(2024, Month::July, 4).unwrap();
+    let moment = day
+        .with_hms(18, 30, 15)
+        .unwrap()
+        .assume_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+    assert_eq!(
+        Dates { day, moment }.render().unwrap(),
+        "2024-07-04 Thu, 04 Jul 2024 18:30:15 -0500"
+    );
+
+    #[derive(Template)]
+    #[template(source = "{{ published|timesince }}", ext = "html")]
+    struct Published {
+        published: OffsetDateTime,
+    }
+
+    let published = OffsetDateTime::now_utc() - Duration::days(2);
+    assert_eq!(Published { published }.render().unwrap(), "2 days ago");
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_invalid_runtime_format() {
+    #[derive(Template)]
+    #[template(source = "{{ day|date(format) }}", ext = "txt")]
+    struct Dynamic<'a> {
+        day: time::Date,
+        format: &'a str,
+    }
+
+    let day = time::Date::from_calendar_date(2024, time::Month::July, 4).unwrap();
+    assert_eq!(
+        Dynamic {
+            day,
+            format: "%d.%m."
+        }
+        .render()
+        .unwrap(),
+        "04.07."
+    );
+    assert!(matches!(
+        Dynamic { day, format: "%H" }.render(),
+        Err(rinja::Error::Custom(_))
+    ));
+}
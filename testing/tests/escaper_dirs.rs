@@ -0,0 +1,38 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(path = "email/welcome.txt", config = "escaper-dirs.toml")]
+struct Email<'a> {
+    name: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    path = "email/welcome.txt",
+    config = "escaper-dirs.toml",
+    escape = "none"
+)]
+struct UnescapedEmail<'a> {
+    name: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "export/name.txt", config = "escaper-dirs.toml")]
+struct Export<'a> {
+    name: &'a str,
+}
+
+#[test]
+fn test_escaper_dirs() {
+    let name = "<Tom & Jerry>";
+    assert_eq!(
+        Email { name }.render().unwrap(),
+        "Hello &#60;Tom &#38; Jerry&#62;!"
+    );
+    assert_eq!(Email::MIME_TYPE, "text/plain; charset=utf-8");
+    assert_eq!(
+        UnescapedEmail { name }.render().unwrap(),
+        "Hello <Tom & Jerry>!"
+    );
+    assert_eq!(Export { name }.render().unwrap(), "<Tom & Jerry>");
+}
@@ -328,5 +328,8 @@ fn filter_block_custom_errors() {
     let template = FilterBlockCustomErrors {
         msg: Err("🐢".to_owned()),
     };
-    assert_eq!(template.render().unwrap_err().to_string(), "🐢");
+    assert_eq!(
+        template.render().unwrap_err().to_string(),
+        "FilterBlockCustomErrors.html:3:12 in {{ msg.clone()? }}: 🐢",
+    );
 }
@@ -74,6 +74,182 @@ fn filter_opt_escaper_html() {
     );
 }
 
+#[test]
+fn filter_escapejs() {
+    #[derive(Template)]
+    #[template(
+        source = r#"let a = "{{ s }}";
+let b = '{{ s|escapejs }}';
+let c = `{{ s|escape("js") }}`;"#,
+        ext = "html"
+    )]
+    struct EscapeJsTemplate<'a> {
+        s: &'a str,
+    }
+
+    let t = EscapeJsTemplate {
+        s: "</script>\"'`\\\n\u{2028}",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        "let a = \"&#60;/script&#62;&#34;&#39;`\\\n\u{2028}\";\n\
+         let b = '\\u003C/script\\u003E\\u0022\\u0027\\u0060\\\\\\u000A\\u2028';\n\
+         let c = `\\u003C/script\\u003E\\u0022\\u0027\\u0060\\\\\\u000A\\u2028`;",
+    );
+}
+
+#[test]
+fn filter_escaper_js_extension() {
+    #[derive(Template)]
+    #[template(source = r#"const name = "{{ name }}";"#, ext = "js")]
+    struct JsTemplate<'a> {
+        name: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(source = r#"export const name = "{{ name }}";"#, ext = "mjs")]
+    struct ModuleTemplate<'a> {
+        name: &'a str,
+    }
+
+    let name = "\"; alert(1); //";
+    assert_eq!(
+        JsTemplate { name }.render().unwrap(),
+        r#"const name = "\u0022; alert(1); //";"#
+    );
+    assert_eq!(
+        ModuleTemplate { name }.render().unwrap(),
+        r#"export const name = "\u0022; alert(1); //";"#
+    );
+}
+
+#[test]
+fn filter_escaper_css() {
+    #[derive(Template)]
+    #[template(
+        source = "body { font-family: \"{{ font }}\"; color: {{ color }}; }",
+        ext = "css"
+    )]
+    struct CssTemplate<'a> {
+        font: &'a str,
+        color: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = r#"<p style="color: {{ color|escape("css") }}">"#,
+        ext = "html"
+    )]
+    struct StyleTemplate<'a> {
+        color: &'a str,
+    }
+
+    let t = CssTemplate {
+        font: "Comic Sans\"; } * { x: y",
+        color: "red;x:url(a)",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        r#"body { font-family: "Comic\20 Sans\22 \3b \20 \7d \20 \2a \20 \7b \20 x\3a \20 y"; color: red\3b x\3a url\28 a\29 ; }"#
+    );
+    assert_eq!(
+        StyleTemplate { color: "#fff\"" }.render().unwrap(),
+        r#"<p style="color: \23 fff\22 ">"#
+    );
+}
+
+#[test]
+fn filter_escaper_latex_and_roff() {
+    #[derive(Template)]
+    #[template(source = r"\textbf{ {{- name -}} } & {{ total }}\\", ext = "tex")]
+    struct InvoiceTemplate<'a> {
+        name: &'a str,
+        total: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(source = ".SH NAME\n{{ name }} \\- {{ description }}\n", ext = "1")]
+    struct ManPageTemplate<'a> {
+        name: &'a str,
+        description: &'a str,
+    }
+
+    let t = InvoiceTemplate {
+        name: "R&D_{2}",
+        total: "$100 (50% off)",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        r"\textbf{R\&D\_\{2\}} & \$100 (50\% off)\\"
+    );
+
+    let t = ManPageTemplate {
+        name: "rinja-cli",
+        description: ".\\\" run 'it'",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        ".SH NAME\nrinja\\-cli \\- \\&.\\e\\(dq run \\(aqit\\(aq\n",
+    );
+}
+
+#[test]
+fn filter_escaper_shell() {
+    #[derive(Template)]
+    #[template(
+        source = "cp {{ src }} {{ dest|shell_quote }} # {{ dest }}",
+        ext = "sh"
+    )]
+    struct ScriptTemplate<'a> {
+        src: &'a str,
+        dest: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(source = "<code>rm {{ path|shell_quote }}</code>", ext = "html")]
+    struct DocTemplate<'a> {
+        path: &'a str,
+    }
+
+    let t = ScriptTemplate {
+        src: "$(rm -rf ~)",
+        dest: "it's",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        r"cp '$(rm -rf ~)' 'it'\''s' # 'it'\''s'"
+    );
+    assert_eq!(
+        DocTemplate { path: "a b" }.render().unwrap(),
+        "<code>rm &#39;a b&#39;</code>"
+    );
+}
+
+#[test]
+fn filter_escaper_xml() {
+    #[derive(Template)]
+    #[template(source = r#"<item title="{{ title }}">{{ body }}</item>"#, ext = "xml")]
+    struct ItemTemplate<'a> {
+        title: &'a str,
+        body: &'a str,
+    }
+
+    let t = ItemTemplate {
+        title: "Tom's \"<b>\"",
+        body: "a & b\n",
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        "<item title=\"Tom&apos;s &quot;&lt;b&gt;&quot;\">a &amp; b\n</item>"
+    );
+
+    let t = ItemTemplate {
+        title: "",
+        body: "form\x0cfeed",
+    };
+    assert!(matches!(t.render(), Err(rinja::Error::Fmt)));
+}
+
 #[test]
 fn filter_format() {
     #[derive(Template)]
@@ -103,7 +279,7 @@ mod filters {
         Ok(s.replace("oo", "aa"))
     }
     // for test_nested_filter_ref
-    pub fn mytrim(s: &dyn (::std::fmt::Display)) -> ::rinja::Result<String> {
+    pub fn mytrim(s: &dyn ::std::fmt::Display) -> ::rinja::Result<String> {
         Ok(s.to_string().trim().to_owned())
     }
 }
@@ -450,3 +626,319 @@ fn test_whitespace_around_filter_operator() {
 
     assert_eq!(S.render().unwrap(), "12\n8\n4");
 }
+
+#[test]
+fn test_numberformat() {
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ amount|numberformat }}
+{{ amount|numberformat(decimals=2) }}
+{{ amount|numberformat(decimals=2, locale="de") }}
+{{ amount|numberformat(locale=locale, decimals=places) }}
+{{ count|numberformat }} {{ count|numberformat(1, "fr") }}"#,
+        ext = "html"
+    )]
+    struct Numbers<'a> {
+        amount: f64,
+        count: &'a std::cell::Cell<i64>,
+        locale: &'a str,
+        places: u8,
+    }
+
+    let count = std::cell::Cell::new(-1234567);
+    let numbers = Numbers {
+        amount: 1234567.891,
+        count: &count,
+        locale: "de-CH",
+        places: 1,
+    };
+    assert_eq!(
+        numbers.render().unwrap(),
+        "1,234,567.891\n1,234,567.89\n1.234.567,89\n1’234’567.9\n\
+         -1,234,567 -1\u{202f}234\u{202f}567,0"
+    );
+
+    let numbers = Numbers {
+        locale: "xx",
+        ..numbers
+    };
+    assert!(matches!(numbers.render(), Err(rinja::Error::Custom(_))));
+}
+
+#[test]
+fn test_percent_and_currency() {
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ ratio|percent }} {{ ratio|percent(decimals=1, locale="de") }}
+{{ price|currency("USD") }} {{ price|currency("EUR", locale="fr") }} {{ price|currency(code) }}"#,
+        ext = "html"
+    )]
+    struct Prices<'a> {
+        ratio: f32,
+        price: i32,
+        code: &'a str,
+    }
+
+    assert_eq!(
+        Prices {
+            ratio: 0.126,
+            price: -1500,
+            code: "JPY",
+        }
+        .render()
+        .unwrap(),
+        "13% 12,6\u{a0}%\n-$1,500.00 -1\u{202f}500,00\u{a0}€ -¥1,500"
+    );
+}
+
+#[test]
+fn test_collection_filters() {
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ names|length }} {{ names|unique|length }} {{ names|sort|join(",") }}
+{{ names|reverse|join(",") }} {{ names|unique|sort(true)|join(",") }}
+{%- if let Some(name) = names|first %} {{ name }}{% endif %}
+{%- if let Some(name) = names|sort|last %} {{ name }}{% endif %}
+{{ scores|sum }} {{ (scores|min).copied().unwrap_or_default() }} {{ (scores|max).unwrap() }}
+{%- for group in groups %} {{ group|sum }}{% endfor %}"#,
+        ext = "txt"
+    )]
+    struct Collections<'a> {
+        names: Vec<String>,
+        scores: &'a [i32],
+        groups: &'a [Vec<u8>],
+    }
+
+    let names = ["bob", "alice", "carol", "bob"].map(String::from).to_vec();
+    let groups = [vec![1, 2], vec![], vec![3]];
+    assert_eq!(
+        Collections {
+            names,
+            scores: &[3, -1, 7],
+            groups: &groups,
+        }
+        .render()
+        .unwrap(),
+        "4 3 alice,bob,bob,carol\nbob,carol,alice,bob carol,bob,alice bob carol\n\
+         9 -1 7 3 0 3"
+    );
+
+    // `sum` fails instead of overflowing
+    let groups = [vec![200, 100]];
+    let err = Collections {
+        names: vec![],
+        scores: &[1],
+        groups: &groups,
+    }
+    .render()
+    .unwrap_err();
+    assert_eq!(err.to_string(), "the `sum` of the items overflows");
+}
+
+#[test]
+fn test_sort_by_attribute() {
+    struct User<'a> {
+        name: &'a str,
+        address: Address<'a>,
+    }
+
+    struct Address<'a> {
+        city: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = r#"
+            {%- for user in users|sort(attribute="name") %}{{ user.name }} {% endfor -%}
+            |
+            {%- for user in users|sort(attribute="address.city", reverse=true) %} {{ user.name }}
+            {%- endfor -%}
+            |
+            {%- for pair in pairs|sort(false, "1") %} {{ pair.0 }}{% endfor %}"#,
+        ext = "txt"
+    )]
+    struct Users<'a> {
+        users: Vec<User<'a>>,
+        pairs: &'a [(char, f32)],
+    }
+
+    let user = |name, city| User {
+        name,
+        address: Address { city },
+    };
+    let users = Users {
+        users: vec![
+            user("carol", "Berlin"),
+            user("alice", "Paris"),
+            user("bob", "Berlin"),
+        ],
+        pairs: &[('a', 2.5), ('b', -1.0), ('c', 0.0)],
+    };
+    assert_eq!(
+        users.render().unwrap(),
+        "alice bob carol | alice carol bob| b c a"
+    );
+}
+
+#[test]
+fn test_map_select_reject() {
+    fn shout(name: &&str) -> String {
+        name.to_uppercase()
+    }
+
+    struct Task<'a> {
+        name: &'a str,
+        done: bool,
+        owner: Option<&'a str>,
+        priority: u8,
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = r#"{{ tasks|map(attribute="name")|join(", ") }}
+{{ tasks|select(attribute="done")|map(attribute="name")|join(", ") }}
+{{ tasks|reject(attribute="done")|map(shout, attribute="name")|join(", ") }}
+{{ tasks|select("none", attribute="owner")|length }} {{ tasks|select("some", attribute="owner")|length }}
+{{ tasks|select(test="even", attribute="priority")|map(attribute="name")|reverse|join(", ") }}
+{{ numbers|select("odd")|join(",") }} {{ numbers|reject(is_small)|join(",") }}
+{{ flags|select|length }} {{ flags|select("false")|length }}"#,
+        ext = "txt"
+    )]
+    struct Tasks<'a> {
+        tasks: Vec<Task<'a>>,
+        numbers: &'a [i32],
+        flags: &'a [bool],
+        is_small: fn(&&i32) -> bool,
+        shout: fn(&&str) -> String,
+    }
+
+    let tasks = Tasks {
+        tasks: vec![
+            Task {
+                name: "write",
+                done: true,
+                owner: Some("ann"),
+                priority: 2,
+            },
+            Task {
+                name: "test",
+                done: false,
+                owner: None,
+                priority: 1,
+            },
+            Task {
+                name: "ship",
+                done: false,
+                owner: Some("bob"),
+                priority: 4,
+            },
+        ],
+        numbers: &[1, 2, 3, 4, 5],
+        flags: &[true, false, true],
+        is_small: |n| **n < 3,
+        shout,
+    };
+    assert_eq!(
+        tasks.render().unwrap(),
+        "write, test, ship
+write
+TEST, SHIP
+1 2
+ship, write
+1,3,5 3,4,5
+2 1"
+    );
+
+    // `map(attribute=...)` borrows from the items, so owned items must be stored in a variable.
+    #[derive(Template)]
+    #[template(
+        source = r#"{% let pairs = self.pairs() %}{{ pairs|map(attribute="1")|join(",") }}"#,
+        ext = "txt"
+    )]
+    struct OwnedItems;
+
+    impl OwnedItems {
+        fn pairs(&self) -> Vec<(&'static str, u8)> {
+            vec![("a", 1), ("b", 2)]
+        }
+    }
+
+    assert_eq!(OwnedItems.render().unwrap(), "1,2");
+}
+
+#[test]
+fn test_groupby() {
+    struct Order<'a> {
+        id: u32,
+        status: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = r#"
+            {%- for (status, orders) in orders|groupby("status") -%}
+                [{{ status }}: {{ orders|map(attribute="id")|join(",") }}]
+            {%- endfor %}
+            {%- for group in orders|groupby(attribute="id") %} {{ group.0 }}{% endfor %}"#,
+        ext = "txt"
+    )]
+    struct Orders<'a> {
+        orders: &'a [Order<'a>],
+    }
+
+    let orders = Orders {
+        orders: &[
+            Order {
+                id: 3,
+                status: "shipped",
+            },
+            Order {
+                id: 1,
+                status: "open",
+            },
+            Order {
+                id: 2,
+                status: "shipped",
+            },
+        ],
+    };
+    assert_eq!(orders.render().unwrap(), "[open: 1][shipped: 3,2] 1 2 3");
+}
+
+#[test]
+fn test_batch_and_slice() {
+    #[derive(Template)]
+    #[template(
+        source = r#"
+            {%- for row in products|batch(3) -%}
+                {%- for product in row %}{{ product }}{% if !loop.last %},{% endif %}{% endfor -%}
+                {%- if !loop.last %} | {% endif -%}
+            {%- endfor %}
+            {%~ for row in products|batch(size=4, fill_with=empty) -%}
+                [{{ row|length }}: {{ row|join(" ") }}]
+            {%- endfor %}
+            {%~ for column in numbers|select("odd")|slice(2) -%}
+                {{ loop.index }}={{ column|join("+") }}{% if loop.last %}.{% else %} {% endif %}
+            {%- endfor %}"#,
+        ext = "txt"
+    )]
+    struct Grid<'a> {
+        products: Vec<String>,
+        empty: String,
+        numbers: &'a [u32],
+    }
+
+    let grid = Grid {
+        products: ["tea", "coffee", "cake", "pie", "soup"]
+            .map(String::from)
+            .into(),
+        empty: "-".into(),
+        numbers: &[1, 2, 3, 4, 5, 6, 7, 8, 9],
+    };
+    assert_eq!(
+        grid.render().unwrap(),
+        "tea,coffee,cake | pie,soup
+[4: tea coffee cake pie][4: soup - - -]
+1=1+3+5 2=7+9."
+    );
+}
@@ -0,0 +1,113 @@
+#![cfg(all(feature = "hot-reload", debug_assertions))]
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use rinja::Template;
+use rinja::filters::Html;
+use rinja::hot_reload::{Source, Value, Whitespace, hash, render};
+
+#[derive(Template)]
+#[template(path = "hot-reload.html")]
+struct HotReload<'a> {
+    title: &'a str,
+    items: Vec<&'a str>,
+}
+
+#[test]
+fn test_unmodified_template() {
+    // The template file is unmodified, so the compiled code is used.
+    let tmpl = HotReload {
+        title: "<Fruits>",
+        items: vec!["apple", "banana"],
+    };
+    assert_eq!(
+        tmpl.render().unwrap(),
+        "<h1>&#60;Fruits&#62;</h1>\n<ul>\n  <li>apple</li>\n  <li>banana</li>\n</ul>",
+    );
+}
+
+/// A template file in the temp directory, which is removed when dropped
+struct TempTemplate {
+    path: PathBuf,
+    created: SystemTime,
+}
+
+impl TempTemplate {
+    fn new(content: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("rinja-hot-reload-{}.html", std::process::id()));
+        fs::write(&path, content).unwrap();
+        let created = fs::metadata(&path).unwrap().modified().unwrap();
+        Self { path, created }
+    }
+
+    fn write(&self, content: &str, age: u64) {
+        fs::write(&self.path, content).unwrap();
+        // Some file systems have a coarse timestamp resolution, so set the time explicitly.
+        File::options()
+            .write(true)
+            .open(&self.path)
+            .unwrap()
+            .set_modified(self.created + Duration::from_secs(age))
+            .unwrap();
+    }
+}
+
+impl Drop for TempTemplate {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[test]
+fn test_render_modified_file() {
+    const COMPILED: &str = "<h1>{{ title }}</h1>\n";
+
+    let file = TempTemplate::new(COMPILED);
+    let source = Source {
+        path: String::leak(file.path.to_str().unwrap().to_owned()),
+        syntax: ["{%", "%}", "{{", "}}", "{#", "#}"],
+        whitespace: Whitespace::Preserve,
+        hash: hash(COMPILED.as_bytes()),
+    };
+    let fields = |name: &str| match name {
+        "title" => Some(Value::Str("<Fruits>".into())),
+        "items" => Some(Value::List(
+            [Value::Str("apple".into()), Value::Str("banana".into())].into(),
+        )),
+        _ => None,
+    };
+    let render = || {
+        let mut output = String::new();
+        render(&mut output, &source, &Html, &fields).map(|result| result.map(|()| output))
+    };
+
+    // the file is unmodified, so the compiled code should be used
+    assert!(render().is_none());
+
+    // the modified template is interpreted
+    file.write(
+        "<h2>{{ title|upper }}</h2>\n\
+        {%- for item in items if item != \"apple\" %} {{ loop.index }}. {{ item }}{% endfor %}\n",
+        1,
+    );
+    assert_eq!(
+        render().unwrap().unwrap(),
+        "<h2>&#60;FRUITS&#62;</h2> 1. banana",
+    );
+
+    // calling a method is not supported, so the compiled code should be used
+    file.write("{{ title.len() }}", 2);
+    assert!(render().is_none());
+
+    // syntax errors are reported
+    file.write("{% if title %}", 3);
+    let err = render().unwrap().unwrap_err();
+    assert!(err.to_string().contains("rinja-hot-reload-"), "{err}");
+
+    // the original content is not interpreted again
+    file.write(COMPILED, 4);
+    assert!(render().is_none());
+}
@@ -0,0 +1,119 @@
+#![cfg(any(feature = "with-actix-web", feature = "with-axum"))]
+
+use rinja::Template;
+
+#[derive(Template)]
+#[template(path = "hello.html")]
+struct HelloTemplate<'a> {
+    name: &'a str,
+}
+
+#[derive(Template)]
+#[template(source = "{{ value()? }}", ext = "txt")]
+struct FailingTemplate;
+
+impl FailingTemplate {
+    fn value(&self) -> Result<&'static str, std::fmt::Error> {
+        Err(std::fmt::Error)
+    }
+}
+
+#[cfg(feature = "with-axum")]
+mod axum {
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use http_body_util::BodyExt;
+    use tower::util::ServiceExt;
+
+    use super::{FailingTemplate, HelloTemplate};
+
+    // `#[tokio::test]` cannot be used, because this crate shadows `::core`.
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn template_to_response() {
+        let app = Router::new().route("/", get(|| async { HelloTemplate { name: "world" } }));
+
+        let res = block_on(app.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()));
+        let res = res.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let headers = res.headers();
+        assert_eq!(headers["Content-Type"], "text/html; charset=utf-8");
+
+        let body = block_on(res.into_body().collect()).unwrap().to_bytes();
+        assert_eq!(&body[..], b"Hello, world!");
+    }
+
+    #[test]
+    fn render_error_to_response() {
+        let app = Router::new().route("/", get(|| async { FailingTemplate }));
+
+        let res = block_on(app.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()));
+        assert_eq!(res.unwrap().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
+#[cfg(feature = "with-actix-web")]
+mod actix_web {
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::CONTENT_TYPE;
+    use actix_web::{App, test, web};
+    use rinja::Template;
+
+    use super::{FailingTemplate, HelloTemplate};
+
+    // `#[actix_web::test]` cannot be used, because this crate shadows `::core`.
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        actix_web::rt::System::new().block_on(fut)
+    }
+
+    #[test]
+    fn template_to_response() {
+        block_on(async {
+            let srv = test::init_service(
+                App::new()
+                    .service(web::resource("/").to(|| async { HelloTemplate { name: "world" } })),
+            )
+            .await;
+
+            let resp = test::call_service(&srv, test::TestRequest::default().to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(
+                resp.headers().get(CONTENT_TYPE).unwrap(),
+                "text/html; charset=utf-8",
+            );
+            assert_eq!(&test::read_body(resp).await[..], b"Hello, world!");
+        });
+    }
+
+    #[test]
+    fn render_error_to_response() {
+        async fn handler() -> actix_web::Result<String> {
+            Ok(FailingTemplate.render()?)
+        }
+
+        block_on(async {
+            let srv = test::init_service(
+                App::new()
+                    .service(web::resource("/responder").to(|| async { FailingTemplate }))
+                    .service(web::resource("/error").to(handler)),
+            )
+            .await;
+
+            for uri in ["/responder", "/error"] {
+                let req = test::TestRequest::with_uri(uri).to_request();
+                let resp = test::call_service(&srv, req).await;
+                assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+                assert!(test::read_body(resp).await.is_empty());
+            }
+        });
+    }
+}
@@ -0,0 +1,78 @@
+use rinja::{DynTemplate, Template};
+
+macro_rules! test_mime_type {
+    ($path:literal, $extension:expr, $mime_type:literal) => {{
+        #[derive(Template)]
+        #[template(path = $path)]
+        struct T;
+
+        assert_eq!(T::EXTENSION, $extension);
+        assert_eq!(T::MIME_TYPE, $mime_type);
+        assert_eq!((&T as &dyn DynTemplate).extension(), $extension);
+        assert_eq!((&T as &dyn DynTemplate).mime_type(), $mime_type);
+    }};
+}
+
+#[test]
+fn test_path_mime_type() {
+    test_mime_type!("foo.html", Some("html"), "text/html; charset=utf-8");
+    test_mime_type!("size-child.txt", Some("txt"), "text/plain; charset=utf-8");
+}
+
+#[test]
+fn test_jinja_path_mime_type() {
+    test_mime_type!("foo.html.jinja", Some("html"), "text/html; charset=utf-8");
+    test_mime_type!("foo.html.rinja", Some("html"), "text/html; charset=utf-8");
+    test_mime_type!("foo.jinja", Some("jinja"), "text/html; charset=utf-8");
+}
+
+#[test]
+fn test_source_mime_type() {
+    #[derive(Template)]
+    #[template(source = "{}", ext = "xml")]
+    struct Xml;
+
+    assert_eq!(Xml::EXTENSION, Some("xml"));
+    assert_eq!(Xml::MIME_TYPE, "text/xml; charset=utf-8");
+
+    #[derive(Template)]
+    #[template(source = "{}", ext = "unknown-ext", escape = "none")]
+    struct Unknown;
+
+    assert_eq!(Unknown::EXTENSION, Some("unknown-ext"));
+    assert_eq!(Unknown::MIME_TYPE, "application/octet-stream");
+}
+
+#[test]
+fn test_mime_type_matches_escaper() {
+    #[derive(Template)]
+    #[template(source = "{{ s }}", ext = "txt.j2")]
+    struct TxtJinja<'a> {
+        s: &'a str,
+    }
+
+    assert_eq!(TxtJinja::EXTENSION, Some("txt"));
+    assert_eq!(TxtJinja::MIME_TYPE, "text/plain; charset=utf-8");
+    // The escaper is selected by the last extension, `j2`.
+    assert_eq!(TxtJinja { s: "<&>" }.render().unwrap(), "&#60;&#38;&#62;");
+
+    #[derive(Template)]
+    #[template(source = "{{ s }}", ext = "txt", escape = "html")]
+    struct EscapeHtml<'a> {
+        s: &'a str,
+    }
+
+    assert_eq!(EscapeHtml::EXTENSION, Some("html"));
+    assert_eq!(EscapeHtml::MIME_TYPE, "text/html; charset=utf-8");
+    assert_eq!(EscapeHtml { s: "<&>" }.render().unwrap(), "&#60;&#38;&#62;");
+
+    #[derive(Template)]
+    #[template(source = "{{ s }}", ext = "html", escape = "none")]
+    struct EscapeNone<'a> {
+        s: &'a str,
+    }
+
+    assert_eq!(EscapeNone::EXTENSION, Some("html"));
+    assert_eq!(EscapeNone::MIME_TYPE, "text/html; charset=utf-8");
+    assert_eq!(EscapeNone { s: "<&>" }.render().unwrap(), "<&>");
+}
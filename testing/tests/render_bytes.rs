@@ -0,0 +1,92 @@
+use std::io;
+
+use rinja::{CHUNK_SIZE, DynTemplate, Template};
+
+#[derive(Template)]
+#[template(
+    source = "{% for i in 0..count %}<li>{{ i }}: {{ text }}</li>{% endfor %}",
+    ext = "html"
+)]
+struct List<'a> {
+    count: usize,
+    text: &'a str,
+}
+
+/// An unbuffered writer that records the size of every write
+#[derive(Default)]
+struct Recorder {
+    data: Vec<u8>,
+    writes: Vec<usize>,
+}
+
+impl io::Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        self.writes.push(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_render_bytes() {
+    let tmpl = List {
+        count: 3,
+        text: "<ä>",
+    };
+    let expected = "<li>0: &#60;ä&#62;</li><li>1: &#60;ä&#62;</li><li>2: &#60;ä&#62;</li>";
+    assert_eq!(tmpl.render_bytes().unwrap(), expected.as_bytes());
+    assert_eq!(
+        (&tmpl as &dyn DynTemplate).dyn_render_bytes().unwrap(),
+        expected.as_bytes(),
+    );
+}
+
+#[test]
+fn test_write_into_is_buffered() {
+    let tmpl = List {
+        count: 5_000,
+        text: "hello",
+    };
+
+    let mut recorder = Recorder::default();
+    tmpl.write_into(&mut recorder).unwrap();
+    assert_eq!(recorder.data, tmpl.render().unwrap().as_bytes());
+
+    let (last, init) = recorder.writes.split_last().unwrap();
+    assert!(*last > 0);
+    assert!(init.iter().all(|&len| len >= CHUNK_SIZE));
+    assert!(recorder.writes.len() <= recorder.data.len() / CHUNK_SIZE + 1);
+}
+
+#[test]
+fn test_write_into_error() {
+    struct Broken;
+
+    impl io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let tmpl = List {
+        count: 5_000,
+        text: "hello",
+    };
+    let err = tmpl.write_into(&mut Broken).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+    let tmpl = List {
+        count: 1,
+        text: "hello",
+    };
+    let err = tmpl.write_into(&mut Broken).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,162 @@
+use rinja::{CHUNK_SIZE, Template};
+
+#[derive(Template)]
+#[template(
+    source = "{% for i in 0..count %}<li>{{ i }}: {{ text }}</li>{% endfor %}",
+    ext = "html"
+)]
+struct List<'a> {
+    count: usize,
+    text: &'a str,
+}
+
+#[test]
+fn test_render_chunks() {
+    let tmpl = List {
+        count: 5_000,
+        text: "<hello>",
+    };
+
+    let mut chunks = vec![];
+    tmpl.render_chunks(|chunk| {
+        chunks.push(chunk.to_owned());
+        Ok(())
+    })
+    .unwrap();
+
+    assert!(chunks.len() > 1);
+    let (last, init) = chunks.split_last().unwrap();
+    assert!(!last.is_empty());
+    for chunk in init {
+        assert!(chunk.len() >= CHUNK_SIZE);
+        // chunks end at write boundaries, never in the middle of an escape sequence
+        let tail = &chunk[chunk.len() - 4..];
+        assert!(!tail.contains('&') || tail.ends_with(';'));
+    }
+    assert_eq!(chunks.concat(), tmpl.render().unwrap());
+}
+
+#[test]
+fn test_render_chunks_small() {
+    let tmpl = List {
+        count: 2,
+        text: "hi",
+    };
+
+    let mut chunks = vec![];
+    tmpl.render_chunks(|chunk| {
+        chunks.push(chunk.to_owned());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(chunks, ["<li>0: hi</li><li>1: hi</li>"]);
+
+    #[derive(Template)]
+    #[template(source = "", ext = "txt")]
+    struct Empty;
+
+    Empty
+        .render_chunks(|_| panic!("there are no chunks in an empty template"))
+        .unwrap();
+}
+
+#[test]
+fn test_render_chunks_abort() {
+    let tmpl = List {
+        count: 5_000,
+        text: "hello",
+    };
+
+    let mut calls = 0;
+    let err = tmpl
+        .render_chunks(|_| {
+            calls += 1;
+            Err(rinja::Error::custom("connection closed"))
+        })
+        .unwrap_err();
+    assert_eq!(calls, 1);
+    assert_eq!(err.to_string(), "connection closed");
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_io {
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use tokio::io::AsyncWrite;
+
+    use super::*;
+
+    #[derive(Template)]
+    #[template(source = "{% for i in 0..count %}{{ i }},{% endfor %}", ext = "txt")]
+    struct Numbers {
+        count: usize,
+    }
+
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn test_write_into() {
+        let mut dest = Vec::new();
+        block_on(rinja::tokio::write_into(
+            &Numbers { count: 10_000 },
+            &mut dest,
+        ))
+        .unwrap();
+        assert_eq!(dest, Numbers { count: 10_000 }.render().unwrap().as_bytes());
+    }
+
+    #[test]
+    fn test_stream_into() {
+        let mut dest = Vec::new();
+        block_on(rinja::tokio::stream_into(
+            Numbers { count: 10_000 },
+            &mut dest,
+        ))
+        .unwrap();
+        assert_eq!(dest, Numbers { count: 10_000 }.render().unwrap().as_bytes());
+    }
+
+    #[test]
+    fn test_write_into_error() {
+        struct Broken;
+
+        impl AsyncWrite for Broken {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                _: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let err = block_on(rinja::tokio::write_into(
+            &Numbers { count: 10_000 },
+            &mut Broken,
+        ))
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        let err = block_on(rinja::tokio::stream_into(
+            Numbers { count: 10_000 },
+            &mut Broken,
+        ))
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
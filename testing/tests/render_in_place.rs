@@ -1,4 +1,4 @@
-use rinja::Template;
+use rinja::{DynTemplate, Template};
 
 #[test]
 fn test_render_in_place() {
@@ -38,3 +38,54 @@ fn test_render_in_place() {
         "Section 1: A=A\nB=B\nSection 2: C=C\nD=D\nSection 3 for:\n* A=1\nB=2\n* A=A\nB=B\n* A=a\nB=b\n"
     );
 }
+
+#[test]
+fn test_render_in_place_escaped() {
+    mod filters {
+        pub fn fail(msg: &str) -> rinja::Result<&str> {
+            Err(rinja::Error::custom(msg.to_owned()))
+        }
+    }
+
+    #[derive(Template)]
+    #[template(
+        source = "<p>{{ child }}</p><p>{{ child|e }}</p><p>{{ boxed }}</p>",
+        ext = "html"
+    )]
+    struct Parent<'a> {
+        child: Child<'a>,
+        boxed: Box<dyn DynTemplate>,
+    }
+
+    #[derive(Template)]
+    #[template(source = "{{ name }} & {{ name|safe }}", ext = "txt")]
+    struct Child<'a> {
+        name: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(source = "<{{ msg|fail }}>", ext = "txt")]
+    struct Failing {
+        msg: &'static str,
+    }
+
+    let t = Parent {
+        child: Child { name: "<Tom>" },
+        boxed: Box::new(Child { name: "'Jerry'" }),
+    };
+    assert_eq!(
+        t.render().unwrap(),
+        "<p>&#60;Tom&#62; &#38; &#60;Tom&#62;</p>\
+        <p>&#60;Tom&#62; &#38; &#60;Tom&#62;</p>\
+        <p>&#39;Jerry&#39; &#38; &#39;Jerry&#39;</p>",
+    );
+
+    // the error of a boxed child template is not lost in a `fmt::Formatter`
+    let t = Parent {
+        child: Child { name: "" },
+        boxed: Box::new(Failing { msg: "oops" }),
+    };
+    let err = t.render().unwrap_err();
+    assert!(matches!(err, rinja::Error::Custom(_)), "{err:?}");
+    assert_eq!(err.to_string(), "Failing.txt:1:5 in {{ msg|fail }}: oops");
+}
@@ -0,0 +1,87 @@
+use rinja::{DynTemplate, Error, Limit, Limits, Template};
+
+#[derive(Template)]
+#[template(
+    source = "{% for i in 0..rows %}{% for j in 0..cols %}{{ i * j }} {% endfor %}{% endfor %}",
+    ext = "txt"
+)]
+struct Table {
+    rows: usize,
+    cols: usize,
+}
+
+#[derive(Template)]
+#[template(source = "[{{ table }}]{{ text|center(*width) }}", ext = "txt")]
+struct Page<'a> {
+    table: Table,
+    text: &'a str,
+    width: usize,
+}
+
+const LIMITS: Limits = Limits {
+    max_bytes: 100,
+    max_loop_iterations: 20,
+};
+
+#[test]
+fn test_within_limits() {
+    let table = Table { rows: 3, cols: 4 };
+    assert_eq!(
+        table.render_with_limits(LIMITS).unwrap(),
+        "0 0 0 0 0 1 2 3 0 2 4 6 "
+    );
+    assert_eq!(
+        table.render_with_limits(Limits::default()).unwrap(),
+        table.render().unwrap(),
+    );
+}
+
+#[test]
+fn test_loop_limit() {
+    // 5 iterations of the outer loop + 5 * 4 of the inner loop
+    let table = Table { rows: 5, cols: 4 };
+    assert!(matches!(
+        table.render_with_limits(LIMITS),
+        Err(Error::LimitExceeded(Limit::LoopIterations)),
+    ));
+
+    // The budget is shared with nested templates
+    let page = Page {
+        table: Table { rows: 4, cols: 5 },
+        text: "",
+        width: 0,
+    };
+    assert!(matches!(
+        page.render_with_limits(LIMITS),
+        Err(Error::LimitExceeded(Limit::LoopIterations)),
+    ));
+
+    // The budget does not leak into later renderings
+    assert_eq!(
+        table.render().unwrap(),
+        "0 0 0 0 0 1 2 3 0 2 4 6 0 3 6 9 0 4 8 12 "
+    );
+}
+
+#[test]
+fn test_byte_limit() {
+    let page = Page {
+        table: Table { rows: 1, cols: 1 },
+        text: "x",
+        width: 1_000,
+    };
+    let err = page.render_with_limits(LIMITS).unwrap_err();
+    assert!(matches!(err, Error::LimitExceeded(Limit::Bytes)));
+    assert_eq!(
+        err.to_string(),
+        "the rendered output exceeds the byte limit"
+    );
+
+    let page = Page { width: 10, ..page };
+    assert_eq!(
+        (&page as &dyn DynTemplate)
+            .dyn_render_with_limits(LIMITS)
+            .unwrap(),
+        "[0 ]    x     ",
+    );
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "sanitize")]
+
+use rinja::Template;
+
+#[derive(Template)]
+#[template(source = "<div>{{ comment|sanitize }}</div>", ext = "html")]
+struct Comment<'a> {
+    comment: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "{{ comment|sanitize }}",
+    ext = "html",
+    config = "sanitize.toml"
+)]
+struct ConfiguredComment<'a> {
+    comment: &'a str,
+}
+
+#[derive(Template)]
+#[template(source = "{{ comment|sanitize }}", ext = "txt")]
+struct TextComment<'a> {
+    comment: &'a str,
+}
+
+#[test]
+fn test_sanitize() {
+    let comment = r#"<P onclick="x()">1 < 2 & <em>3</em><script>alert(1)</script><a href=" javascript:x()">y"#;
+    assert_eq!(
+        Comment { comment }.render().unwrap(),
+        "<div><p>1 &#60; 2 &#38; <em>3</em><a>y</a></p></div>"
+    );
+    assert_eq!(
+        ConfiguredComment { comment }.render().unwrap(),
+        "<p>1 &#60; 2 &#38; 3y</p>"
+    );
+    assert_eq!(
+        TextComment { comment }.render().unwrap(),
+        "<p>1 &#60; 2 &#38; <em>3</em><a>y</a></p>"
+    );
+}
+
+#[test]
+fn test_sanitize_configured_attributes() {
+    let comment =
+        r#"<img src="/a.png" alt='"a"' title="t" onerror="x()"><img src="data:image/png,x">"#;
+    assert_eq!(
+        ConfiguredComment { comment }.render().unwrap(),
+        r#"<img src="/a.png" alt="&#34;a&#34;"><img>"#
+    );
+}
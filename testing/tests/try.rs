@@ -18,7 +18,7 @@ fn test_int_parser() {
     assert!(matches!(template.render(), Err(rinja::Error::Custom(_))));
     assert_eq!(
         format!("{}", &template.render().unwrap_err()),
-        "invalid digit found in string"
+        "IntParserTemplate.txt:1:12 in {% let v = self.parse()? %}: invalid digit found in string"
     );
 
     let template = IntParserTemplate { s: "100" };
@@ -47,7 +47,7 @@ fn fail_fmt() {
     assert!(matches!(template.render(), Err(rinja::Error::Custom(_))));
     assert_eq!(
         format!("{}", &template.render().unwrap_err()),
-        format!("{}", std::fmt::Error)
+        format!("FailFmt.txt:1:4 in {{{{ value()? }}}}: {}", std::fmt::Error)
     );
 
     let template = FailFmt {
@@ -76,8 +76,99 @@ fn fail_str() {
 
     let template = FailStr { value: false };
     assert!(matches!(template.render(), Err(rinja::Error::Custom(_))));
-    assert_eq!(format!("{}", &template.render().unwrap_err()), "FAIL");
+    assert_eq!(
+        format!("{}", &template.render().unwrap_err()),
+        "FailStr.txt:1:4 in {{ value()? }}: FAIL"
+    );
 
     let template = FailStr { value: true };
     assert_eq!(template.render().unwrap(), "hello world");
 }
+
+#[test]
+fn error_location() {
+    mod filters {
+        pub fn checked_total(items: &[u32]) -> rinja::Result<u32> {
+            items
+                .iter()
+                .try_fold(0u32, |acc, &item| acc.checked_add(item))
+                .ok_or_else(|| rinja::Error::custom("overflow"))
+        }
+    }
+
+    #[derive(Template)]
+    #[template(path = "try-location.html")]
+    struct Invoice<'a> {
+        items: &'a [u32],
+        discount: &'a str,
+    }
+
+    impl Invoice<'_> {
+        fn discount(&self) -> Result<u32, std::num::ParseIntError> {
+            self.discount.parse()
+        }
+    }
+
+    let template = Invoice {
+        items: &[1, 2, 3],
+        discount: "1",
+    };
+    assert_eq!(
+        template.render().unwrap(),
+        "<p>Total: 6</p>\n<p>Discount: 1</p>"
+    );
+
+    let template = Invoice {
+        items: &[1, u32::MAX],
+        discount: "1",
+    };
+    let err = template.render().unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.path(), "templates/try-location.html");
+    assert_eq!(location.row(), 1);
+    assert_eq!(location.column(), 14);
+    assert_eq!(location.source(), "{{ items|checked_total }}");
+    assert_eq!(
+        err.to_string(),
+        "templates/try-location.html:1:14 in {{ items|checked_total }}: overflow",
+    );
+
+    let template = Invoice {
+        items: &[1, 2, 3],
+        discount: "🐢",
+    };
+    let err = template.render().unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.row(), 2);
+    assert_eq!(location.column(), 17);
+    assert_eq!(location.source(), "{{ discount()? }}");
+    assert!(std::error::Error::source(&err).is_some());
+    assert!(err.is::<std::num::ParseIntError>());
+
+    let rinja::Error::Custom(custom) = &err else {
+        panic!("expected a custom error");
+    };
+    let located = custom.downcast_ref::<rinja::Located>().unwrap();
+    assert_eq!(located.location(), location);
+    assert!(located.inner().is::<std::num::ParseIntError>());
+}
+
+#[test]
+fn located_io_error() {
+    #[derive(Template)]
+    #[template(source = "{{ value()? }}", ext = "txt")]
+    struct FailIo;
+
+    impl FailIo {
+        fn value(&self) -> std::io::Result<&'static str> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    let err = FailIo.render().unwrap_err();
+    assert!(err.location().is_some());
+    assert_eq!(err.into_io_error().kind(), std::io::ErrorKind::BrokenPipe);
+
+    let err = FailIo.write_into(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,22 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{% for row in items|batch %}{% endfor %}"#)]
+struct BatchWithoutSize {
+    items: Vec<i32>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{% for row in items|batch(2, fill=0) %}{% endfor %}"#)]
+struct BatchUnknownArgument {
+    items: Vec<i32>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{% for column in items|slice(2, 0) %}{% endfor %}"#)]
+struct SliceWithFill {
+    items: Vec<i32>,
+}
+
+fn main() {
+}
@@ -0,0 +1,75 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(
+    source = "<a href={{ url }}>",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct UnquotedAttribute<'a> {
+    url: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "<div {{ attrs }}>",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct InTag<'a> {
+    attrs: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "<script>let x = {{ x }};</script>",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct JsCode<'a> {
+    x: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "<script>let x = `{{ x }}`;</script>",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct JsTemplateLiteral<'a> {
+    x: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "<script>let x = /'/.test(y) ? '{{ x }}' : z;</script>",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct JsRegex<'a> {
+    x: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "<p{% if cond %} title=\"{% endif %}>{{ x }}",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct DifferentBranches<'a> {
+    cond: bool,
+    x: &'a str,
+}
+
+#[derive(Template)]
+#[template(
+    source = "{% for x in xs %}<script>{% endfor %}{{ x }}",
+    ext = "html",
+    config = "context-aware-escaping.toml"
+)]
+struct LoopChangesContext<'a> {
+    xs: &'a [&'a str],
+    x: &'a str,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+#![cfg(any(feature = "chrono", feature = "time"))]
+
+use rinja::Template;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ day|date("%Y-%m-%Q") }}"#)]
+struct UnknownSpecifier {
+    day: time::Date,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ moment|datetime("100%") }}"#)]
+struct TrailingPercent {
+    moment: time::PrimitiveDateTime,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ day|timesince(now, 1) }}"#)]
+struct TooManyArgs {
+    day: time::Date,
+    now: time::Date,
+}
+
+fn main() {
+}
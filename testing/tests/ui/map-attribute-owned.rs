@@ -0,0 +1,18 @@
+use rinja::Template;
+
+struct Task {
+    name: String,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ self.tasks()|map(attribute="name")|join(", ") }}"#)]
+struct OwnedItems;
+
+impl OwnedItems {
+    fn tasks(&self) -> Vec<Task> {
+        vec![Task { name: "write".into() }]
+    }
+}
+
+fn main() {
+}
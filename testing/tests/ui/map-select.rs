@@ -0,0 +1,28 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|select("positive")|length }}"#)]
+struct UnknownTest {
+    items: Vec<i32>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|map|length }}"#)]
+struct MapWithoutFunction {
+    items: Vec<i32>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|map("upper")|join(", ") }}"#)]
+struct MapWithFilterName {
+    items: Vec<&'static str>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|groupby|length }}"#)]
+struct GroupbyWithoutAttribute {
+    items: Vec<i32>,
+}
+
+fn main() {
+}
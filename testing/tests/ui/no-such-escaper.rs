@@ -3,7 +3,7 @@ use rinja::Template;
 #[derive(Template)]
 #[template(
     ext = "html",
-    source = r#"In LaTeX you write `{{text}}` like `{{text|escape("latex")}}`."#,
+    source = r#"In RTF you write `{{text}}` like `{{text|escape("rtf")}}`."#,
 )]
 struct LocalEscaper<'a> {
     text: &'a str,
@@ -11,7 +11,7 @@ struct LocalEscaper<'a> {
 
 #[derive(Template)]
 #[template(
-    ext = "tex",
+    ext = "rtf",
     source = r#"In HTML you write `{{text}}` like `{{text|escape("html")}}`."#,
 )]
 struct GlobalEscaper<'a> {
@@ -19,7 +19,7 @@ struct GlobalEscaper<'a> {
 }
 
 #[derive(Template)]
-#[template(path = "latex-file.tex")]
+#[template(path = "rtf-file.rtf")]
 struct NoSuchEscaper;
 
 fn main() {
@@ -0,0 +1,32 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|numberformat(locale="xx") }}"#)]
+struct UnknownLocale;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|currency("euro") }}"#)]
+struct InvalidCurrency;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|currency }}"#)]
+struct MissingCurrency;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|percent(precision=2) }}"#)]
+struct UnknownArgument;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|percent(2, decimals=2) }}"#)]
+struct DuplicateArgument;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ 1|percent(2, "en", 3) }}"#)]
+struct TooManyArguments;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ "a"|truncate(length=2) }}"#)]
+struct NoNamedArguments;
+
+fn main() {
+}
@@ -0,0 +1,23 @@
+use rinja::Template;
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|sort(attribute="user-name")|length }}"#)]
+struct InvalidAttribute {
+    items: Vec<u8>,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|sort(attribute=field)|length }}"#)]
+struct NoLiteral {
+    items: Vec<u8>,
+    field: &'static str,
+}
+
+#[derive(Template)]
+#[template(ext = "txt", source = r#"{{ items|first(1) }}"#)]
+struct FirstWithArgument {
+    items: Vec<u8>,
+}
+
+fn main() {
+}